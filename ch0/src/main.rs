@@ -1,62 +1,34 @@
+use circuits::analysis::Response;
+use circuits::boolean::{discretize, Attractor, BooleanNetwork, Update};
+use circuits::continuation::{Branch, Continuation};
+use circuits::gene_network::{Gene, GeneNetwork};
+use circuits::models::{
+    unregulated, Autorepressor, FeedForwardLoop, FflType, NegativeAutoregulation, ToggleSwitch,
+};
+use circuits::motifs::{Motifs, TranscriptionNetwork};
+use circuits::phase_plane::{PhasePlane, Portrait};
+use circuits::regulation::{Hill, Logic};
+use circuits::sbml::{self, ToSbml};
+use circuits::signal::Signal;
+use circuits::stiff::Stiff;
+use circuits::trajectory::{uniform, Trajectory};
+use circuits::{Model, State};
 use ode_solvers::*;
 use plotters::prelude::*;
-
-type State = Vector1<f64>;
-type Time = f64;
-
-fn autorepressive(
-    x: f64,
-    t: f64,
-    beta0: f64,
-    gamma: f64,
-    k: f64,
-    n: f64,
-    ks: f64,
-    ns: f64,
-    s: f64,
-) -> f64 {
-    beta0 * (s / ks).powf(ns) / (1.0 + (s / ks).powf(ns)) / (1.0 + (x / k).powf(n)) - gamma * x
-}
-
-fn unregulated(t: f64, beta0: f64, gamma: f64, k: f64, n: f64, ks: f64, ns: f64, s: f64) -> f64 {
-    beta0 / gamma * (1.0 - (-gamma * t).exp())
-}
-
-struct Cs {
-    beta0: f64,
-    gamma: f64,
-    k: f64,
-    n: f64,
-    ks: f64,
-    ns: f64,
-    s: f64,
-}
-
-impl ode_solvers::System<f64, State> for Cs {
-    fn system(&self, t: Time, y: &State, dy: &mut State) {
-        dy[0] = autorepressive(
-            y[0], t, self.beta0, self.gamma, self.k, self.n, self.ks, self.ns, self.s,
-        );
-    }
-}
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
 fn main() {
     let step_size = 0.05;
-    let y0 = State::new(0.0);
-
-    let system = Cs {
-        beta0: 100.0,
-        gamma: 1.0,
-        k: 1.0,
-        n: 1.0,
-        ns: 10.0,
-        ks: 0.1,
-        s: 100.0,
-    };
-    // 0-10 200
-    let mut stepper = Rk4::new(system, 0.0, y0, 10.0, step_size);
-    let res = stepper.integrate();
+    let system = Autorepressor::default();
+    let y0: State = system.initial_state();
+    let name = system.name().to_string();
+    let mut stepper = Rk4::new(system.clone(), 0.0, y0, 10.0, step_size);
+    stepper.integrate().expect("failed integration");
     let (x_out, y_out) = stepper.results().get();
+    let trajectory = Trajectory::from_steps(&system, x_out, y_out);
+    // Simulation and analytic solution on the same time axis.
+    let times = uniform(0.0, 10.0, 201);
 
     let root = BitMapBackend::new("myplot.png", (640, 480)).into_drawing_area();
     root.fill(&WHITE).unwrap();
@@ -71,33 +43,553 @@ fn main() {
 
     chart.configure_mesh().draw().unwrap();
 
-    let points = x_out
+    let points = times
         .iter()
-        .zip(y_out.iter())
-        .map(|(x, y)| (*x, y[0]))
+        .zip(trajectory.sample(&times))
+        .map(|(t, y)| (*t, y[0]))
         .collect::<Vec<(f64, f64)>>();
     chart
         .draw_series(LineSeries::new(points, &BLUE))
         .unwrap()
-        .label("autorepressive")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
+        .label(name)
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
 
-    let points = (0..200)
-        .map(|t| t as f64 * 0.05)
-        .map(|t| (t, unregulated(t, 100.0, 1.0, 1.0, 1.0, 10.0, 0.1, 100.0)))
+    let points = times
+        .iter()
+        .map(|t| (*t, unregulated(*t, system.beta0, system.gamma)))
         .collect::<Vec<(f64, f64)>>();
     chart
         .draw_series(LineSeries::new(points, &RED))
         .unwrap()
         .label("unregulated")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+
+    root.present().unwrap();
+
+    sbml::write_file("autorepressor.xml", &system.to_sbml()).unwrap();
+
+    on_off(step_size);
+    feed_forward();
+    boolean(&gene_network());
+    response_times(step_size / 50.0);
+    bifurcations();
+
+    let portrait = PhasePlane::new((0.0, 1.0), (0.0, 4.0))
+        .t_end(25.0)
+        .portrait(&NegativeAutoregulation::default());
+    phase_plot(&portrait, "phaseplane.png");
+
+    // A transcription network given as edge list or GraphML, such as Alon's E. coli network.
+    match std::env::args().nth(1) {
+        Some(path) => motifs(&path),
+        None => println!("Pass a network edge list or GraphML file to count its motifs."),
+    }
+}
+
+/// Network motifs of three and four genes, against 100 degree-preserving randomizations.
+fn motifs(path: &str) {
+    let network = if path.ends_with(".graphml") || path.ends_with(".xml") {
+        TranscriptionNetwork::from_graphml(path)
+    } else {
+        TranscriptionNetwork::from_edge_list(path)
+    }
+    .expect("unreadable network");
+    println!(
+        "{}: {} genes, {} interactions",
+        path,
+        network.node_count(),
+        network.edge_count()
+    );
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    for size in [3, 4] {
+        let scores = Motifs::new(size)
+            .run(&network, &mut rng)
+            .expect("subgraphs of three or four genes");
+        for score in scores.iter().filter(|s| s.is_motif(2.0)) {
+            println!(
+                "{}: {} found, {:.1} +- {:.1} at random, Z = {:.1}",
+                score.subgraph, score.count, score.random_mean, score.random_std, score.z_score
+            );
+        }
+    }
+    for (kind, count) in network.ffl_types() {
+        println!("{:?}-FFL: {}", kind, count);
+    }
+    for (regulator, targets) in network.single_input_modules(3) {
+        println!("SIM of {}: {}", regulator, targets.join(", "));
+    }
+}
+
+/// Feed-forward loops after `Sx` switches on at t = 0: the delay of the coherent type-1 loop
+/// with AND logic and the pulse of the incoherent type-1 loop, next to simple regulation.
+fn feed_forward() {
+    for logic in [Logic::And, Logic::Or] {
+        for kind in FflType::ALL {
+            let ffl = FeedForwardLoop::new(kind, logic);
+            let delays = ffl.delays().expect("failed integration");
+            let pulse = ffl.pulse(0.05).expect("failed integration");
+            println!(
+                "{:?}-FFL {:?}: Z t1/2 on = {:.3}, off = {:.3} (simple {:.3}){}",
+                kind,
+                logic,
+                delays.on,
+                delays.off,
+                delays.reference,
+                pulse.map_or(String::new(), |p| format!(
+                    ", pulse of {:.3} at t = {:.2}, {:.0}% adapted",
+                    p.amplitude(),
+                    p.t_peak,
+                    100.0 * p.adaptation()
+                ))
+            );
+        }
+    }
+
+    let t_end = 5.0;
+    let times = uniform(0.0, t_end, 501);
+    let root = BitMapBackend::new("ffl.png", (640, 480)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Feed-forward loops", ("sans-serif", 50).into_font())
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(0.0f64..t_end, 0.0f64..2.0f64)
+        .unwrap();
+    chart.configure_mesh().draw().unwrap();
+
+    for (label, kind, color) in [
+        ("C1-FFL AND", FflType::C1, BLUE),
+        ("I1-FFL AND", FflType::I1, GREEN),
+    ] {
+        let ffl = FeedForwardLoop::new(kind, Logic::And);
+        let z_st = ffl.steady_state(1.0, 1.0)[1];
+        let trajectory = ffl.sx_step(true, t_end).expect("failed integration");
+        let points = times
+            .iter()
+            .zip(trajectory.sample(&times))
+            .map(|(t, y)| (*t, y[1] / z_st));
+        chart
+            .draw_series(LineSeries::new(points, &color))
+            .unwrap()
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    let simple = times.iter().map(|t| (*t, unregulated(*t, 1.0, 1.0)));
+    chart
+        .draw_series(LineSeries::new(simple, &RED))
+        .unwrap()
+        .label("simple regulation")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+    root.present().unwrap();
+}
+
+/// The toggle switch assembled from its regulatory graph, against the hand-written model.
+fn gene_network() -> GeneNetwork {
+    let toggle = ToggleSwitch::default();
+    let network = GeneNetwork::new("Toggle switch")
+        .gene(Gene::new("u").beta(toggle.alpha1).initial(toggle.alpha1))
+        .and_then(|n| n.gene(Gene::new("v").beta(toggle.alpha2)))
+        .and_then(|n| n.regulation("v", "u", Hill::repression(1.0, toggle.beta)))
+        .and_then(|n| n.regulation("u", "v", Hill::repression(1.0, toggle.gamma)))
+        .expect("valid gene names");
+    let t_end = 20.0;
+    let y0 = State::from_vec(vec![0.0, 1.0]);
+    let generated = solve(network.clone(), y0.clone(), t_end);
+    let written = solve(toggle, y0, t_end);
+    let times = uniform(0.0, t_end, 201);
+    let difference = generated
+        .sample(&times)
+        .iter()
+        .zip(written.sample(&times))
+        .map(|(a, b)| (a - b).amax())
+        .fold(0.0, f64::max);
+    println!(
+        "Generated toggle switch: {} parameters, largest difference from ToggleSwitch {:.2e}",
+        network.parameters().len(),
+        difference
+    );
+    network
+}
+
+/// Boolean abstractions of the toggle switch and of a protein-only repressilator, with the
+/// stable states of the continuous toggle on the same footing.
+fn boolean(toggle: &GeneNetwork) {
+    let repressilator = ["lacI", "tetR", "cI"]
+        .iter()
+        .try_fold(GeneNetwork::new("Repressilator"), |network, gene| {
+            network.gene(Gene::new(gene).beta(10.0))
+        })
+        .and_then(|n| n.regulation("cI", "lacI", Hill::repression(1.0, 3.0)))
+        .and_then(|n| n.regulation("lacI", "tetR", Hill::repression(1.0, 3.0)))
+        .and_then(|n| n.regulation("tetR", "cI", Hill::repression(1.0, 3.0)))
+        .expect("valid gene names");
+    for network in [toggle, &repressilator] {
+        let abstraction = BooleanNetwork::from_gene_network(network);
+        let rules: Vec<String> = (0..abstraction.len())
+            .map(|i| {
+                let rule = abstraction
+                    .rule_of(i)
+                    .map_or("input".to_string(), |r| r.to_string());
+                format!("{} = {}", abstraction.names()[i], rule)
+            })
+            .collect();
+        println!("Boolean {}: {}", network.name(), rules.join(", "));
+        for update in [Update::Synchronous, Update::Asynchronous] {
+            let attractors = abstraction.attractors(update).expect("a small network");
+            let described: Vec<String> = attractors.iter().map(|a| describe(a, update)).collect();
+            println!("  {:?} attractors: {}", update, described.join("; "));
+        }
+    }
+    let stable: Vec<String> = ToggleSwitch::default()
+        .steady_states()
+        .iter()
+        .filter(|p| p.stability.is_stable())
+        .map(|p| bits(&discretize(toggle, &p.state).expect("one level per gene")))
+        .collect();
+    println!("  stable states of the Hill model: {}", stable.join(", "));
+}
+
+fn bits(state: &[bool]) -> String {
+    state.iter().map(|on| if *on { '1' } else { '0' }).collect()
+}
+
+/// States of an attractor, as a cycle for synchronous updates and as a set otherwise.
+fn describe(attractor: &Attractor, update: Update) -> String {
+    let states: Vec<String> = attractor.states.iter().map(|s| bits(s)).collect();
+    let states = match update {
+        _ if attractor.is_fixed_point() => states[0].clone(),
+        Update::Synchronous => states.join(" -> "),
+        _ => format!("{{{}}}", states.join(", ")),
+    };
+    format!("{} (basin {:.0}%)", states, 100.0 * attractor.basin)
+}
+
+fn solve<M: Model>(model: M, y0: State, t_end: f64) -> Trajectory {
+    let mut stepper = Stiff::new(model, 0.0, t_end, 0.0, y0, 1e-8, 1e-8);
+    stepper.integrate().expect("failed integration");
+    stepper.trajectory().clone()
+}
+
+/// Alon's on/off experiment: the input is switched on at t = 0 and off again at t = 10.
+fn on_off(step_size: f64) {
+    let (t_off, t_end) = (10.0, 20.0);
+    let input = Signal::Pulse {
+        t_on: 0.0,
+        t_off,
+        low: 0.0,
+        high: 100.0,
+    };
+    let regulated = Autorepressor {
+        s: input.clone(),
+        ..Default::default()
+    };
+    // Without repression the response is that of simple regulation.
+    let simple = Autorepressor {
+        k: f64::INFINITY,
+        s: input,
+        ..Default::default()
+    };
+
+    let root = BitMapBackend::new("onoff.png", (640, 480)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Input on/off dynamics", ("sans-serif", 50).into_font())
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(0.0f64..t_end, 0.0f64..1.0f64)
+        .unwrap();
+    chart.configure_mesh().draw().unwrap();
+
+    for (label, system, color) in [
+        ("negative autoregulation", regulated, BLUE),
+        ("simple regulation", simple, RED),
+    ] {
+        let y0 = system.initial_state();
+        let mut stepper = Rk4::new(system, 0.0, y0, t_end, step_size);
+        stepper.integrate().expect("failed integration");
+        let (t, y) = stepper.results().get();
+        let x: Vec<f64> = y.iter().map(|y| y[0]).collect();
+        let i_off = t.partition_point(|t| *t < t_off);
+        let on = Response::new(&t[..i_off], &x[..i_off]).expect("no turn-on response");
+        let off = Response::new(&t[i_off..], &x[i_off..]).expect("no turn-off response");
+        println!(
+            "{}: turn-on t1/2 = {:.3}, turn-off t1/2 = {:.3}",
+            label, on.rise_time, off.rise_time
+        );
+
+        let points = t.iter().zip(&x).map(|(t, x)| (*t, x / on.steady_state));
+        chart
+            .draw_series(LineSeries::new(points, &color))
+            .unwrap()
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
+    root.present().unwrap();
+}
+
+/// Rise time of negative autoregulation relative to simple regulation, `ln 2 / gamma`, as a
+/// function of the repression strength `beta0 / k`.
+///
+/// Strong repression makes the rise much faster than the output grid, so the integration uses
+/// adaptive Dopri5 steps and only samples the dense output every `dx`.
+fn response_times(dx: f64) {
+    let t_end = 10.0;
+    let mut rows = Vec::new();
+    for n in [1.0, 2.0, 4.0] {
+        for beta0 in [50.0, 100.0, 200.0] {
+            for i in 0..=16 {
+                let k = 10f64.powf(-2.0 + i as f64 * 0.25);
+                let system = Autorepressor {
+                    beta0,
+                    k,
+                    n,
+                    ..Default::default()
+                };
+                let unregulated = std::f64::consts::LN_2 / system.gamma;
+                let y0 = system.initial_state();
+                let mut stepper = Dopri5::new(system, 0.0, t_end, dx, y0, 1e-8, 1e-8);
+                stepper.integrate().expect("failed integration");
+                let x: Vec<f64> = stepper.y_out().iter().map(|y| y[0]).collect();
+                let response = Response::new(stepper.x_out(), &x).expect("no response");
+                rows.push((beta0, k, n, response, response.rise_time / unregulated));
+            }
+        }
+    }
+
+    let mut csv =
+        String::from("beta0,k,n,steady_state,rise_time,overshoot,settling_time,relative\n");
+    println!(
+        "{:>8} {:>8} {:>4} {:>12} {:>10} {:>10} {:>10} {:>9}",
+        "beta0", "k", "n", "steady", "t1/2", "overshoot", "settling", "relative"
+    );
+    for (beta0, k, n, r, relative) in &rows {
+        println!(
+            "{:>8.1} {:>8.3} {:>4.0} {:>12.4} {:>10.4} {:>10.4} {:>10.4} {:>9.4}",
+            beta0, k, n, r.steady_state, r.rise_time, r.overshoot, r.settling_time, relative
+        );
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            beta0, k, n, r.steady_state, r.rise_time, r.overshoot, r.settling_time, relative
+        ));
+    }
+    std::fs::write("response_times.csv", csv).unwrap();
+
+    let root = BitMapBackend::new("response_times.png", (640, 480)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            "Response time vs repression",
+            ("sans-serif", 40).into_font(),
+        )
+        .margin(5)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d((0.1f64..1e5f64).log_scale(), 0.0f64..1.1f64)
+        .unwrap();
+    chart
+        .configure_mesh()
+        .x_desc("repression strength beta0 / k")
+        .y_desc("t1/2 relative to simple regulation")
+        .draw()
+        .unwrap();
+
+    for (n, color) in [(1.0, BLUE), (2.0, RED), (4.0, GREEN)] {
+        let points = rows
+            .iter()
+            .filter(|row| row.2 == n)
+            .map(|(beta0, k, _, _, relative)| (beta0 / k, *relative));
+        chart
+            .draw_series(points.map(|p| Circle::new(p, 3, color.filled())))
+            .unwrap()
+            .label(format!("n = {}", n))
+            .legend(move |(x, y)| Circle::new((x + 10, y), 3, color.filled()));
+    }
 
     chart
         .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .draw()
         .unwrap();
+    root.present().unwrap();
+}
 
+/// Equilibrium branches of the autorepressor in the Hill coefficient and the repression
+/// threshold, written to `bifurcation_<parameter>.csv` and `.png`.
+fn bifurcations() {
+    let system = Autorepressor::default();
+    for (parameter, min, max) in [("n", 0.5, 8.0), ("k", 0.1, 10.0)] {
+        let branch = Continuation::new(parameter, min, max)
+            .run(&system, &system.initial_state())
+            .expect("failed continuation");
+        for bifurcation in &branch.bifurcations {
+            println!(
+                "{:?} at {} = {:.4}",
+                bifurcation.kind, parameter, bifurcation.parameter
+            );
+        }
+        branch
+            .write_csv(format!("bifurcation_{}.csv", parameter))
+            .unwrap();
+        bifurcation_plot(&branch, min, max);
+    }
+}
+
+/// Steady state of the first species against the parameter, blue where it is stable and red
+/// where it is not.
+fn bifurcation_plot(branch: &Branch, min: f64, max: f64) {
+    let path = format!("bifurcation_{}.png", branch.parameter);
+    let top = branch.points.iter().map(|p| p.state[0]).fold(0.0, f64::max);
+    let root = BitMapBackend::new(&path, (640, 480)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            format!("Steady state vs {}", branch.parameter),
+            ("sans-serif", 40).into_font(),
+        )
+        .margin(5)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(min..max, 0.0..1.1 * top)
+        .unwrap();
+    chart
+        .configure_mesh()
+        .x_desc(branch.parameter.as_str())
+        .y_desc(branch.species[0].as_str())
+        .draw()
+        .unwrap();
+    chart
+        .draw_series(branch.points.windows(2).map(|w| {
+            let color = if w[1].stability.is_stable() {
+                BLUE
+            } else {
+                RED
+            };
+            PathElement::new(
+                vec![
+                    (w[0].parameter, w[0].state[0]),
+                    (w[1].parameter, w[1].state[0]),
+                ],
+                color.stroke_width(2),
+            )
+        }))
+        .unwrap();
+    chart
+        .draw_series(
+            branch
+                .bifurcations
+                .iter()
+                .map(|b| Cross::new((b.parameter, b.state[0]), 6, BLACK.stroke_width(2))),
+        )
+        .unwrap();
+    root.present().unwrap();
+}
+
+/// Direction field, nullclines, trajectories and fixed points of a phase portrait.
+fn phase_plot(portrait: &Portrait, path: &str) {
+    let (x0, x1) = portrait.x_range;
+    let (y0, y1) = portrait.y_range;
+    let root = BitMapBackend::new(path, (640, 640)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Phase plane", ("sans-serif", 40).into_font())
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(x0..x1, y0..y1)
+        .unwrap();
+    chart
+        .configure_mesh()
+        .x_desc(portrait.x_label.as_str())
+        .y_desc(portrait.y_label.as_str())
+        .draw()
+        .unwrap();
+
+    // Arrows of equal length in screen units, a fifth of the field spacing.
+    let field = (portrait.arrows.len() as f64).sqrt();
+    let (sx, sy) = ((x1 - x0) / field / 5.0, (y1 - y0) / field / 5.0);
+    chart
+        .draw_series(portrait.arrows.iter().map(|a| {
+            let (u, v) = (a.dx / sx, a.dy / sy);
+            let norm = u.hypot(v).max(f64::MIN_POSITIVE);
+            let (u, v) = (2.0 * u / norm, 2.0 * v / norm);
+            let tip = (a.x + sx * u, a.y + sy * v);
+            // Barbs of the head at 150 degrees from the shaft.
+            let barb = |c: f64, s: f64| {
+                (
+                    tip.0 + sx * 0.4 * (c * u - s * v),
+                    tip.1 + sy * 0.4 * (s * u + c * v),
+                )
+            };
+            let (c, s) = (-(3f64.sqrt()) / 2.0, 0.5);
+            PathElement::new(
+                vec![(a.x, a.y), tip, barb(c, s), tip, barb(c, -s)],
+                BLACK.mix(0.4),
+            )
+        }))
+        .unwrap();
+    for trajectory in &portrait.trajectories {
+        chart
+            .draw_series(LineSeries::new(trajectory.clone(), GREEN.mix(0.7)))
+            .unwrap();
+    }
+    for (segments, color, label) in [
+        (&portrait.x_nullcline, BLUE, &portrait.x_label),
+        (&portrait.y_nullcline, RED, &portrait.y_label),
+    ] {
+        chart
+            .draw_series(
+                segments
+                    .iter()
+                    .map(|s| PathElement::new(s.to_vec(), color.stroke_width(2))),
+            )
+            .unwrap()
+            .label(format!("{} nullcline", label))
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    chart
+        .draw_series(portrait.fixed_points.iter().map(|p| {
+            let style = if p.stability.is_stable() {
+                BLACK.filled()
+            } else {
+                WHITE.filled()
+            };
+            Circle::new(
+                (p.state[portrait.axes.0], p.state[portrait.axes.1]),
+                6,
+                style,
+            )
+        }))
+        .unwrap();
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()
+        .unwrap();
     root.present().unwrap();
 }
@@ -1,7 +1,10 @@
 use dioxus::prelude::*;
 use dioxus_logger::tracing::{info, Level};
-use ode_solvers::dopri5::*;
-use ode_solvers::*;
+use ode_solvers::{Dopri5, OutputType};
+
+use circuits::models::Unregulated;
+use circuits::trajectory::{uniform, Trajectory};
+use circuits::Model;
 
 use charming::{
     component::{
@@ -15,56 +18,34 @@ use charming::{
     series::{Line, Scatter},
     Chart, ChartResize, HtmlRenderer, WasmRenderer,
 };
-#[derive(Copy, Clone, Debug)]
-struct Model {
-    beta_m: f64,
-    gamma_m: f64,
-    beta_p: f64,
-    gamma_p: f64,
-}
-
-type State = Vector2<f64>;
-type Time = f64;
-
-impl ode_solvers::System<f64, State> for Model {
-    // x(t) =  (self.beta / self.gamma) * (1.0- (-self.gamma*_t).exp());
-    fn system(&self, _t: Time, x: &State, dx: &mut State) {
-        dx[0] = self.beta_m - self.gamma_m * x[0];
-        dx[1] = self.beta_p * x[0] - self.gamma_p * x[1];
-    }
-}
 
 #[component]
 pub fn LineChart1() -> Element {
     let chart = use_signal(|| {
-        let system = Model {
-            beta_m: 1.0,
-            gamma_m: 1.0,
-            beta_p: 1.0,
-            gamma_p: 0.1,
-        };
-        let x = State::new(0.0, 0.0);
+        let system = Unregulated::default();
+        let x = system.initial_state();
         let t = 0.0;
         let t_end = 50.0;
-        let dt = 0.0015; // Step size to get ~4000 points
+        let times = uniform(t, t_end, 4001);
 
-        let mut stepper = Dopri5::new(system, t, t_end, dt, x, 1e-6_f64, 1e-6_f64);
+        let mut stepper = Dopri5::new(system, t, t_end, 0.0, x, 1e-6_f64, 1e-6_f64);
+        stepper.set_output(OutputType::Sparse);
         stepper.integrate().expect("failed integration");
+        let trajectory = Trajectory::from_steps(&system, stepper.x_out(), stepper.y_out());
         //let t0 = 1.0 / system.gamma;
         //let x0 = system.beta / system.gamma * (1.0 - (-1.0_f64).exp());
-        let series_m = stepper
-            .x_out()
-            .iter()
-            .zip(stepper.y_out().iter())
-            .map(|(x, y)| vec![*x, y[0]])
-            .collect();
-        let series_p = stepper
-            .x_out()
+        let series = system
+            .species()
             .iter()
-            .zip(stepper.y_out().iter())
-            .map(|(x, y)| vec![*x, y[1]])
-            .collect();
-        Chart::new()
+            .enumerate()
+            .map(|(i, name)| {
+                Line::new()
+                    .data(trajectory.series(i, &times))
+                    .show_symbol(false)
+                    .name(format!("Concentration {}", name))
+            })
+            .collect::<Vec<_>>();
+        let chart = Chart::new()
             //.title(Title::new().text("Unregulated Expression").item_gap(25))
             .legend(Legend::new())
             .x_axis(
@@ -85,19 +66,8 @@ pub fn LineChart1() -> Element {
                     .name_gap(25)
                     .name_location(NameLocation::Middle),
             )
-            .series(
-                Line::new()
-                    .data(series_m) // blue
-                    .show_symbol(false)
-                    .name("Concentration m"),
-            )
-            .series(
-                Line::new()
-                    .data(series_p)
-                    .show_symbol(false)
-                    .name("Concentration p"),
-            )
-            .data_zoom(DataZoom::new().type_(DataZoomType::Inside).realtime(true))
+            .data_zoom(DataZoom::new().type_(DataZoomType::Inside).realtime(true));
+        series.into_iter().fold(chart, |chart, line| chart.series(line))
     });
     let renderer = use_signal(|| WasmRenderer::new(600, 400));
     let mut echarts = use_signal(|| None);
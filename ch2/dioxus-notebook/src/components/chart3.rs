@@ -1,7 +1,5 @@
 use dioxus::prelude::*;
 use dioxus_logger::tracing::{info, Level};
-use ode_solvers::dopri5::*;
-use ode_solvers::*;
 
 use charming::{
     component::{
@@ -9,42 +7,38 @@ use charming::{
         ToolboxDataZoom,
     },
     element::{
-        AxisPointer, AxisType, Label, LabelPosition, MarkLine, MarkLineData, MarkLineVariant,
-        MarkPoint, MarkPointData, NameLocation, Symbol, Tooltip, Trigger,
+        AxisPointer, AxisType, ItemStyle, Label, LabelPosition, LineStyle, LineStyleType,
+        MarkLine, MarkLineData, MarkLineVariant, MarkPoint, MarkPointData, NameLocation, Symbol,
+        Tooltip, Trigger,
     },
-    series::{Line, Scatter},
+    series::{Bar, Line, Scatter},
     Chart, ChartResize, HtmlRenderer, WasmRenderer,
 };
 
+/// Replicates of the stochastic SIR model shown in the charts.
+const REPLICATES: usize = 200;
+
+/// Sampling times, then the mean, 5% and 95% quantiles of every species, and the bin edges and
+/// counts of the final number of recovered.
+type SirEnsemble = (Vec<f64>, Vec<[Vec<f64>; 3]>, Vec<f64>, Vec<usize>);
+
 #[component]
 pub fn LineChart3() -> Element {
     let series = use_resource(|| async move { get_sir_data().await.ok() });
 
     let mut chart = use_signal(|| Chart::new());
+    let mut histogram = use_signal(|| Chart::new());
     let renderer = use_signal(|| WasmRenderer::new(600, 400));
     let mut echarts = use_signal(|| None);
+    let mut echarts_histogram = use_signal(|| None);
 
     use_effect(move || {
-        if let Some(Some(data)) = &*series.read() {
-            let series_s = data
-                .0
-                .iter()
-                .zip(data.1.iter())
-                .map(|(x, y)| vec![*x, *y])
-                .collect();
-            let series_i = data
-                .0
-                .iter()
-                .zip(data.2.iter())
-                .map(|(x, y)| vec![*x, *y])
-                .collect();
-            let series_r = data
-                .0
-                .iter()
-                .zip(data.3.iter())
-                .map(|(x, y)| vec![*x, *y])
-                .collect();
-            let updated_chart = Chart::new()
+        if let Some(Some((t, species, edges, counts))) = &*series.read() {
+            let colors = ["blue", "red", "green"];
+            let points = |values: &[f64]| -> Vec<Vec<f64>> {
+                t.iter().zip(values).map(|(x, y)| vec![*x, *y]).collect()
+            };
+            let mut updated_chart = Chart::new()
                 .legend(Legend::new())
                 .x_axis(
                     Axis::new()
@@ -59,73 +53,125 @@ pub fn LineChart3() -> Element {
                         .name_gap(25)
                         .name_location(NameLocation::Middle),
                 )
-                .series(
-                    Line::new().show_symbol(false).name("S").data(series_s), // Use data safely
-                )
-                .series(
-                    Line::new().show_symbol(false).name("I").data(series_i), // Use data safely
-                )
-                .series(
-                    Line::new().show_symbol(false).name("R").data(series_r), // Use data safely
-                )
                 .data_zoom(DataZoom::new().type_(DataZoomType::Inside).realtime(true));
-
+            for ((name, [mean, lower, upper]), color) in
+                ["S", "I", "R"].iter().zip(species).zip(colors)
+            {
+                let band = format!("{} 5-95%", name);
+                updated_chart = updated_chart
+                    .series(
+                        Line::new()
+                            .show_symbol(false)
+                            .name(*name)
+                            .item_style(ItemStyle::new().color(color))
+                            .line_style(LineStyle::new().color(color))
+                            .data(points(mean)),
+                    )
+                    .series(
+                        Line::new()
+                            .show_symbol(false)
+                            .name(band.as_str())
+                            .item_style(ItemStyle::new().color(color))
+                            .line_style(
+                                LineStyle::new()
+                                    .color(color)
+                                    .width(1)
+                                    .type_(LineStyleType::Dashed),
+                            )
+                            .data(points(lower)),
+                    )
+                    .series(
+                        Line::new()
+                            .show_symbol(false)
+                            .name(band.as_str())
+                            .item_style(ItemStyle::new().color(color))
+                            .line_style(
+                                LineStyle::new()
+                                    .color(color)
+                                    .width(1)
+                                    .type_(LineStyleType::Dashed),
+                            )
+                            .data(points(upper)),
+                    );
+            }
             chart.set(updated_chart);
 
+            let labels = edges
+                .windows(2)
+                .map(|e| format!("{}-{}", e[0], e[1] - 1.0))
+                .collect::<Vec<_>>();
+            histogram.set(
+                Chart::new()
+                    .x_axis(
+                        Axis::new()
+                            .type_(AxisType::Category)
+                            .name("Recovered at the end")
+                            .name_gap(25)
+                            .name_location(NameLocation::Middle)
+                            .data(labels),
+                    )
+                    .y_axis(
+                        Axis::new()
+                            .name("Replicates")
+                            .name_gap(25)
+                            .name_location(NameLocation::Middle),
+                    )
+                    .series(
+                        Bar::new()
+                            .item_style(ItemStyle::new().color("green"))
+                            .data(counts.iter().map(|c| *c as f64).collect::<Vec<_>>()),
+                    ),
+            );
+
             *echarts.write() = Some(
                 renderer
                     .read_unchecked()
                     .render("chart3", &chart.read())
                     .unwrap(),
             );
+            *echarts_histogram.write() = Some(
+                renderer
+                    .read_unchecked()
+                    .render("chart3histogram", &histogram.read())
+                    .unwrap(),
+            );
         }
     });
 
     rsx! (
-        // TODO fix loading of echart wasm
-        document::Script { src: asset!("/assets/echarts/echarts.min.js") }
         div { style: "width: 100%; text-align: center;",
-            h1 { style: "color:black", "SIR" }
+            h1 { style: "color:black", "Stochastic SIR" }
             // Show loading spinner while waiting for data
             if series.read().is_none() {
                 div { style: "padding: 20px;", "Loading data..." }
             } else if series.read().as_ref().unwrap().is_none() {
                 div { style: "color: red;", "Failed to load data!" }
             } else {
+                p { style: "color:black", "Mean and 5-95% band of {REPLICATES} replicates" }
                 div { id: "chart3", style: "display: inline-block;" }
+                div { id: "chart3histogram", style: "display: inline-block;" }
             }
         }
     )
 }
 
 #[server]
-async fn get_sir_data() -> Result<(Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>), ServerFnError> {
-    #![allow(non_snake_case)]
-    use rebop::define_system;
-
-    define_system! {
-        r_infection r_healing;
-        SIR { S, I, R }
-        infection   : S + I => 2 I @ r_infection
-        healing     : I     => R   @ r_healing
-    }
-
-    let mut series_t = Vec::new();
-    let mut series_s = Vec::new();
-    let mut series_i = Vec::new();
-    let mut series_r = Vec::new();
+async fn get_sir_data() -> Result<SirEnsemble, ServerFnError> {
+    use circuits::models::Sir;
+    use circuits::stochastic::{Method, Replicates};
 
-    let mut problem = SIR::new();
-    problem.r_infection = 0.1 / 1000.;
-    problem.r_healing = 0.01;
-    problem.S = 999;
-    problem.I = 1;
-    for t in 0..250 {
-        problem.advance_until(t as f64);
-        series_s.push(problem.S as f64);
-        series_i.push(problem.I as f64);
-        series_r.push(problem.R as f64);
-        series_t.push(problem.t as f64);
-    }
-    Ok((series_t, series_s, series_i, series_r))
+    let network = Sir::default().network();
+    let times = (0..250).map(|t| t as f64).collect::<Vec<_>>();
+    let replicates = Replicates::run(&network, Method::NextReaction, &times, REPLICATES, 0);
+    let (mean, lower, upper) = (
+        replicates.mean(),
+        replicates.quantile(0.05),
+        replicates.quantile(0.95),
+    );
+    let column = |states: &[circuits::State], i: usize| states.iter().map(|y| y[i]).collect();
+    let species = (0..network.species.len())
+        .map(|i| [column(&mean, i), column(&lower, i), column(&upper, i)])
+        .collect();
+    let histogram = replicates.histogram(2, times.len() - 1, 20);
+    Ok((times, species, histogram.edges, histogram.counts))
 }
@@ -3,28 +3,35 @@ use dioxus_logger::tracing::{info, Level};
 
 use charming::{
     component::{Axis, DataZoom, DataZoomType, Legend},
-    element::{AxisPointer, ItemStyle, LineStyle, NameLocation},
-    series::Line,
+    element::{AxisPointer, ItemStyle, LineStyle, LineStyleType, NameLocation},
+    series::{Line, Scatter},
     Chart, WasmRenderer,
 };
 
-use ode_solvers::dopri5::*;
-use ode_solvers::*;
+use circuits::models::Sir;
+use circuits::{Model, State};
+
+/// `[t, value]` pairs of every species, then the time and height of the peak of `I`.
+type SirTrajectory = (Vec<Vec<Vec<f64>>>, Option<(f64, f64)>);
 
 #[component]
 pub fn LineChartSIR() -> Element {
-    let mut r_infection = use_signal(|| 0.1 / 1000.0);
-    let mut r_healing = use_signal(|| 0.01);
-    let mut i0 = use_signal(|| 1.0_f64);
-    let mut s0 = use_signal(|| 999.0_f64);
-    let mut ti = use_signal(||250.0_f64);
+    let mut model = use_signal(Sir::default);
+    let mut y0 = use_signal(|| Sir::default().initial_state().as_slice().to_vec());
+    let mut ti = use_signal(|| 250.0_f64);
+    // Why the last parameter typed in was rejected.
+    let mut invalid = use_signal(|| None::<String>);
     let series = use_resource(move || {
-        let r_inf = *r_infection.read();
-        let r_heal = *r_healing.read();
-        let i0 = *i0.read();
-        let s0 = *s0.read();
+        let model = *model.read();
+        let y0 = y0.read().clone();
+        let ti = *ti.read();
+        async move { get_sir_data(model.beta, model.gamma, y0, ti).await.ok() }
+    });
+    let ensemble = use_resource(move || {
+        let model = *model.read();
+        let y0 = y0.read().clone();
         let ti = *ti.read();
-        async move { get_sir_data(r_inf, r_heal, s0, i0, ti).await.ok() }
+        async move { get_sir_ensemble(model.beta, model.gamma, y0, ti).await.ok() }
     });
 
     let mut chart = use_signal(|| Chart::new());
@@ -32,7 +39,22 @@ pub fn LineChartSIR() -> Element {
     let mut echarts = use_signal(|| None);
 
     use_effect(move || {
-        if let Some(Some((series_s, series_i, series_r))) = &*series.read() {
+        if let Some(Some((data, peak))) = &*series.read() {
+            let colors = ["blue", "red", "green"];
+            let chart_lines = Sir::default()
+                .species()
+                .iter()
+                .zip(data.iter())
+                .zip(colors.iter())
+                .map(|((name, data), color)| {
+                    Line::new()
+                        .show_symbol(false)
+                        .name(*name)
+                        .item_style(ItemStyle::new().color(*color))
+                        .line_style(LineStyle::new().color(*color))
+                        .data(data.to_vec()) // Use data safely
+                })
+                .collect::<Vec<_>>();
             let updated_chart = Chart::new()
                 .legend(Legend::new())
                 .x_axis(
@@ -44,42 +66,54 @@ pub fn LineChartSIR() -> Element {
                 )
                 .y_axis(
                     Axis::new()
-                        .name("S,I,R")
+                        .name(Sir::default().species().join(","))
                         .name_gap(25)
                         .name_location(NameLocation::Middle),
                 )
-                .series(
-                    Line::new()
-                        .show_symbol(false)
-                        .name("S")
-                        .item_style(ItemStyle::new().color("blue"))
-                        .line_style(LineStyle::new().color("blue"))
-                        .data(series_s.to_vec()), // Use data safely
-                )
-                .series(
-                    Line::new()
-                        .show_symbol(false)
-                        .item_style(ItemStyle::new().color("red"))
-                        .name("I")
-                        .line_style(LineStyle::new().color("red"))
-                        .data(series_i.to_vec()), // Use data safely
-                )
-                .series(
-                    Line::new()
-                        .show_symbol(false)
-                        .name("R")
-                        .item_style(ItemStyle::new().color("green"))
-                        .line_style(LineStyle::new().color("green"))
-                        .data(series_r.to_vec()), // Use data safely
-                )
                 .data_zoom(DataZoom::new().type_(DataZoomType::Inside).realtime(true));
+            let updated_chart = chart_lines
+                .into_iter()
+                .fold(updated_chart, |chart, line| chart.series(line));
+            let updated_chart = match peak {
+                Some((t, i)) => updated_chart.series(
+                    Scatter::new()
+                        .name("I peak")
+                        .symbol_size(10)
+                        .item_style(ItemStyle::new().color("red"))
+                        .data(vec![vec![*t, *i]]),
+                ),
+                None => updated_chart,
+            };
+            // Mean of the stochastic replicates over the deterministic curves.
+            let updated_chart = match &*ensemble.read() {
+                Some(Some(means)) => Sir::default()
+                    .species()
+                    .iter()
+                    .zip(means.iter())
+                    .zip(colors.iter())
+                    .fold(updated_chart, |chart, ((name, data), color)| {
+                        chart.series(
+                            Line::new()
+                                .show_symbol(false)
+                                .name(format!("{} stochastic mean", name))
+                                .item_style(ItemStyle::new().color(*color))
+                                .line_style(
+                                    LineStyle::new()
+                                        .color(*color)
+                                        .type_(LineStyleType::Dashed),
+                                )
+                                .data(data.to_vec()),
+                        )
+                    }),
+                _ => updated_chart,
+            };
 
             chart.set(updated_chart);
 
             *echarts.write() = Some(
                 renderer
                     .read_unchecked()
-                    .render("chart3", &chart.read())
+                    .render("chart4", &chart.read())
                     .unwrap(),
             );
         }
@@ -94,73 +128,52 @@ pub fn LineChartSIR() -> Element {
             } else if series.read().as_ref().unwrap().is_none() {
                 div { style: "color: red;", "Failed to load data!" }
             } else {
-                div { id: "chart3", style: "display: inline-block;" }
+                div { id: "chart4", style: "display: inline-block;" }
+                if let Some(Some((_, Some((t, i))))) = &*series.read() {
+                    p { style: "color:black", "I peaks at t = {t:.1} with {i:.1} infected" }
+                }
             }
         
         }
         div { class: "flex gap-4 justify-center mb-4",
 
-            div { class: "flex flex-col",
-                label { class: "text-sm text-gray-700", "Infection rate" }
-                input {
-                    class: "border rounded px-2 py-1 w-32",
-                    r#type: "number",
-                    step: "0.00001",
-                    value: "{r_infection}",
-                    oninput: move |e| {
-                        if let Ok(v) = e.value().parse::<f64>() {
-                            r_infection.set(v);
-                        }
-                    },
-                }
-            }
-
-            div { class: "flex flex-col",
-                label { class: "text-sm text-gray-700", "Recovery rate" }
-                input {
-                    class: "border rounded px-2 py-1 w-32",
-                    r#type: "number",
-                    step: "0.001",
-                    value: "{r_healing}",
-                    oninput: move |e| {
-                        if let Ok(v) = e.value().parse::<f64>() {
-                            r_healing.set(v);
-                        }
-                    },
-                }
-            }
-
-            div { class: "flex flex-col",
-                label { class: "text-sm text-gray-700", "Initial I" }
-                input {
-                    class: "border rounded px-2 py-1 w-32",
-                    r#type: "number",
-                    step: "1",
-                    value: "{i0}",
-                    oninput: move |e| {
-                        if let Ok(v) = e.value().parse::<f64>() {
-                            i0.set(v);
-                        }
-                    },
+            for parameter in model.read().parameters() {
+                div { class: "flex flex-col",
+                    label { class: "text-sm text-gray-700", "{parameter.name}" }
+                    input {
+                        class: "border rounded px-2 py-1 w-32",
+                        r#type: "number",
+                        step: "{parameter.default / 10.0}",
+                        min: "{parameter.min}",
+                        max: "{parameter.max}",
+                        value: "{parameter.value}",
+                        oninput: move |e| {
+                            if let Ok(v) = e.value().parse::<f64>() {
+                                let result = model.write().set(&parameter.name, v);
+                                invalid.set(result.err().map(|e| e.to_string()));
+                            }
+                        },
+                    }
                 }
             }
 
-            div { class: "flex flex-col",
-                label { class: "text-sm text-gray-700", "Initial S" }
-                input {
-                    class: "border rounded px-2 py-1 w-32",
-                    r#type: "number",
-                    step: "1",
-                    value: "{s0}",
-                    oninput: move |e| {
-                        if let Ok(v) = e.value().parse::<f64>() {
-                            s0.set(v);
-                        }
-                    },
+            for (i, name) in Sir::default().species().into_iter().enumerate() {
+                div { class: "flex flex-col",
+                    label { class: "text-sm text-gray-700", "Initial {name}" }
+                    input {
+                        class: "border rounded px-2 py-1 w-32",
+                        r#type: "number",
+                        step: "1",
+                        value: "{y0.read()[i]}",
+                        oninput: move |e| {
+                            if let Ok(v) = e.value().parse::<f64>() {
+                                y0.write()[i] = v;
+                            }
+                        },
+                    }
                 }
             }
 
-
             div { class: "flex flex-col",
                 label { class: "text-sm text-gray-700", "ti" }
                 input {
@@ -176,67 +189,84 @@ pub fn LineChartSIR() -> Element {
                 }
             }
         }
+        if let Some(message) = &*invalid.read() {
+            p { style: "color: red; text-align: center;", "{message}" }
+        }
 
     )
 }
 
-struct Model {
-    beta : f64,
+#[server]
+async fn get_sir_data(
+    beta: f64,
     gamma: f64,
-}
-
-type State = Vector3<f64>;
-type Time = f64;
+    y0: Vec<f64>,
+    ti: f64,
+) -> Result<SirTrajectory, ServerFnError> {
+    use circuits::events::{Direction, Event};
+    use circuits::stiff::Stiff;
+    use circuits::trajectory::uniform;
 
-impl ode_solvers::System<f64, State> for Model {
-    fn system(&self, _t: Time, x: &State, dx: &mut State) {
-        let s = x[0];
-        let i = x[1];
+    let model = Sir { beta, gamma };
+    let system = model.network();
+    let x = State::from_vec(y0);
+    let dim = x.len();
+    let t = 0.0;
+    let t_end = ti;
+    let times = uniform(t, t_end, 4001);
 
-        dx[0] = -self.beta * s * i;
-        dx[1] = self.beta * s * i - self.gamma * i;
-        dx[2] = self.gamma * i;
-    }
+    // The infected peak where dI/dt turns negative.
+    let peak = Event::new("peak", move |t, y| model.rhs(t, y)[1]).direction(Direction::Falling);
+    let mut stepper = Stiff::new(system, t, t_end, 0.0, x, 1e-6_f64, 1e-6_f64).event(peak);
+    stepper
+        .integrate()
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let peak = stepper.occurrences().first().map(|o| (o.t, o.y[1]));
+    let series = (0..dim)
+        .map(|i| stepper.trajectory().series(i, &times))
+        .collect();
+    Ok((series, peak))
 }
 
+/// Mean of stochastic replicates of the same model, as `[t, value]` pairs per species.
 #[server]
-async fn get_sir_data(
+async fn get_sir_ensemble(
     beta: f64,
     gamma: f64,
-    s0: f64,
-    i0: f64,
-    ti : f64,
-) -> Result<(Vec<Vec<f64>>, Vec<Vec<f64>>, Vec<Vec<f64>>), ServerFnError> {
-    // Return some meaningful data or simulate a fetch error
-
-        let system = Model {
-            beta,
-            gamma
-        };
-        let x = State::new(s0, i0, 0.0);
-        let t = 0.0;
-        let t_end = ti;
-        let dt = 0.003; // Step size to get ~4000 points
-
-        let mut stepper = Dopri5::new(system, t, t_end, dt, x, 1e-6_f64, 1e-6_f64);
-        stepper.integrate().expect("failed integration");
-        let series_s = stepper
-            .x_out()
-            .iter()
-            .zip(stepper.y_out().iter())
-            .map(|(x, y)| vec![*x, y[0]])
-            .collect();
-        let series_i = stepper
-            .x_out()
-            .iter()
-            .zip(stepper.y_out().iter())
-            .map(|(x, y)| vec![*x, y[1]])
-            .collect();
-        let series_r = stepper
-            .x_out()
-            .iter()
-            .zip(stepper.y_out().iter())
-            .map(|(x, y)| vec![*x, y[2]])
-            .collect();  
-    Ok((series_s, series_i, series_r))
+    y0: Vec<f64>,
+    ti: f64,
+) -> Result<Vec<Vec<Vec<f64>>>, ServerFnError> {
+    use circuits::stochastic::{Method, Replicates};
+    use circuits::trajectory::uniform;
+
+    let mut model = Sir::default();
+    for (name, value) in [("beta", beta), ("gamma", gamma)] {
+        model
+            .set(name, value)
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+    }
+    if !(ti.is_finite() && ti > 0.0) {
+        return Err(ServerFnError::new(format!("invalid end time {}", ti)));
+    }
+    let mut network = model.network();
+    if y0.len() != network.species.len() || !y0.iter().all(|y| y.is_finite() && *y >= 0.0) {
+        return Err(ServerFnError::new(format!(
+            "the initial state needs {} non-negative amounts",
+            network.species.len()
+        )));
+    }
+    network.initial = y0;
+    // Every 16th point of the deterministic grid.
+    let times = uniform(0.0, ti, 251);
+    let mean = Replicates::run(&network, Method::NextReaction, &times, 100, 0).mean();
+    let series = (0..network.species.len())
+        .map(|i| {
+            times
+                .iter()
+                .zip(mean.iter())
+                .map(|(t, y)| vec![*t, y[i]])
+                .collect()
+        })
+        .collect();
+    Ok(series)
 }
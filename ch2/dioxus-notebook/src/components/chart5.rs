@@ -0,0 +1,147 @@
+use dioxus::prelude::*;
+
+use circuits::delay::Dde;
+use circuits::models::DelayedAutoregulation;
+use circuits::{Model, State};
+
+use charming::{
+    component::{Axis, DataZoom, DataZoomType, Legend},
+    element::{AxisPointer, LineStyle, LineStyleType, NameLocation},
+    series::Line,
+    Chart, WasmRenderer,
+};
+
+/// Protein level of the delayed autorepressor from `t = 0` to `t_end`, with no expression
+/// before `t = 0`.
+fn protein(tau: f64, t_end: f64) -> Dde<DelayedAutoregulation, impl Fn(f64) -> State> {
+    let system = DelayedAutoregulation {
+        tau,
+        ..Default::default()
+    };
+    let y0 = system.initial_state();
+    let mut dde = Dde::new(system, move |_t| y0.clone(), 0.0, t_end);
+    dde.integrate().expect("failed integration");
+    dde
+}
+
+/// Peak-to-peak protein level over the last quarter of a long run.
+fn amplitude(tau: f64) -> f64 {
+    let t_end = 2000.0;
+    let dde = protein(tau, t_end);
+    let late = (0..=500).map(|k| dde.at(0.75 * t_end + k as f64)[1]);
+    let (low, high) = late.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), p| {
+        (low.min(p), high.max(p))
+    });
+    high - low
+}
+
+#[component]
+pub fn LineChartDelay() -> Element {
+    let t_end = 300.0;
+    let delays = [5.0, 15.0, 25.0, 35.0];
+    let chart = use_signal(move || {
+        let times: Vec<f64> = (0..=3000).map(|k| k as f64 * t_end / 3000.0).collect();
+        let chart = Chart::new()
+            .legend(Legend::new())
+            .x_axis(
+                Axis::new()
+                    .name("Time")
+                    .name_gap(25)
+                    .name_location(NameLocation::Middle)
+                    .min(0.0)
+                    .max(t_end)
+                    .axis_pointer(AxisPointer::new().z(100)),
+            )
+            .y_axis(
+                Axis::new()
+                    .name("Protein p")
+                    .min(0)
+                    .name_gap(25)
+                    .name_location(NameLocation::Middle),
+            )
+            .data_zoom(DataZoom::new().type_(DataZoomType::Inside).realtime(true));
+        delays.iter().fold(chart, |chart, tau| {
+            let dde = protein(*tau, t_end);
+            let data = times
+                .iter()
+                .zip(dde.sample(&times))
+                .map(|(t, y)| vec![*t, y[1]])
+                .collect();
+            chart.series(
+                Line::new()
+                    .data(data)
+                    .show_symbol(false)
+                    .name(format!("tau = {}", tau)),
+            )
+        })
+    });
+    let critical = DelayedAutoregulation::default().critical_delay();
+    let onset = use_signal(move || {
+        let data: Vec<Vec<f64>> = (0..=40)
+            .map(|k| k as f64)
+            .map(|tau| vec![tau, if tau > 0.0 { amplitude(tau) } else { 0.0 }])
+            .collect();
+        let top = data.iter().map(|p| p[1]).fold(0.0, f64::max);
+        let mut chart = Chart::new()
+            .legend(Legend::new())
+            .x_axis(
+                Axis::new()
+                    .name("Delay tau")
+                    .name_gap(25)
+                    .name_location(NameLocation::Middle)
+                    .min(0)
+                    .max(40),
+            )
+            .y_axis(
+                Axis::new()
+                    .name("Peak-to-peak p")
+                    .min(0)
+                    .name_gap(25)
+                    .name_location(NameLocation::Middle),
+            )
+            .series(Line::new().data(data).name("Oscillation amplitude"));
+        if let Some(tau) = critical {
+            chart = chart.series(
+                Line::new()
+                    .data(vec![vec![tau, 0.0], vec![tau, top]])
+                    .show_symbol(false)
+                    .line_style(LineStyle::new().type_(LineStyleType::Dashed))
+                    .name("Critical delay"),
+            );
+        }
+        chart
+    });
+    let summary = match critical {
+        Some(tau) => format!(
+            "The steady state p = {:.3} loses stability to oscillations at a delay of {:.2}.",
+            DelayedAutoregulation::default().steady_state_protein(),
+            tau
+        ),
+        None => "The feedback is too weak to oscillate at any delay.".to_string(),
+    };
+    let renderer = use_signal(|| WasmRenderer::new(600, 400));
+    let mut echarts = use_signal(|| None);
+    let mut echarts_onset = use_signal(|| None);
+    use_effect(move || {
+        *echarts.write() = Some(
+            renderer
+                .read_unchecked()
+                .render("chart5", &chart.read())
+                .unwrap(),
+        );
+        *echarts_onset.write() = Some(
+            renderer
+                .read_unchecked()
+                .render("chart5onset", &onset.read())
+                .unwrap(),
+        )
+    });
+    rsx! (
+        div { style: "width: 100%; text-align: center;",
+            h1 { style: "color:black", "Delayed negative autoregulation" }
+            div { id: "chart5", style: "display: inline-block;" }
+            div { id: "chart5onset", style: "display: inline-block;" }
+            p { style: "color:black", "{summary}" }
+        }
+    )
+}
@@ -7,5 +7,17 @@ pub use chart1::LineChart1;
 mod chart2;
 pub use chart2::LineChart2;
 
+mod chart3;
+pub use chart3::LineChart3;
+
 mod chart4;
-pub use chart4::LineChartSIR;
\ No newline at end of file
+pub use chart4::LineChartSIR;
+
+mod chart5;
+pub use chart5::LineChartDelay;
+
+mod phase_plane;
+pub use phase_plane::{PhasePlane1, PhasePlane2};
+
+mod synthetic;
+pub use synthetic::{ActivatorRepressorChart, GoodwinChart, RepressilatorChart, ToggleSwitchChart};
\ No newline at end of file
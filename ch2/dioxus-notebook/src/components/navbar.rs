@@ -77,6 +77,12 @@ pub fn Navbar() -> Element {
                                     class: "rounded-md px-3 py-2 text-sm font-medium text-gray-300 hover:bg-white/5 hover:text-white",
                                     "Regulation"
                                 }
+
+                                Link {
+                                    to: Route::Synthetic {},
+                                    class: "rounded-md px-3 py-2 text-sm font-medium text-gray-300 hover:bg-white/5 hover:text-white",
+                                    "Synthetic circuits"
+                                }
                             }
                         }
                     }
@@ -104,6 +110,12 @@ pub fn Navbar() -> Element {
                         class: "block rounded-md px-3 py-2 text-base font-medium text-gray-300 hover:bg-white/5 hover:text-white",
                         "Regulation"
                     }
+
+                    Link {
+                        to: Route::Synthetic {},
+                        class: "block rounded-md px-3 py-2 text-base font-medium text-gray-300 hover:bg-white/5 hover:text-white",
+                        "Synthetic circuits"
+                    }
                 }
             }
         }
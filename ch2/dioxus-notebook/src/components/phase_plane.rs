@@ -0,0 +1,163 @@
+use dioxus::prelude::*;
+
+use circuits::models::{NegativeAutoregulation, Unregulated};
+use circuits::phase_plane::{PhasePlane, Portrait};
+
+use charming::{
+    component::{Axis, Legend},
+    element::{ItemStyle, LineStyle, NameLocation, Symbol},
+    series::{Line, Scatter},
+    Chart, WasmRenderer,
+};
+
+/// Points of several polylines in one series, separated by NaN so that echarts breaks the line.
+fn polylines<'a>(lines: impl Iterator<Item = &'a [(f64, f64)]>) -> Vec<Vec<f64>> {
+    lines
+        .flat_map(|line| {
+            line.iter()
+                .map(|(x, y)| vec![*x, *y])
+                .chain(std::iter::once(vec![f64::NAN, f64::NAN]))
+        })
+        .collect()
+}
+
+fn path(name: &str, data: Vec<Vec<f64>>, color: &str, width: f64) -> Line {
+    Line::new()
+        .name(name)
+        .data(data)
+        .show_symbol(false)
+        .item_style(ItemStyle::new().color(color))
+        .line_style(LineStyle::new().color(color).width(width))
+}
+
+/// Direction field, nullclines, trajectories and fixed points as one chart.
+fn portrait_chart(portrait: &Portrait) -> Chart {
+    let (x0, x1) = portrait.x_range;
+    let (y0, y1) = portrait.y_range;
+
+    // Arrows of equal length relative to the window, a fifth of the field spacing.
+    let field = (portrait.arrows.len() as f64).sqrt();
+    let (sx, sy) = ((x1 - x0) / field / 5.0, (y1 - y0) / field / 5.0);
+    let arrows: Vec<Vec<(f64, f64)>> = portrait
+        .arrows
+        .iter()
+        .map(|a| {
+            let (u, v) = (a.dx / sx, a.dy / sy);
+            let norm = u.hypot(v).max(f64::MIN_POSITIVE);
+            let (u, v) = (2.0 * u / norm, 2.0 * v / norm);
+            let tip = (a.x + sx * u, a.y + sy * v);
+            let barb = |c: f64, s: f64| {
+                (
+                    tip.0 + sx * 0.4 * (c * u - s * v),
+                    tip.1 + sy * 0.4 * (s * u + c * v),
+                )
+            };
+            let (c, s) = (-(3f64.sqrt()) / 2.0, 0.5);
+            vec![(a.x, a.y), tip, barb(c, s), tip, barb(c, -s)]
+        })
+        .collect();
+    let segments = |segments: &[[(f64, f64); 2]]| polylines(segments.iter().map(|s| &s[..]));
+    let (fx, fy) = portrait.axes;
+    let fixed_points = portrait
+        .fixed_points
+        .iter()
+        .map(|p| vec![p.state[fx], p.state[fy]])
+        .collect::<Vec<_>>();
+
+    Chart::new()
+        .legend(Legend::new())
+        .x_axis(
+            Axis::new()
+                .name(portrait.x_label.as_str())
+                .name_gap(25)
+                .name_location(NameLocation::Middle)
+                .min(x0)
+                .max(x1),
+        )
+        .y_axis(
+            Axis::new()
+                .name(portrait.y_label.as_str())
+                .name_gap(25)
+                .name_location(NameLocation::Middle)
+                .min(y0)
+                .max(y1),
+        )
+        .series(path(
+            "direction",
+            polylines(arrows.iter().map(|a| &a[..])),
+            "#999999",
+            1.0,
+        ))
+        .series(path(
+            "trajectories",
+            polylines(portrait.trajectories.iter().map(|t| &t[..])),
+            "#2ca02c",
+            1.0,
+        ))
+        .series(path(
+            &format!("{} nullcline", portrait.x_label),
+            segments(&portrait.x_nullcline),
+            "#1f77b4",
+            2.5,
+        ))
+        .series(path(
+            &format!("{} nullcline", portrait.y_label),
+            segments(&portrait.y_nullcline),
+            "#d62728",
+            2.5,
+        ))
+        .series(
+            Scatter::new()
+                .name("fixed points")
+                .symbol(Symbol::Circle)
+                .symbol_size(12)
+                .item_style(ItemStyle::new().color("#000000"))
+                .data(fixed_points),
+        )
+}
+
+#[component]
+fn PhasePlaneChart(id: String, title: String, portrait: Signal<Portrait>) -> Element {
+    let chart = use_signal(|| portrait_chart(&portrait.read()));
+    let renderer = use_signal(|| WasmRenderer::new(600, 600));
+    let mut echarts = use_signal(|| None);
+    let chart_id = id.clone();
+    use_effect(move || {
+        *echarts.write() = Some(
+            renderer
+                .read_unchecked()
+                .render(&chart_id, &chart.read())
+                .unwrap(),
+        )
+    });
+    rsx! (
+        div { style: "width: 100%; text-align: center;",
+            h1 { style: "color:black", "{title}" }
+            div { id: "{id}", style: "display: inline-block;" }
+        }
+    )
+}
+
+#[component]
+pub fn PhasePlane1() -> Element {
+    let portrait = use_signal(|| {
+        PhasePlane::new((0.0, 1.5), (0.0, 15.0))
+            .t_end(50.0)
+            .portrait(&Unregulated::default())
+    });
+    rsx! {
+        PhasePlaneChart { id: "phase1", title: "Unregulated expression phase plane", portrait }
+    }
+}
+
+#[component]
+pub fn PhasePlane2() -> Element {
+    let portrait = use_signal(|| {
+        PhasePlane::new((0.0, 1.0), (0.0, 4.0))
+            .t_end(25.0)
+            .portrait(&NegativeAutoregulation::default())
+    });
+    rsx! {
+        PhasePlaneChart { id: "phase2", title: "Negative autoregulation phase plane", portrait }
+    }
+}
@@ -0,0 +1,407 @@
+use dioxus::prelude::*;
+
+use charming::{
+    component::{Axis, DataZoom, DataZoomType, Legend},
+    element::{AxisPointer, AxisType, ItemStyle, LineStyle, LineStyleType, NameLocation, Symbol},
+    series::{Line, Scatter},
+    Chart, WasmRenderer,
+};
+
+/// `[t, value]` pairs of each shown species, by species name.
+type Curves = Vec<(String, Vec<Vec<f64>>)>;
+
+/// Time courses of the toggle switch from each start, by label of the start, and a summary.
+type ToggleCourses = (Vec<(String, Curves)>, String);
+
+/// `[IPTG, u]` of the stable and of the unstable steady states, and a summary.
+type ToggleScan = (Vec<Vec<f64>>, Vec<Vec<f64>>, String);
+
+fn time_axis(t_end: f64) -> Axis {
+    Axis::new()
+        .name("Time")
+        .name_gap(25)
+        .name_location(NameLocation::Middle)
+        .min(0.0)
+        .max(t_end)
+        .axis_pointer(AxisPointer::new().z(100))
+}
+
+fn concentration_axis(name: &str) -> Axis {
+    Axis::new()
+        .name(name)
+        .min(0)
+        .name_gap(25)
+        .name_location(NameLocation::Middle)
+}
+
+/// A chart that is drawn once its data has arrived, with the summary below it.
+/// `status` is `None` while loading and `Some(None)` when the data failed to load.
+#[component]
+fn CircuitChart(
+    id: String,
+    title: String,
+    chart: Signal<Option<Chart>>,
+    status: Option<Option<String>>,
+) -> Element {
+    let renderer = use_signal(|| WasmRenderer::new(600, 400));
+    let mut echarts = use_signal(|| None);
+    let chart_id = id.clone();
+    use_effect(move || {
+        if let Some(chart) = &*chart.read() {
+            *echarts.write() = Some(renderer.read_unchecked().render(&chart_id, chart).unwrap())
+        }
+    });
+    rsx! (
+        div { style: "width: 100%; text-align: center;",
+            h1 { style: "color:black", "{title}" }
+            div { id: "{id}", style: "display: inline-block;" }
+            match status {
+                None => rsx! { div { style: "padding: 20px;", "Loading data..." } },
+                Some(None) => rsx! { div { style: "color: red;", "Failed to load data!" } },
+                Some(Some(summary)) => rsx! { p { style: "color:black", "{summary}" } },
+            }
+        }
+    )
+}
+
+#[component]
+pub fn ToggleSwitchChart() -> Element {
+    let t_end = 20.0;
+    let courses = use_resource(move || async move { get_toggle_courses(t_end).await.ok() });
+    let scan = use_resource(|| async move { get_toggle_scan().await.ok() });
+    let mut chart = use_signal(|| None);
+    let mut scan_chart = use_signal(|| None);
+
+    use_effect(move || {
+        if let Some(Some((starts, _))) = &*courses.read() {
+            let updated_chart = Chart::new()
+                .legend(Legend::new())
+                .x_axis(time_axis(t_end))
+                .y_axis(concentration_axis("Repressor"));
+            // Solid lines for the first start, dashed for the second.
+            let updated_chart = starts
+                .iter()
+                .zip([LineStyleType::Solid, LineStyleType::Dashed])
+                .fold(updated_chart, |chart, ((label, curves), style)| {
+                    curves.iter().fold(chart, |chart, (name, data)| {
+                        chart.series(
+                            Line::new()
+                                .data(data.clone())
+                                .show_symbol(false)
+                                .line_style(LineStyle::new().type_(style.clone()))
+                                .name(format!("{} ({})", name, label)),
+                        )
+                    })
+                });
+            chart.set(Some(updated_chart));
+        }
+    });
+
+    use_effect(move || {
+        if let Some(Some((stable, unstable, _))) = &*scan.read() {
+            scan_chart.set(Some(
+                Chart::new()
+                    .legend(Legend::new())
+                    .x_axis(
+                        Axis::new()
+                            .type_(AxisType::Log)
+                            .name("IPTG [M]")
+                            .name_gap(25)
+                            .name_location(NameLocation::Middle),
+                    )
+                    .y_axis(
+                        Axis::new()
+                            .name("LacI u at steady state")
+                            .name_gap(25)
+                            .name_location(NameLocation::Middle),
+                    )
+                    .series(
+                        Scatter::new()
+                            .name("stable")
+                            .symbol(Symbol::Circle)
+                            .item_style(ItemStyle::new().color("#1f77b4"))
+                            .data(stable.clone()),
+                    )
+                    .series(
+                        Scatter::new()
+                            .name("unstable")
+                            .symbol(Symbol::Circle)
+                            .item_style(ItemStyle::new().color("#d62728"))
+                            .data(unstable.clone()),
+                    ),
+            ));
+        }
+    });
+
+    let status = courses
+        .read()
+        .clone()
+        .map(|data| data.map(|(_, summary)| summary));
+    let scan_status = scan
+        .read()
+        .clone()
+        .map(|data| data.map(|(_, _, summary)| summary));
+    rsx! {
+        CircuitChart { id: "toggle", title: "Toggle switch", chart, status }
+        CircuitChart {
+            id: "togglescan",
+            title: "Toggle switch bistability",
+            chart: scan_chart,
+            status: scan_status,
+        }
+    }
+}
+
+/// Time course of the shown species of the oscillator `circuit`, drawn by
+/// [`get_oscillator_data`].
+#[component]
+fn OscillatorChart(id: String, title: String, circuit: String, t_end: f64) -> Element {
+    let data = use_resource(move || {
+        let circuit = circuit.clone();
+        async move { get_oscillator_data(circuit, t_end).await.ok() }
+    });
+    let mut chart = use_signal(|| None);
+
+    use_effect(move || {
+        if let Some(Some((curves, _))) = &*data.read() {
+            let updated_chart = Chart::new()
+                .legend(Legend::new())
+                .x_axis(time_axis(t_end))
+                .y_axis(concentration_axis("Concentration"))
+                .data_zoom(DataZoom::new().type_(DataZoomType::Inside).realtime(true));
+            let updated_chart = curves.iter().fold(updated_chart, |chart, (name, data)| {
+                chart.series(
+                    Line::new()
+                        .data(data.clone())
+                        .show_symbol(false)
+                        .name(name.as_str()),
+                )
+            });
+            chart.set(Some(updated_chart));
+        }
+    });
+
+    let status = data
+        .read()
+        .clone()
+        .map(|data| data.map(|(_, summary)| summary));
+    rsx! {
+        CircuitChart { id, title, chart, status }
+    }
+}
+
+#[component]
+pub fn RepressilatorChart() -> Element {
+    rsx! {
+        OscillatorChart {
+            id: "repressilator",
+            title: "Repressilator",
+            circuit: "repressilator",
+            t_end: 300.0,
+        }
+    }
+}
+
+#[component]
+pub fn GoodwinChart() -> Element {
+    rsx! {
+        OscillatorChart {
+            id: "goodwin",
+            title: "Goodwin oscillator",
+            circuit: "goodwin",
+            t_end: 600.0,
+        }
+    }
+}
+
+#[component]
+pub fn ActivatorRepressorChart() -> Element {
+    rsx! {
+        OscillatorChart {
+            id: "activatorrepressor",
+            title: "Activator-repressor clock",
+            circuit: "activator-repressor",
+            t_end: 200.0,
+        }
+    }
+}
+
+#[server]
+async fn get_toggle_courses(t_end: f64) -> Result<ToggleCourses, ServerFnError> {
+    use circuits::models::ToggleSwitch;
+    use circuits::stiff::{Method, Stiff};
+    use circuits::trajectory::uniform;
+    use circuits::{Model, State};
+
+    // The charts span a few hundred time units; longer runs would only tie up the server.
+    if !(t_end > 0.0 && t_end <= 1e4) {
+        return Err(ServerFnError::new(format!("invalid end time {}", t_end)));
+    }
+
+    let model = ToggleSwitch::default();
+    let times = uniform(0.0, t_end, 2001);
+    // The same switch started once with LacI and once with its partner in the lead.
+    let starts = [
+        ("LacI first", State::from_vec(vec![model.alpha1, 0.0])),
+        ("TetR first", State::from_vec(vec![0.0, model.alpha2])),
+    ];
+    let mut courses = Vec::new();
+    for (label, y0) in starts {
+        let mut stepper = Stiff::new(model, 0.0, t_end, 0.0, y0, 1e-6, 1e-6).method(Method::Auto);
+        stepper
+            .integrate()
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+        let curves = model
+            .species()
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.to_string(), stepper.trajectory().series(i, &times)))
+            .collect();
+        courses.push((label.to_string(), curves));
+    }
+
+    let states = model
+        .steady_states()
+        .iter()
+        .filter(|p| p.stability.is_stable())
+        .map(|p| format!("(u, v) = ({:.2}, {:.2})", p.state[0], p.state[1]))
+        .collect::<Vec<_>>()
+        .join(" and ");
+    let summary = if model.is_bistable() {
+        format!("Without inducer the switch is bistable with stable states {states}.")
+    } else {
+        format!("Without inducer the switch has a single stable state {states}.")
+    };
+    Ok((courses, summary))
+}
+
+#[server]
+async fn get_toggle_scan() -> Result<ToggleScan, ServerFnError> {
+    use circuits::models::ToggleSwitch;
+    use circuits::trajectory::logarithmic;
+
+    let model = ToggleSwitch::default();
+    let doses = logarithmic(1e-6, 1e-2, 61).map_err(|e| ServerFnError::new(e.to_string()))?;
+    let mut stable = Vec::new();
+    let mut unstable = Vec::new();
+    let mut bistable = Vec::new();
+    for iptg in &doses {
+        let induced = ToggleSwitch {
+            iptg: *iptg,
+            ..model
+        };
+        for point in induced.steady_states() {
+            let data = vec![*iptg, point.state[0]];
+            if point.stability.is_stable() {
+                stable.push(data);
+            } else {
+                unstable.push(data);
+            }
+        }
+        bistable.push(induced.is_bistable());
+    }
+
+    // Refine the first dose of the scan past which the switch is no longer bistable.
+    let is_bistable = |iptg: f64| ToggleSwitch { iptg, ..model }.is_bistable();
+    let last = bistable.iter().rposition(|b| *b);
+    let summary = match last {
+        None => {
+            "IPTG inactivates LacI; the switch is not bistable at any dose scanned.".to_string()
+        }
+        Some(i) if i + 1 == doses.len() => format!(
+            "IPTG inactivates LacI; the switch stays bistable up to {:.0e} M.",
+            doses[i]
+        ),
+        Some(i) => {
+            let (mut low, mut high) = (doses[i], doses[i + 1]);
+            for _ in 0..30 {
+                let middle = (low * high).sqrt();
+                if is_bistable(middle) {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            format!(
+                "IPTG inactivates LacI; above about {:.1e} M only the state with v high is left.",
+                high
+            )
+        }
+    };
+    Ok((stable, unstable, summary))
+}
+
+#[server]
+async fn get_oscillator_data(
+    circuit: String,
+    t_end: f64,
+) -> Result<(Curves, String), ServerFnError> {
+    use circuits::analysis::Oscillation;
+    use circuits::models::{ActivatorRepressor, Goodwin, Repressilator};
+    use circuits::stiff::{Method, Stiff};
+    use circuits::trajectory::uniform;
+    use circuits::Model;
+
+    /// Time course of the `shown` species and the oscillation of the first of them over the
+    /// second half of the run.
+    fn run<M: Model + Clone>(
+        model: M,
+        t_end: f64,
+        shown: &[usize],
+        method: Method,
+    ) -> Result<(Curves, Option<Oscillation>), ServerFnError> {
+        let names: Vec<String> = model.species().iter().map(|s| s.to_string()).collect();
+        let y0 = model.initial_state();
+        let mut stepper = Stiff::new(model, 0.0, t_end, 0.0, y0, 1e-6, 1e-6).method(method);
+        stepper
+            .integrate()
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+        let trajectory = stepper.trajectory();
+        let times = uniform(0.0, t_end, 4001);
+        let curves = shown
+            .iter()
+            .map(|i| (names[*i].clone(), trajectory.series(*i, &times)))
+            .collect();
+        let late = uniform(t_end / 2.0, t_end, 4001);
+        let values: Vec<f64> = trajectory
+            .sample(&late)
+            .iter()
+            .map(|y| y[shown[0]])
+            .collect();
+        Ok((curves, Oscillation::new(&late, &values)))
+    }
+
+    if !(t_end > 0.0 && t_end <= 1e4) {
+        return Err(ServerFnError::new(format!("invalid end time {}", t_end)));
+    }
+
+    let (species, (curves, oscillation)) = match circuit.as_str() {
+        "repressilator" => (
+            "LacI",
+            run(Repressilator::default(), t_end, &[3, 4, 5], Method::Auto)?,
+        ),
+        "goodwin" => (
+            "The mRNA x",
+            run(Goodwin::default(), t_end, &[0, 1, 2], Method::Auto)?,
+        ),
+        // Fast promoter binding makes the clock stiff.
+        "activator-repressor" => (
+            "The activator A",
+            run(ActivatorRepressor::default(), t_end, &[5, 7], Method::Bdf)?,
+        ),
+        _ => return Err(ServerFnError::new(format!("unknown circuit {}", circuit))),
+    };
+    let summary = match oscillation {
+        Some(o) if o.is_sustained(0.95) => format!(
+            "{} oscillates with period {:.2} and peak-to-trough amplitude {:.2}.",
+            species, o.period, o.amplitude
+        ),
+        Some(o) => format!(
+            "{} oscillations are damped: each cycle keeps {:.0}% of the amplitude of the last.",
+            species,
+            100.0 * o.ratio
+        ),
+        None => format!("{} settles without oscillating.", species),
+    };
+    Ok((curves, summary))
+}
@@ -1,7 +1,7 @@
 use dioxus::prelude::*;
 
 use components::Navbar;
-use views::{Home,Sir,Regulation};
+use views::{Home,Sir,Regulation,Synthetic};
 
 mod components;
 mod views;
@@ -18,6 +18,9 @@ enum Route {
 
     #[route("/regulation")]
     Regulation {},
+
+    #[route("/synthetic")]
+    Synthetic {},
 }
 
 const FAVICON: Asset = asset!("/assets/favicon.ico");
@@ -6,3 +6,6 @@ pub use sir::Sir;
 
 mod regulation;
 pub use regulation::Regulation;
+
+mod synthetic;
+pub use synthetic::Synthetic;
@@ -5,6 +5,9 @@ use dioxus::prelude::*;
 pub fn Regulation() -> Element {
     rsx! {
         LineChart1 {}
+        PhasePlane1 {}
         LineChart2 {}
+        PhasePlane2 {}
+        LineChartDelay {}
     }
 }
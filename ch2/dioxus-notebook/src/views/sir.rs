@@ -5,5 +5,6 @@ use dioxus::prelude::*;
 pub fn Sir() -> Element {
     rsx! {
         LineChartSIR {}
+        LineChart3 {}
     }
 }
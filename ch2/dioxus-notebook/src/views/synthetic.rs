@@ -0,0 +1,12 @@
+use crate::components::*;
+use dioxus::prelude::*;
+
+#[component]
+pub fn Synthetic() -> Element {
+    rsx! {
+        ToggleSwitchChart {}
+        RepressilatorChart {}
+        GoodwinChart {}
+        ActivatorRepressorChart {}
+    }
+}
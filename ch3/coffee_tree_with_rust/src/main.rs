@@ -1,76 +1,621 @@
+use ode_solvers::OutputType;
 use ode_solvers::dop853::*;
-use ode_solvers::*;
+
+use circuits::approximation::{Approximation, Scheme, compare};
+use circuits::continuation::Continuation;
+use circuits::fitting::{Estimation, Fit, Observations};
+use circuits::global_sensitivity::{Morris, Range, Sobol};
+use circuits::mcmc::{Ensemble, Prior};
+use circuits::models::CoffeeRust;
+use circuits::sbml::{self, ToSbml};
+use circuits::sensitivity::{Method, sensitivities};
+use circuits::stability::{FixedPoint, fixed_points};
+use circuits::stiff::Stiff;
+use circuits::stochastic::{self, Replicates};
+use circuits::sweep::Sweep;
+use circuits::trajectory::uniform;
+use circuits::{Model, State};
 
 use comrak::{Options, markdown_to_html};
 use df_interchange::Interchange;
-use plotlars::{LinePlot, Plot, Rgb};
+use plotlars::{BarPlot, LinePlot, Plot, Rgb, ScatterPlot};
 use polars::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-type State = Vector3<f64>;
-type Time = f64;
+const T_END: f64 = 700.0;
 
-struct Model {
-    a: f64,
-    beta: f64,
-    k: f64,
-    gamma: f64,
-    b: f64,
-    d: f64,
+fn main() -> Result<()> {
+    let system = CoffeeRust::default();
+    let (equilibria, equilibria_table) = equilibria(&system);
+    let bifurcation_section = bifurcations(&system, &equilibria)?;
+    let sensitivity_section = local_sensitivity(&system)?;
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let global_section = global_sensitivity(&system, &mut rng)?;
+    let df = baseline(&system)?;
+    let (fit_section, fit, observations) = estimation(&system, &df, &mut rng)?;
+    let posterior_section = posterior(&system, &fit, &observations, &mut rng)?;
+    let approximation_section = approximations(&system);
+    let sweep_section = sweep(&system)?;
+
+    let mut markdown =
+        "# Mathematical model of coffee tree's rust control using snail as biological agents\n"
+            .to_string();
+    markdown.push_str(
+        r#"$$
+            \frac{dT_s(t)}{dt} = a T_s(t) - \beta T_s(t) T_i(t) + k S(t) T_i(t)
+            $$
+            "#,
+    );
+    markdown.push_str(
+        r#"$$
+            \frac{dT_s(t)}{dt} = \beta T_s(t) T_i(t) - k S(t) T_i(t) - \gamma T_i(t)
+            $$
+            "#,
+    );
+    markdown.push_str(
+        r#"$$
+            \frac{dT_s(t)}{dt} = b S(t) T_i(t) - d S(t)
+            $$
+            "#,
+    );
+    markdown.push_str("![alt text](p1.svg) \n");
+    markdown.push_str("\n## Fixed points\n\n");
+    markdown.push_str(&equilibria_table);
+    markdown.push_str("\n## Bifurcation diagrams\n\n");
+    markdown.push_str(&bifurcation_section);
+    markdown.push_str("\n## Sensitivities\n\n");
+    markdown.push_str(&sensitivity_section);
+    markdown.push_str("\n## Parameter estimation\n\n");
+    markdown.push_str(&fit_section);
+    markdown.push_str("\n## Posterior\n\n");
+    markdown.push_str(&posterior_section);
+    markdown.push_str("\n## Global sensitivity\n\n");
+    markdown.push_str(&global_section);
+    markdown.push_str("\n## Stochastic approximations\n\n");
+    markdown.push_str(&approximation_section);
+    markdown.push_str("\n## Parameter sweep\n\n");
+    markdown.push_str(&sweep_section);
+    page(&markdown)
 }
 
-impl System<f64, State> for Model {
-    fn system(&self, _t: Time, y: &State, dy: &mut State) {
-        let y1 = y[0];
-        let y2 = y[1];
-        let y3 = y[2];
+/// Fixed points found by Newton from a logarithmic grid of guesses, whatever their stability,
+/// and their table.
+fn equilibria(system: &CoffeeRust) -> (Vec<FixedPoint>, String) {
+    let species = system.species();
+    let levels = [0.0, 0.1, 10.0, 1e3, 1e5];
+    let guesses: Vec<State> = levels
+        .iter()
+        .flat_map(|t_s| levels.iter().map(move |t_i| (*t_s, *t_i)))
+        .flat_map(|(t_s, t_i)| {
+            levels
+                .iter()
+                .map(move |s| State::from_vec(vec![t_s, t_i, *s]))
+        })
+        .collect();
+    let equilibria = fixed_points(system, &guesses);
+    let mut table = format!(
+        "| {} | eigenvalues | type |\n|{}---|---|\n",
+        species.join(" | "),
+        "---|".repeat(species.len())
+    );
+    for point in &equilibria {
+        let state: Vec<String> = point.state.iter().map(|v| format!("{:.4}", v)).collect();
+        let eigenvalues: Vec<String> = point
+            .eigenvalues
+            .iter()
+            .map(|l| format!("{:.3e} {:+.3e}i", l.re, l.im))
+            .collect();
+        println!("Fixed point ({}): {}", state.join(", "), point.stability);
+        table.push_str(&format!(
+            "| {} | {} | {} |\n",
+            state.join(" | "),
+            eigenvalues.join(", "),
+            point.stability
+        ));
+    }
+    (equilibria, table)
+}
 
-        dy[0] = self.a * y1 - self.beta * y1 * y2 + self.k * y3 * y2;
-        dy[1] = self.beta * y1 * y2 - self.k * y3 * y2 - self.gamma * y2;
-        dy[2] = self.b * y3 * y2 - self.d * y3;
+/// Equilibrium branches through the coexistence state for the control parameters, written to
+/// `bifurcation_<parameter>.csv` and `.svg`.
+fn bifurcations(system: &CoffeeRust, equilibria: &[FixedPoint]) -> Result<String> {
+    let mut markdown = String::new();
+    let Some(coexistence) = equilibria.iter().find(|p| p.state.iter().all(|v| *v > 1.0)) else {
+        return Ok(markdown);
+    };
+    for name in ["k", "b", "d"] {
+        let value = system.get(name).unwrap_or_default();
+        let branch =
+            Continuation::new(name, 0.1 * value, 2.0 * value).run(system, &coexistence.state)?;
+        branch.write_csv(format!("bifurcation_{}.csv", name))?;
+
+        let mut columns = vec![Column::new(
+            branch.parameter.as_str().into(),
+            branch
+                .points
+                .iter()
+                .map(|p| p.parameter)
+                .collect::<Vec<_>>(),
+        )];
+        for (i, name) in branch.species.iter().enumerate() {
+            columns.push(Column::new(
+                name.into(),
+                branch.points.iter().map(|p| p.state[i]).collect::<Vec<_>>(),
+            ));
+        }
+        let df = Interchange::from_polars_0_51(DataFrame::new(columns)?)?.to_polars_0_50()?;
+        let path = format!("bifurcation_{}.svg", branch.parameter);
+        LinePlot::builder()
+            .data(&df)
+            .x(&branch.parameter)
+            .y(&branch.species[0])
+            .additional_lines(branch.species[1..].iter().map(|s| s.as_str()).collect())
+            .size(12)
+            .colors(vec![Rgb(0, 255, 0), Rgb(255, 0, 0), Rgb(0, 0, 255)])
+            .plot_title(format!("Equilibria vs {}", branch.parameter).as_str())
+            .x_title(branch.parameter.as_str())
+            .y_title("Population in size")
+            .build()
+            .write_image(&path, 1000, 600, 1.0)?;
+        let stable = branch
+            .points
+            .iter()
+            .filter(|p| p.stability.is_stable())
+            .count();
+        markdown.push_str(&format!(
+            "![bifurcation diagram]({}) \n\n{} of {} equilibria are stable, {} bifurcations.\n\n",
+            path,
+            stable,
+            branch.points.len(),
+            branch.bifurcations.len()
+        ));
     }
+    Ok(markdown)
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let system = Model {
-        a: 0.0000283 / (24.0 * 24.0),
-        beta: 0.0298 / (24.0 * 24.0),
-        k: 0.07333 / (24.0 * 24.0),
-        gamma: 0.005 / (24.0 * 24.0),
-        b: 0.0025 / (24.0 * 24.0),
-        d: 0.00125,
+/// Logarithmic sensitivities of the trajectory to every parameter, written to
+/// `sensitivities.csv` and `.svg`, and the parameters ranked by their effect on `T_i`.
+fn local_sensitivity(system: &CoffeeRust) -> Result<String> {
+    let parameters = system.parameters();
+    let names: Vec<&str> = parameters.iter().map(|p| p.name.as_str()).collect();
+    let sensitivity = sensitivities(system, &names, T_END, 1.0, Method::Forward)?;
+    let mut df = DataFrame::new(
+        sensitivity
+            .columns(true)
+            .into_iter()
+            .map(|(name, values)| Column::new(name.into(), values))
+            .collect(),
+    )?;
+    CsvWriter::new(std::fs::File::create("sensitivities.csv")?).finish(&mut df)?;
+    let sensitivity_df = Interchange::from_polars_0_51(df)?.to_polars_0_50()?;
+    let normalized = sensitivity.normalized();
+    let mut ranking: Vec<(&str, f64)> = names
+        .iter()
+        .enumerate()
+        .map(|(j, name)| {
+            let peak = normalized
+                .iter()
+                .map(|s| s[(1, j)].abs())
+                .fold(0.0, f64::max);
+            (*name, peak)
+        })
+        .collect();
+    ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let lines: Vec<String> = names.iter().map(|p| format!("dlogT_i/dlog{}", p)).collect();
+    LinePlot::builder()
+        .data(&sensitivity_df)
+        .x("t")
+        .y(&lines[0])
+        .additional_lines(lines[1..].iter().map(|s| s.as_str()).collect())
+        .size(12)
+        .plot_title("Normalized sensitivities of T_i")
+        .x_title("Time [in hours]")
+        .y_title("dlog T_i / dlog p")
+        .build()
+        .write_image("sensitivities.svg", 1000, 600, 1.0)?;
+    let mut markdown = "![sensitivities](sensitivities.svg) \n\n".to_string();
+    markdown.push_str("| parameter | max \\|dlog T_i / dlog p\\| |\n|---|---|\n");
+    for (name, peak) in &ranking {
+        println!("Sensitivity of T_i to {}: {:.3}", name, peak);
+        markdown.push_str(&format!("| {} | {:.3} |\n", name, peak));
+    }
+    Ok(markdown)
+}
+
+/// Global sensitivity of the infected trees at the end of the run, every parameter over a
+/// decade either side of its value, written to `global_sensitivity.csv` and `sobol.svg`.
+fn global_sensitivity(system: &CoffeeRust, rng: &mut ChaCha8Rng) -> Result<String> {
+    let parameters = system.parameters();
+    let names: Vec<&str> = parameters.iter().map(|p| p.name.as_str()).collect();
+    let ranges: Vec<Range> = parameters
+        .iter()
+        .map(|p| Range::new(&p.name, p.value / 10.0, p.value * 10.0).log())
+        .collect();
+    let infected = |model: &CoffeeRust| {
+        let mut stepper = Dop853::new(*model, 0.0, T_END, T_END, model.initial_state(), 1e-6, 1e-6);
+        stepper.set_output(OutputType::Sparse);
+        match stepper.integrate() {
+            Ok(_) => stepper.y_out().last().map_or(f64::NAN, |y| y[1]),
+            Err(_) => f64::NAN,
+        }
     };
+    let morris = Morris::new(ranges.clone()).run(system, infected, rng)?;
+    let sobol = Sobol::new(ranges).samples(512).run(system, infected, rng)?;
+    let mut df = DataFrame::new(vec![
+        Column::new("parameter".into(), names.clone()),
+        Column::new(
+            "mu_star".into(),
+            morris.iter().map(|m| m.mu_star).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "sigma".into(),
+            morris.iter().map(|m| m.sigma).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "first".into(),
+            sobol.iter().map(|s| s.first).collect::<Vec<_>>(),
+        ),
+        Column::new(
+            "total".into(),
+            sobol.iter().map(|s| s.total).collect::<Vec<_>>(),
+        ),
+    ])?;
+    println!("{}", df);
+    CsvWriter::new(std::fs::File::create("global_sensitivity.csv")?).finish(&mut df)?;
+    let indices = DataFrame::new(vec![
+        Column::new("parameter".into(), [names.clone(), names.clone()].concat()),
+        Column::new(
+            "index".into(),
+            [vec!["first order"; names.len()], vec!["total"; names.len()]].concat(),
+        ),
+        Column::new(
+            "value".into(),
+            sobol
+                .iter()
+                .map(|s| s.first)
+                .chain(sobol.iter().map(|s| s.total))
+                .collect::<Vec<_>>(),
+        ),
+    ])?;
+    let indices_df = Interchange::from_polars_0_51(indices)?.to_polars_0_50()?;
+
+    BarPlot::builder()
+        .data(&indices_df)
+        .labels("parameter")
+        .values("value")
+        .group("index")
+        .colors(vec![Rgb(255, 127, 80), Rgb(64, 224, 208)])
+        .plot_title("Sobol indices of T_i at 700 hours")
+        .x_title("parameter")
+        .y_title("index")
+        .build()
+        .write_image("sobol.svg", 1000, 600, 1.0)?;
+    let mut markdown = "![Sobol indices](sobol.svg) \n\n".to_string();
+    markdown.push_str("| parameter | Morris μ* | Morris σ | first order | total |\n");
+    markdown.push_str("|---|---|---|---|---|\n");
+    for (m, s) in morris.iter().zip(&sobol) {
+        markdown.push_str(&format!(
+            "| {} | {:.3e} | {:.3e} | {:.3} | {:.3} |\n",
+            m.parameter, m.mu_star, m.sigma, s.first, s.total
+        ));
+    }
+    Ok(markdown)
+}
 
-    let y0 = State::new(1000.0, 500.0, 0.0);
+/// The trajectory of the model, written to `model_answer_polars.csv` and `p1.svg`, and the
+/// model itself to `coffee_tree_rust.xml`.
+fn baseline(system: &CoffeeRust) -> Result<DataFrame> {
+    let species = system.species();
+    let y0 = system.initial_state();
     let t_start = 0.0;
-    let t_end = 700.0;
     let h_init = 1.0;
 
-    let mut stepper = Dop853::new(system, t_start, t_end, h_init, y0, 1e-6, 1e-6);
-    match stepper.integrate() {
-        Ok(stats) => {
-            println!("Integration successful: {}", stats);
+    // The rates scaled from days to hours make the model stiff at times; the automatic method
+    // switches between explicit and Rosenbrock steps as needed.
+    let mut stepper = Stiff::new(*system, t_start, T_END, h_init, y0, 1e-6, 1e-6);
+    let stats = stepper.integrate()?;
+    println!("Integration successful: {}", stats);
+    for (t, method) in stepper.segments() {
+        println!("{:?} steps from t = {:.1}", method, t);
+    }
 
-            let t_series = Column::new("t".into(), stepper.x_out().to_vec());
-            let y1_series = Column::new(
-                "T<sub>s</sub>(t)".into(),
-                stepper.y_out().iter().map(|v| v[0]).collect::<Vec<_>>(),
-            );
-            let y2_series = Column::new(
-                "T<sub>i</sub>(t)".into(),
-                stepper.y_out().iter().map(|v| v[1]).collect::<Vec<_>>(),
-            );
-            let y3_series = Column::new(
-                "S(t)".into(),
-                stepper.y_out().iter().map(|v| v[2]).collect::<Vec<_>>(),
-            );
+    let mut columns = vec![Column::new("t".into(), stepper.x_out().to_vec())];
+    for (i, name) in species.iter().enumerate() {
+        columns.push(Column::new(
+            (*name).into(),
+            stepper.y_out().iter().map(|v| v[i]).collect::<Vec<_>>(),
+        ));
+    }
 
-            let mut df = DataFrame::new(vec![t_series, y1_series, y2_series, y3_series])?;
+    let mut df = DataFrame::new(columns)?;
 
-            CsvWriter::new(std::fs::File::create("model_answer_polars.csv")?).finish(&mut df)?;
+    CsvWriter::new(std::fs::File::create("model_answer_polars.csv")?).finish(&mut df)?;
+    sbml::write_file("coffee_tree_rust.xml", &system.to_sbml())?;
 
-            let df_0_50 = Interchange::from_polars_0_51(df)?.to_polars_0_50()?;
+    let df_0_50 = Interchange::from_polars_0_51(df.clone())?.to_polars_0_50()?;
+    LinePlot::builder()
+        .data(&df_0_50)
+        .x("t")
+        .y(species[0])
+        .additional_lines(species[1..].to_vec())
+        .size(12)
+        .colors(vec![Rgb(0, 255, 0), Rgb(255, 0, 0), Rgb(0, 0, 255)])
+        .plot_title("Base line")
+        .x_title("Time [in hours]")
+        .y_title("Population in size")
+        .build()
+        .write_image("p1.svg", 1000, 600, 1.0)?;
+    Ok(df)
+}
 
-            let mut html = r#"<!DOCTYPE html>
+/// Noisy observations of the trees every 25 hours, written to `observations.csv` and fitted
+/// again from a wrong start.
+fn estimation(
+    system: &CoffeeRust,
+    df: &DataFrame,
+    rng: &mut ChaCha8Rng,
+) -> Result<(String, Fit<CoffeeRust>, Observations)> {
+    let every =
+        BooleanChunked::from_iter_values("every".into(), (0..df.height()).map(|i| i % 25 == 0));
+    let mut observed = df.select(["t", "T_s", "T_i"])?.filter(&every)?;
+    let rows = observed.height();
+    for name in ["T_s", "T_i"] {
+        let noisy: Vec<f64> = observed
+            .column(name)?
+            .f64()?
+            .into_no_null_iter()
+            .map(|v| v * (1.0 + 0.05 * (2.0 * rng.random::<f64>() - 1.0)))
+            .collect();
+        observed.replace(name, Series::new(name.into(), noisy))?;
+    }
+    CsvWriter::new(std::fs::File::create("observations.csv")?).finish(&mut observed)?;
+    let observations = Observations::from_csv("observations.csv")?;
+    let mut guess = *system;
+    guess.set("beta", 2.0 * system.beta)?;
+    guess.set("gamma", 0.5 * system.gamma)?;
+    let fit = Estimation::new(&["beta", "gamma"]).run(&guess, &observations)?;
+    let mut markdown = format!(
+        "Fitted to {} observations in {} iterations, weighted SSR {:.3e}.\n\n| parameter | true | start | estimate | 95% interval |\n|---|---|---|---|---|\n",
+        rows, fit.iterations, fit.cost
+    );
+    for estimate in &fit.estimates {
+        let name = estimate.name.as_str();
+        println!(
+            "Estimated {} = {:.4e} ± {:.1e}",
+            name, estimate.value, estimate.standard_error
+        );
+        markdown.push_str(&format!(
+            "| {} | {:.4e} | {:.4e} | {:.4e} | [{:.4e}, {:.4e}] |\n",
+            name,
+            system.get(name).unwrap_or_default(),
+            guess.get(name).unwrap_or_default(),
+            estimate.value,
+            estimate.interval.0,
+            estimate.interval.1
+        ));
+    }
+    for (name, residuals) in &fit.residuals {
+        let rms = (residuals.iter().map(|r| r * r).sum::<f64>() / rows as f64).sqrt();
+        markdown.push_str(&format!("\nRMS residual of {}: {:.3}\n", name, rms));
+    }
+    Ok((markdown, fit, observations))
+}
+
+/// Posterior of the fitted parameters with the noise level of the observations, written to
+/// `posterior.csv`, and its predictive band of `T_i` to `posterior_predictive.svg`.
+fn posterior(
+    system: &CoffeeRust,
+    fit: &Fit<CoffeeRust>,
+    observations: &Observations,
+    rng: &mut ChaCha8Rng,
+) -> Result<String> {
+    let priors = ["beta", "gamma"].map(|name| {
+        let value = system.get(name).unwrap_or_default();
+        (
+            name,
+            Prior::LogUniform {
+                min: value / 100.0,
+                max: value * 100.0,
+            },
+        )
+    });
+    let mut ensemble = Ensemble::new(priors.to_vec()).steps(1000).burn_in(300);
+    for (name, values) in &observations.columns {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        ensemble = ensemble.sigma(name, 0.03 * mean);
+    }
+    let posterior = ensemble.run(&fit.model, observations, rng)?;
+    let mut chains = DataFrame::new(
+        posterior
+            .columns()
+            .into_iter()
+            .map(|(name, values)| Column::new(name.into(), values))
+            .collect(),
+    )?;
+    CsvWriter::new(std::fs::File::create("posterior.csv")?).finish(&mut chains)?;
+    let r_hat = posterior.r_hat().ok_or("too few steps for R-hat")?;
+    let ess = posterior.ess();
+    let (mean, low, high) = (
+        posterior.mean(),
+        posterior.quantile(0.025),
+        posterior.quantile(0.975),
+    );
+    let mut table = format!(
+        "{} walkers, acceptance {:.2}.\n\n| parameter | mean | 95% credible interval | R-hat | ESS |\n|---|---|---|---|---|\n",
+        posterior.chains.len(),
+        posterior.acceptance
+    );
+    for (i, name) in posterior.parameters.iter().enumerate() {
+        println!(
+            "Posterior {}: {:.4e} [{:.4e}, {:.4e}], R-hat {:.3}, ESS {:.0}",
+            name, mean[i], low[i], high[i], r_hat[i], ess[i]
+        );
+        table.push_str(&format!(
+            "| {} | {:.4e} | [{:.4e}, {:.4e}] | {:.3} | {:.0} |\n",
+            name, mean[i], low[i], high[i], r_hat[i], ess[i]
+        ));
+    }
+    let bands = posterior.predictive(&fit.model, &observations.t, 200, 0.9, rng)?;
+    let infected = &bands[1];
+    let predictive = DataFrame::new(vec![
+        Column::new("t".into(), infected.t.clone()),
+        Column::new("observed".into(), observations.columns[1].1.clone()),
+        Column::new("median".into(), infected.median.clone()),
+        Column::new("5%".into(), infected.lower.clone()),
+        Column::new("95%".into(), infected.upper.clone()),
+    ])?;
+    let predictive_df = Interchange::from_polars_0_51(predictive)?.to_polars_0_50()?;
+
+    LinePlot::builder()
+        .data(&predictive_df)
+        .x("t")
+        .y("observed")
+        .additional_lines(vec!["median", "5%", "95%"])
+        .size(12)
+        .colors(vec![
+            Rgb(0, 0, 0),
+            Rgb(255, 0, 0),
+            Rgb(255, 160, 160),
+            Rgb(255, 160, 160),
+        ])
+        .plot_title("Posterior predictive T_i (90% band)")
+        .x_title("Time [in hours]")
+        .y_title("Population in size")
+        .build()
+        .write_image("posterior_predictive.svg", 1000, 600, 1.0)?;
+    let mut markdown = "![posterior predictive](posterior_predictive.svg) \n\n".to_string();
+    markdown.push_str(&table);
+    Ok(markdown)
+}
+
+/// Large copy numbers make exact simulation slow; compares the approximations with it.
+fn approximations(system: &CoffeeRust) -> String {
+    let network = system.network();
+    let times: Vec<f64> = (0..=28).map(|k| 25.0 * k as f64).collect();
+    let start = std::time::Instant::now();
+    let exact = Replicates::run(&network, stochastic::Method::Direct, &times, 200, 7);
+    let mut markdown = format!(
+        "200 replicates to 700 hours; exact SSA took {:.2} s.\n\n| method | seconds | species | mean error | std ratio | KS distance |\n|---|---|---|---|---|---|\n",
+        start.elapsed().as_secs_f64()
+    );
+    for (label, approximation) in [
+        ("tau-leaping", Approximation::TauLeaping { epsilon: 0.03 }),
+        (
+            "Euler–Maruyama",
+            Approximation::Langevin {
+                dt: 0.5,
+                scheme: Scheme::EulerMaruyama,
+            },
+        ),
+        (
+            "Milstein",
+            Approximation::Langevin {
+                dt: 0.5,
+                scheme: Scheme::Milstein,
+            },
+        ),
+    ] {
+        let start = std::time::Instant::now();
+        let replicates = approximation.replicates(&network, &times, 200, 8);
+        let seconds = start.elapsed().as_secs_f64();
+        for deviation in compare(&exact, &replicates) {
+            println!(
+                "{} {}: {:.2} s, mean error {:.2e}, std ratio {:.3}, KS {:.3}",
+                label,
+                deviation.species,
+                seconds,
+                deviation.mean_error,
+                deviation.std_ratio,
+                deviation.ks_distance
+            );
+            markdown.push_str(&format!(
+                "| {} | {:.2} | {} | {:.2e} | {:.3} | {:.3} |\n",
+                label,
+                seconds,
+                deviation.species,
+                deviation.mean_error,
+                deviation.std_ratio,
+                deviation.ks_distance
+            ));
+        }
+    }
+    markdown
+}
+
+/// Infection and recovery rates at half, once and twice their values, run in parallel and
+/// written in long format to `sweep.csv` and `sweep.svg`.
+fn sweep(system: &CoffeeRust) -> Result<String> {
+    let defaults = CoffeeRust::default();
+    let sweep = Sweep::grid(&[
+        ("beta", [0.5, 1.0, 2.0].map(|f| f * defaults.beta).to_vec()),
+        (
+            "gamma",
+            [0.5, 1.0, 2.0].map(|f| f * defaults.gamma).to_vec(),
+        ),
+    ]);
+    let start = std::time::Instant::now();
+    let runs = sweep.run(system, &uniform(0.0, T_END, 141))?;
+    let seconds = start.elapsed().as_secs_f64();
+    let mut sweep_df = runs.data_frame()?;
+    let labels: Vec<String> = sweep_df
+        .column("sweep")?
+        .u32()?
+        .into_no_null_iter()
+        .map(|id| {
+            let values = &runs.runs[id as usize].values;
+            format!(
+                "beta x{:.1}, gamma x{:.1}",
+                values[0] / defaults.beta,
+                values[1] / defaults.gamma
+            )
+        })
+        .collect();
+    sweep_df.with_column(Column::new("label".into(), labels))?;
+    CsvWriter::new(std::fs::File::create("sweep.csv")?).finish(&mut sweep_df)?;
+    let infected_df = Interchange::from_polars_0_51(
+        sweep_df
+            .lazy()
+            .filter(col("species").eq(lit("T_i")))
+            .collect()?,
+    )?
+    .to_polars_0_50()?;
+
+    ScatterPlot::builder()
+        .data(&infected_df)
+        .x("t")
+        .y("value")
+        .group("label")
+        .size(4)
+        .plot_title("T_i over a grid of beta and gamma")
+        .x_title("Time [in hours]")
+        .y_title("Infected trees")
+        .build()
+        .write_image("sweep.svg", 1000, 600, 1.0)?;
+    let mut markdown = "![parameter sweep](sweep.svg) \n\n".to_string();
+    markdown.push_str(&format!(
+        "{} runs on {} threads in {:.2} s.\n\n| beta | gamma | T_i at 700 hours |\n|---|---|---|\n",
+        runs.runs.len(),
+        std::thread::available_parallelism().map_or(1, |n| n.get()),
+        seconds
+    ));
+    for run in &runs.runs {
+        markdown.push_str(&format!(
+            "| {:.3e} | {:.3e} | {:.1} |\n",
+            run.values[0],
+            run.values[1],
+            run.states.last().map_or(f64::NAN, |y| y[1])
+        ));
+    }
+    Ok(markdown)
+}
+
+/// Renders the report to `line.html`.
+fn page(markdown: &str) -> Result<()> {
+    let mut html = r#"<!DOCTYPE html>
                             <html>
                             <head>
                                <link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/katex@0.16.25/dist/katex.min.css" integrity="sha384-WcoG4HRXMzYzfCgiyfrySxx90XSl2rxY5mnVY5TwtWE6KLrArNKn0T/mOgNL0Mmi" crossorigin="anonymous">
@@ -111,50 +656,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             </head>
                             <body>
                             <div>"#
-                .to_string();
-
-            LinePlot::builder()
-                .data(&df_0_50)
-                .x("t")
-                .y("T<sub>s</sub>(t)")
-                .additional_lines(vec!["T<sub>i</sub>(t)", "S(t)"])
-                .size(12)
-                .colors(vec![Rgb(0, 255, 0), Rgb(255, 0, 0), Rgb(0, 0, 255)])
-                .plot_title("Base line")
-                .x_title("Time [in hours]")
-                .y_title("Population in size")
-                .build()
-                .write_image("p1.svg", 1000, 600, 1.0)?;
-
-            let mut options = Options::default();
-            options.extension.table = true;
-            let mut markdown = "# Mathematical model of coffee tree's rust control using snail as biological agents\n".to_string();
-            
-            markdown.push_str(r#"$$
-            \frac{dT_s(t)}{dt} = a T_s(t) - \beta T_s(t) T_i(t) + k S(t) T_i(t)
-            $$
-            "#);
-            markdown.push_str(r#"$$
-            \frac{dT_s(t)}{dt} = \beta T_s(t) T_i(t) - k S(t) T_i(t) - \gamma T_i(t)
-            $$
-            "#);
-            markdown.push_str(r#"$$
-            \frac{dT_s(t)}{dt} = b S(t) T_i(t) - d S(t)
-            $$
-            "#);
-            markdown.push_str("![alt text](p1.svg) \n");
+        .to_string();
 
-            html.push_str(markdown_to_html(markdown.as_str(), &options).as_str());
-            html.push_str(
-                r#"</div>
+    let mut options = Options::default();
+    options.extension.table = true;
+    html.push_str(markdown_to_html(markdown, &options).as_str());
+    html.push_str(
+        r#"</div>
                   </body>
                   </html>"#,
-            );
-
-            std::fs::write("line.html", html)?;
-        }
-        Err(e) => println!("❌ Integration error: {}", e),
-    }
+    );
 
+    std::fs::write("line.html", html)?;
     Ok(())
 }
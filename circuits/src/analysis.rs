@@ -0,0 +1,133 @@
+//! Step-response and oscillation measures of a single trajectory.
+//!
+//! For a [`Response`] the trajectory is assumed to start at the moment of the step and to run
+//! long enough to reach its new steady state, which is taken to be the last sample. Times are
+//! measured from the first sample. An [`Oscillation`] is measured on the last cycles of a
+//! trajectory, after transients.
+
+/// Band around the steady state, relative to the size of the step, used for the settling time.
+pub const SETTLING_BAND: f64 = 0.02;
+
+/// Steps smaller than this fraction of the larger of the two levels are taken to be round-off
+/// of the integrator rather than a response.
+pub const NEGLIGIBLE_STEP: f64 = 1e-6;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Response {
+    pub initial: f64,
+    pub steady_state: f64,
+    /// Time to cover half of the distance from `initial` to `steady_state`.
+    pub rise_time: f64,
+    /// Largest excursion beyond the steady state, relative to the size of the step.
+    pub overshoot: f64,
+    /// Time after which the trajectory stays within [`SETTLING_BAND`] of the steady state.
+    pub settling_time: f64,
+}
+
+impl Response {
+    /// Measures the response of the samples `x` at times `t`.
+    ///
+    /// Returns `None` for fewer than two samples or a trajectory that ends where it started, to
+    /// within [`NEGLIGIBLE_STEP`].
+    pub fn new(t: &[f64], x: &[f64]) -> Option<Response> {
+        let n = t.len().min(x.len());
+        if n < 2 {
+            return None;
+        }
+        let (initial, steady_state) = (x[0], x[n - 1]);
+        let step = steady_state - initial;
+        if step.abs() <= NEGLIGIBLE_STEP * initial.abs().max(steady_state.abs()) {
+            return None;
+        }
+        let overshoot = x[..n]
+            .iter()
+            .map(|xi| (xi - steady_state) / step)
+            .fold(0.0, f64::max);
+        let band = SETTLING_BAND * step.abs();
+        let settled = x[..n]
+            .iter()
+            .rposition(|xi| (xi - steady_state).abs() > band)
+            .map_or(0, |i| i + 1);
+        Some(Response {
+            initial,
+            steady_state,
+            rise_time: crossing(&t[..n], &x[..n], initial + 0.5 * step)? - t[0],
+            overshoot,
+            settling_time: t[settled.min(n - 1)] - t[0],
+        })
+    }
+}
+
+/// Period and size of the last cycles of an oscillating trajectory.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Oscillation {
+    /// Mean time between the last peaks.
+    pub period: f64,
+    /// Difference between the last peak and the lowest value of the last cycle.
+    pub amplitude: f64,
+    /// Amplitude of the last cycle relative to the one before; close to 1 when the oscillation
+    /// is sustained and below 1 when it is damped.
+    pub ratio: f64,
+}
+
+impl Oscillation {
+    /// Measures the oscillation of the samples `x` at times `t` from their local maxima,
+    /// refined by a parabola through each maximum and its neighbours.
+    ///
+    /// Returns `None` with fewer than three peaks, as for a trajectory settling monotonically.
+    pub fn new(t: &[f64], x: &[f64]) -> Option<Oscillation> {
+        let n = t.len().min(x.len());
+        let peaks: Vec<usize> = (1..n.saturating_sub(1))
+            .filter(|i| x[*i] > x[i - 1] && x[*i] >= x[i + 1])
+            .collect();
+        if peaks.len() < 3 {
+            return None;
+        }
+        let vertex = |i: usize| {
+            let (a, b, c) = (x[i - 1], x[i], x[i + 1]);
+            let curvature = a - 2.0 * b + c;
+            let shift = if curvature < 0.0 {
+                0.5 * (a - c) / curvature
+            } else {
+                0.0
+            };
+            t[i] + shift * (t[i + 1] - t[i - 1]) / 2.0
+        };
+        let used = &peaks[peaks.len().saturating_sub(6)..];
+        let period = (vertex(used[used.len() - 1]) - vertex(used[0])) / (used.len() - 1) as f64;
+        let cycle = |from: usize, to: usize| {
+            let low = x[from..=to].iter().copied().fold(f64::INFINITY, f64::min);
+            x[to] - low
+        };
+        let k = peaks.len();
+        let amplitude = cycle(peaks[k - 2], peaks[k - 1]);
+        let previous = cycle(peaks[k - 3], peaks[k - 2]);
+        Some(Oscillation {
+            period,
+            amplitude,
+            ratio: if previous > 0.0 {
+                amplitude / previous
+            } else {
+                f64::NAN
+            },
+        })
+    }
+
+    /// Whether the last cycle kept at least `fraction` of the amplitude of the one before.
+    pub fn is_sustained(&self, fraction: f64) -> bool {
+        self.ratio >= fraction
+    }
+}
+
+/// First time at which `x` reaches `level`, interpolated linearly between samples.
+pub fn crossing(t: &[f64], x: &[f64], level: f64) -> Option<f64> {
+    let rising = x.first()? <= &level;
+    let i = x
+        .iter()
+        .position(|xi| if rising { *xi >= level } else { *xi <= level })?;
+    if i == 0 {
+        return Some(t[0]);
+    }
+    let (t0, t1, x0, x1) = (t[i - 1], t[i], x[i - 1], x[i]);
+    Some(t0 + (t1 - t0) * (level - x0) / (x1 - x0))
+}
@@ -0,0 +1,722 @@
+//! Boolean networks: genes that are on or off and switch by logical rules.
+//!
+//! Rules are written in the usual notation, with `!`, `&` and `|` or `not`, `and` and `or`:
+//!
+//! ```
+//! use circuits::boolean::{BooleanNetwork, Update};
+//!
+//! let toggle = BooleanNetwork::new()
+//!     .rule("u", "!v")?
+//!     .rule("v", "!u")?;
+//! let attractors = toggle.attractors(Update::Asynchronous)?;
+//! assert_eq!(attractors.len(), 2);
+//! # Ok::<(), circuits::Error>(())
+//! ```
+//!
+//! A gene without a rule keeps its value, which makes it an input. Genes are updated all at
+//! once ([`Update::Synchronous`]), one at a time in random order ([`Update::Asynchronous`]) or
+//! each with a probability per step ([`Update::Probabilistic`]). Attractors are the terminal
+//! strongly connected components of the state transition graph; [`BooleanNetwork::attractors`]
+//! finds all of them for networks of up to 16 genes and [`BooleanNetwork::sampled_attractors`]
+//! those reached from random states of larger ones.
+//!
+//! [`BooleanNetwork::from_gene_network`] abstracts a Hill model to the Boolean network of its
+//! steep limit, in which a regulator is on above the threshold of its outgoing edges.
+//!
+//! [`discretize`] maps a continuous state of the Hill model onto the genes of the abstraction.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use rand::Rng;
+
+use crate::gene_network::GeneNetwork;
+use crate::model::{Error, Model, State};
+use crate::regulation::Sign;
+
+/// Largest network whose state space is enumerated.
+pub const MAX_EXHAUSTIVE: usize = 16;
+/// Largest network whose state space is enumerated under probabilistic updates, where a
+/// state can lead to every subset of its changing genes being updated.
+pub const MAX_EXHAUSTIVE_PROBABILISTIC: usize = 10;
+
+/// Logical rule over the genes of a network, which are referred to by index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Const(bool),
+    Var(usize),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    /// Value of the rule in the state with gene `i` on when bit `i` of `x` is set.
+    fn eval(&self, x: u64) -> bool {
+        match self {
+            Expr::Const(value) => *value,
+            Expr::Var(i) => x >> i & 1 == 1,
+            Expr::Not(e) => !e.eval(x),
+            Expr::And(terms) => terms.iter().all(|e| e.eval(x)),
+            Expr::Or(terms) => terms.iter().any(|e| e.eval(x)),
+        }
+    }
+
+    /// Short sum of products for the truth table `on` over the genes `inputs`, where entry
+    /// `j` of the table is the row in which input `i` is on when bit `i` of `j` is set.
+    pub fn from_truth_table(inputs: &[usize], on: &[bool]) -> Expr {
+        if on.iter().all(|v| *v) {
+            return Expr::Const(true);
+        }
+        if on.iter().all(|v| !*v) {
+            return Expr::Const(false);
+        }
+        // A product term is a mask of the inputs it fixes and their values.
+        let covers = |(mask, values): (usize, usize), row: usize| row & mask == values;
+        let implicant =
+            |term: (usize, usize)| (0..on.len()).all(|row| !covers(term, row) || on[row]);
+        let full = (1 << inputs.len()) - 1;
+        let mut terms: Vec<(usize, usize)> = Vec::new();
+        for row in (0..on.len()).filter(|row| on[*row]) {
+            if terms.iter().any(|t| covers(*t, row)) {
+                continue;
+            }
+            // Drop literals while the term stays inside the table.
+            let mut term = (full, row);
+            for i in 0..inputs.len() {
+                let wider = (term.0 & !(1 << i), term.1 & !(1 << i));
+                if implicant(wider) {
+                    term = wider;
+                }
+            }
+            terms.push(term);
+        }
+        let product = |(mask, values): (usize, usize)| {
+            let literals: Vec<Expr> = (0..inputs.len())
+                .filter(|i| mask >> i & 1 == 1)
+                .map(|i| {
+                    let var = Expr::Var(inputs[i]);
+                    if values >> i & 1 == 1 {
+                        var
+                    } else {
+                        Expr::Not(Box::new(var))
+                    }
+                })
+                .collect();
+            match literals.len() {
+                1 => literals.into_iter().next().expect("one literal"),
+                _ => Expr::And(literals),
+            }
+        };
+        let mut products: Vec<Expr> = terms.into_iter().map(product).collect();
+        match products.len() {
+            1 => products.pop().expect("one product"),
+            _ => Expr::Or(products),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, names: &[String], nested: bool) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter, terms: &[Expr], op: &str| {
+            if nested {
+                write!(f, "(")?;
+            }
+            for (i, e) in terms.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                e.write(f, names, true)?;
+            }
+            if nested {
+                write!(f, ")")?;
+            }
+            Ok(())
+        };
+        match self {
+            Expr::Const(value) => write!(f, "{}", value),
+            Expr::Var(i) => write!(f, "{}", names[*i]),
+            Expr::Not(e) => {
+                write!(f, "!")?;
+                e.write(f, names, true)
+            }
+            Expr::And(terms) => join(f, terms, "&"),
+            Expr::Or(terms) => join(f, terms, "|"),
+        }
+    }
+}
+
+/// Rule of a network with the names of its genes, for printing.
+pub struct Named<'a> {
+    expr: &'a Expr,
+    names: &'a [String],
+}
+
+impl fmt::Display for Named<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.expr.write(f, self.names, false)
+    }
+}
+
+/// How the genes are updated in one step.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Update {
+    /// All genes at once.
+    Synchronous,
+    /// One gene, chosen at random.
+    Asynchronous,
+    /// Each gene independently with the given probability; at 0 nothing changes and at 1 the
+    /// update is synchronous.
+    Probabilistic(f64),
+}
+
+/// Attractor of a Boolean network with the share of states that reach it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attractor {
+    /// States of the attractor, in the order they are visited for a synchronous cycle.
+    pub states: Vec<Vec<bool>>,
+    /// Fraction of the state space, or of the sampled states, from which the attractor is
+    /// reached. Under asynchronous updates one state may reach several attractors, so the
+    /// basins can overlap.
+    pub basin: f64,
+}
+
+impl Attractor {
+    pub fn is_fixed_point(&self) -> bool {
+        self.states.len() == 1
+    }
+}
+
+/// Network of genes with Boolean rules.
+#[derive(Clone, Debug, Default)]
+pub struct BooleanNetwork {
+    names: Vec<String>,
+    rules: Vec<Option<Expr>>,
+}
+
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (i, c) in text.char_indices() {
+            let word = c.is_alphanumeric() || c == '_' || c == '.' || c == '\'';
+            match (word, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    tokens.push(&text[s..i]);
+                    start = None;
+                }
+                _ => {}
+            }
+            if !word && !c.is_whitespace() {
+                tokens.push(&text[i..i + c.len_utf8()]);
+            }
+        }
+        if let Some(s) = start {
+            tokens.push(&text[s..]);
+        }
+        Parser {
+            tokens,
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn accept(&mut self, symbols: &[&str]) -> bool {
+        match self.peek() {
+            Some(t) if symbols.iter().any(|s| t.eq_ignore_ascii_case(s)) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn or(&mut self, network: &mut BooleanNetwork) -> Result<Expr, Error> {
+        let mut terms = vec![self.and(network)?];
+        while self.accept(&["|", "or"]) {
+            terms.push(self.and(network)?);
+        }
+        Ok(match terms.len() {
+            1 => terms.pop().expect("one term"),
+            _ => Expr::Or(terms),
+        })
+    }
+
+    fn and(&mut self, network: &mut BooleanNetwork) -> Result<Expr, Error> {
+        let mut terms = vec![self.unary(network)?];
+        while self.accept(&["&", "and"]) {
+            terms.push(self.unary(network)?);
+        }
+        Ok(match terms.len() {
+            1 => terms.pop().expect("one term"),
+            _ => Expr::And(terms),
+        })
+    }
+
+    fn unary(&mut self, network: &mut BooleanNetwork) -> Result<Expr, Error> {
+        if self.accept(&["!", "~", "not"]) {
+            return Ok(Expr::Not(Box::new(self.unary(network)?)));
+        }
+        if self.accept(&["("]) {
+            let e = self.or(network)?;
+            if !self.accept(&[")"]) {
+                return Err(Error::Parse("missing `)`".to_string()));
+            }
+            return Ok(e);
+        }
+        let token = self
+            .peek()
+            .ok_or_else(|| Error::Parse("rule ends early".to_string()))?;
+        self.position += 1;
+        match token.to_ascii_lowercase().as_str() {
+            "true" | "1" => Ok(Expr::Const(true)),
+            "false" | "0" => Ok(Expr::Const(false)),
+            _ if token
+                .chars()
+                .all(|c| c.is_alphanumeric() || "_.'".contains(c)) =>
+            {
+                Ok(Expr::Var(network.add_node(token)))
+            }
+            _ => Err(Error::Parse(format!("unexpected `{}`", token))),
+        }
+    }
+}
+
+impl BooleanNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_node(&mut self, name: &str) -> usize {
+        match self.index(name) {
+            Some(i) => i,
+            None => {
+                self.names.push(name.to_string());
+                self.rules.push(None);
+                self.names.len() - 1
+            }
+        }
+    }
+
+    /// Adds a gene without a rule, an input that keeps its value.
+    pub fn node(mut self, name: &str) -> Self {
+        self.add_node(name);
+        self
+    }
+
+    /// Sets the rule of `gene`, adding it and the genes the rule names when they are new.
+    pub fn rule(mut self, gene: &str, rule: &str) -> Result<Self, Error> {
+        let i = self.add_node(gene);
+        let mut parser = Parser::new(rule);
+        let expr = parser.or(&mut self)?;
+        if let Some(token) = parser.peek() {
+            return Err(Error::Parse(format!(
+                "unexpected `{}` in `{}`",
+                token, rule
+            )));
+        }
+        self.rules[i] = Some(expr);
+        Ok(self)
+    }
+
+    /// Sets the rule of gene `i` to `expr`.
+    pub fn set_rule(&mut self, i: usize, expr: Expr) {
+        self.rules[i] = Some(expr);
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// Rule of gene `i` for printing, `None` for an input.
+    pub fn rule_of(&self, i: usize) -> Option<Named<'_>> {
+        self.rules[i].as_ref().map(|expr| Named {
+            expr,
+            names: &self.names,
+        })
+    }
+
+    /// State with the genes `on` switched on and all others off.
+    pub fn state(&self, on: &[&str]) -> Vec<bool> {
+        self.names
+            .iter()
+            .map(|name| on.contains(&name.as_str()))
+            .collect()
+    }
+
+    fn check(&self, limit: usize) -> Result<(), Error> {
+        if self.len() > limit {
+            return Err(Error::OutOfBounds {
+                name: "genes".to_string(),
+                value: self.len() as f64,
+            });
+        }
+        Ok(())
+    }
+
+    /// Bits of `state`, which needs one value for each of at most 64 genes.
+    fn encode(&self, state: &[bool]) -> Result<u64, Error> {
+        self.check(64)?;
+        if state.len() != self.len() {
+            return Err(Error::Dimension {
+                expected: self.len(),
+                found: state.len(),
+            });
+        }
+        Ok(state
+            .iter()
+            .enumerate()
+            .fold(0, |x, (i, on)| x | (*on as u64) << i))
+    }
+
+    fn decode(&self, x: u64) -> Vec<bool> {
+        (0..self.len()).map(|i| x >> i & 1 == 1).collect()
+    }
+
+    /// Value of every rule in state `x`, inputs keeping theirs.
+    fn next(&self, x: u64) -> u64 {
+        self.rules.iter().enumerate().fold(0, |y, (i, rule)| {
+            let on = match rule {
+                Some(rule) => rule.eval(x),
+                None => x >> i & 1 == 1,
+            };
+            y | (on as u64) << i
+        })
+    }
+
+    fn step_code<R: Rng>(&self, x: u64, update: Update, rng: &mut R) -> u64 {
+        let target = self.next(x);
+        match update {
+            _ if self.is_empty() => x,
+            Update::Synchronous => target,
+            Update::Asynchronous => {
+                let bit = 1 << rng.random_range(0..self.len());
+                (x & !bit) | (target & bit)
+            }
+            Update::Probabilistic(p) if p <= 0.0 => x,
+            Update::Probabilistic(p) if p >= 1.0 => target,
+            Update::Probabilistic(p) => {
+                let mask = (0..self.len())
+                    .filter(|_| rng.random_bool(p))
+                    .fold(0u64, |m, i| m | 1 << i);
+                (x & !mask) | (target & mask)
+            }
+        }
+    }
+
+    /// States one step after `x` can lead to; `x` itself when it is stationary. Under
+    /// probabilistic updates any subset of the changing genes may update, so the states are
+    /// produced lazily.
+    fn successors(&self, x: u64, update: Update) -> Box<dyn Iterator<Item = u64> + '_> {
+        let target = self.next(x);
+        let changing = x ^ target;
+        match update {
+            _ if changing == 0 => Box::new(std::iter::once(x)),
+            Update::Synchronous => Box::new(std::iter::once(target)),
+            Update::Probabilistic(p) if p <= 0.0 => Box::new(std::iter::once(x)),
+            Update::Probabilistic(p) if p >= 1.0 => Box::new(std::iter::once(target)),
+            Update::Asynchronous => Box::new(
+                (0..self.len())
+                    .filter(move |i| changing >> i & 1 == 1)
+                    .map(move |i| x ^ 1 << i),
+            ),
+            Update::Probabilistic(_) => {
+                let bits: Vec<u64> = (0..self.len())
+                    .map(|i| 1 << i)
+                    .filter(|b| changing & b != 0)
+                    .collect();
+                Box::new((0..1u128 << bits.len()).map(move |subset| {
+                    bits.iter()
+                        .enumerate()
+                        .filter(|(j, _)| subset >> j & 1 == 1)
+                        .fold(x, |y, (_, b)| y ^ b)
+                }))
+            }
+        }
+    }
+
+    /// One update of `state`, which needs one value per gene.
+    pub fn step<R: Rng>(
+        &self,
+        state: &[bool],
+        update: Update,
+        rng: &mut R,
+    ) -> Result<Vec<bool>, Error> {
+        let x = self.encode(state)?;
+        Ok(self.decode(self.step_code(x, update, rng)))
+    }
+
+    /// `steps` updates from `initial`, starting with `initial`, which needs one value per gene.
+    pub fn simulate<R: Rng>(
+        &self,
+        initial: &[bool],
+        update: Update,
+        steps: usize,
+        rng: &mut R,
+    ) -> Result<Vec<Vec<bool>>, Error> {
+        let mut x = self.encode(initial)?;
+        let mut states = vec![initial.to_vec()];
+        for _ in 0..steps {
+            x = self.step_code(x, update, rng);
+            states.push(self.decode(x));
+        }
+        Ok(states)
+    }
+
+    /// Terminal strongly connected components of the transitions between `states`, which
+    /// must hold every successor of its members.
+    fn terminal_components(&self, states: &[u64], update: Update) -> Vec<Vec<u64>> {
+        let index: HashMap<u64, usize> = states.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+        let mut graph = DiGraph::<u64, ()>::with_capacity(states.len(), states.len());
+        for x in states {
+            graph.add_node(*x);
+        }
+        for (i, x) in states.iter().enumerate() {
+            for y in self.successors(*x, update) {
+                graph.add_edge(NodeIndex::new(i), NodeIndex::new(index[&y]), ());
+            }
+        }
+        tarjan_scc(&graph)
+            .into_iter()
+            .filter(|component| {
+                let members: HashSet<NodeIndex> = component.iter().copied().collect();
+                component.iter().all(|i| {
+                    graph
+                        .edges_directed(*i, Direction::Outgoing)
+                        .all(|e| members.contains(&e.target()))
+                })
+            })
+            .map(|component| self.order(component.iter().map(|i| graph[*i]).collect(), update))
+            .collect()
+    }
+
+    /// States of an attractor along its cycle when updates are synchronous, sorted otherwise.
+    fn order(&self, mut states: Vec<u64>, update: Update) -> Vec<u64> {
+        states.sort_unstable();
+        let deterministic = matches!(update, Update::Synchronous)
+            || matches!(update, Update::Probabilistic(p) if p >= 1.0);
+        if deterministic {
+            let mut cycle = vec![states[0]];
+            while cycle.len() < states.len() {
+                cycle.push(self.next(cycle[cycle.len() - 1]));
+            }
+            return cycle;
+        }
+        states
+    }
+
+    /// All attractors and the share of the state space that reaches each, for networks of up
+    /// to [`MAX_EXHAUSTIVE`] genes, or [`MAX_EXHAUSTIVE_PROBABILISTIC`] under probabilistic
+    /// updates.
+    pub fn attractors(&self, update: Update) -> Result<Vec<Attractor>, Error> {
+        match update {
+            Update::Probabilistic(p) if p > 0.0 && p < 1.0 => {
+                self.check(MAX_EXHAUSTIVE_PROBABILISTIC)?
+            }
+            _ => self.check(MAX_EXHAUSTIVE)?,
+        }
+        let states: Vec<u64> = (0..1u64 << self.len()).collect();
+        let components = self.terminal_components(&states, update);
+        let mut predecessors = vec![Vec::new(); states.len()];
+        for x in &states {
+            for y in self.successors(*x, update) {
+                predecessors[y as usize].push(*x);
+            }
+        }
+        Ok(components
+            .into_iter()
+            .map(|component| {
+                let mut reached: HashSet<u64> = component.iter().copied().collect();
+                let mut queue: VecDeque<u64> = component.iter().copied().collect();
+                while let Some(y) = queue.pop_front() {
+                    for x in &predecessors[y as usize] {
+                        if reached.insert(*x) {
+                            queue.push_back(*x);
+                        }
+                    }
+                }
+                Attractor {
+                    states: component.iter().map(|x| self.decode(*x)).collect(),
+                    basin: reached.len() as f64 / states.len() as f64,
+                }
+            })
+            .collect())
+    }
+
+    /// Attractors reached from `samples` random states, with the fraction of samples that
+    /// reach each. Every sample is updated `steps` times before the states it can still reach
+    /// are enumerated; more than `limit` of them is an error.
+    pub fn sampled_attractors<R: Rng>(
+        &self,
+        update: Update,
+        samples: usize,
+        steps: usize,
+        limit: usize,
+        rng: &mut R,
+    ) -> Result<Vec<Attractor>, Error> {
+        self.check(64)?;
+        let mut found: Vec<(Vec<u64>, usize)> = Vec::new();
+        for _ in 0..samples {
+            let mut x = (0..self.len()).fold(0u64, |x, i| x | (rng.random_bool(0.5) as u64) << i);
+            for _ in 0..steps {
+                x = self.step_code(x, update, rng);
+            }
+            // The states still reachable contain at least one attractor.
+            let mut reachable = vec![x];
+            let mut seen: HashSet<u64> = HashSet::from([x]);
+            let mut i = 0;
+            while i < reachable.len() {
+                // Checked per state, as one state can have exponentially many successors.
+                for y in self.successors(reachable[i], update) {
+                    if seen.insert(y) {
+                        reachable.push(y);
+                    }
+                    if reachable.len() > limit {
+                        return Err(Error::NoConvergence(format!(
+                            "more than {} states reachable after {} steps",
+                            limit, steps
+                        )));
+                    }
+                }
+                i += 1;
+            }
+            let components = self.terminal_components(&reachable, update);
+            // Keep updating until the walk enters one of them.
+            let member = |x: u64| components.iter().position(|c| c.contains(&x));
+            let mut entered = member(x);
+            while entered.is_none() {
+                x = self.step_code(x, update, rng);
+                entered = member(x);
+            }
+            let mut component = components[entered.expect("entered an attractor")].clone();
+            component.sort_unstable();
+            match found.iter_mut().find(|(c, _)| {
+                let mut sorted = c.clone();
+                sorted.sort_unstable();
+                sorted == component
+            }) {
+                Some((_, count)) => *count += 1,
+                None => found.push((self.order(component, update), 1)),
+            }
+        }
+        Ok(found
+            .into_iter()
+            .map(|(component, count)| Attractor {
+                states: component.iter().map(|x| self.decode(*x)).collect(),
+                basin: count as f64 / samples.max(1) as f64,
+            })
+            .collect())
+    }
+
+    /// Boolean abstraction of a Hill model in the limit of steep regulation.
+    ///
+    /// Gene `G` is on when its level exceeds its threshold from [`thresholds`], and each Hill
+    /// term becomes 0 or 1 by whether the regulator is on. `G` is switched on by the inputs
+    /// for which `basal + beta * logic(terms)` is above `alpha` times its threshold, so
+    /// it is on whenever its steady level would be. A gene without regulators or removal
+    /// keeps its value.
+    pub fn from_gene_network(network: &GeneNetwork) -> Self {
+        let graph = network.graph();
+        let thresholds = thresholds(network);
+        let mut boolean = BooleanNetwork {
+            names: graph.node_weights().map(|g| g.name.clone()).collect(),
+            rules: vec![None; graph.node_count()],
+        };
+        for target in graph.node_indices() {
+            let gene = &graph[target];
+            let mut edges: Vec<_> = graph.edges_directed(target, Direction::Incoming).collect();
+            edges.sort_by_key(|e| e.id());
+            if edges.is_empty() && gene.alpha == 0.0 {
+                continue;
+            }
+            let inputs: Vec<usize> = edges.iter().map(|e| e.source().index()).collect();
+            let logic = network.gate(target);
+            let table: Vec<bool> = (0..1usize << inputs.len())
+                .map(|row| {
+                    let terms: Vec<f64> = edges
+                        .iter()
+                        .enumerate()
+                        .map(|(j, e)| {
+                            let on = row >> j & 1 == 1;
+                            let active = match e.weight().sign {
+                                Sign::Activation => on,
+                                Sign::Repression => !on,
+                            };
+                            if active {
+                                1.0
+                            } else {
+                                0.0
+                            }
+                        })
+                        .collect();
+                    let production = gene.basal + gene.beta * logic.combine(&terms);
+                    production > gene.alpha * thresholds[target.index()]
+                })
+                .collect();
+            boolean.rules[target.index()] = Some(Expr::from_truth_table(&inputs, &table));
+        }
+        boolean
+    }
+}
+
+/// Level above which each gene of `network` counts as on: the mean `k` of the Hill terms it
+/// regulates, or half its maximal level for a gene that regulates nothing; zero for such a
+/// gene that is not removed.
+pub fn thresholds(network: &GeneNetwork) -> Vec<f64> {
+    let graph = network.graph();
+    graph
+        .node_indices()
+        .map(|i| {
+            let ks: Vec<f64> = graph
+                .edges_directed(i, Direction::Outgoing)
+                .map(|e| e.weight().k)
+                .collect();
+            let gene = &graph[i];
+            if ks.is_empty() && gene.alpha > 0.0 {
+                0.5 * (gene.basal + gene.beta) / gene.alpha
+            } else if ks.is_empty() {
+                0.0
+            } else {
+                ks.iter().sum::<f64>() / ks.len() as f64
+            }
+        })
+        .collect()
+}
+
+/// Boolean state of the levels `x` of the genes of `network`, one level per gene.
+pub fn discretize(network: &GeneNetwork, x: &State) -> Result<Vec<bool>, Error> {
+    if x.len() != network.dim() {
+        return Err(Error::Dimension {
+            expected: network.dim(),
+            found: x.len(),
+        });
+    }
+    Ok(thresholds(network)
+        .iter()
+        .zip(x.iter())
+        .map(|(threshold, level)| level > threshold)
+        .collect())
+}
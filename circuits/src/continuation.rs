@@ -0,0 +1,617 @@
+//! One-parameter continuation of equilibria and limit cycles.
+//!
+//! Equilibrium branches are traced by pseudo-arclength continuation: every step predicts along
+//! the tangent of the branch and corrects with Newton on the equilibrium condition plus the
+//! arclength constraint, so the branch is followed around folds where the parameter turns
+//! back. The parameter is scaled to `[0, 1]` over the continuation range and the state by its
+//! size at the start, so models whose rates and populations differ by orders of magnitude take
+//! comparable steps in both.
+//!
+//! Folds are detected where the parameter component of the tangent changes sign and Hopf points
+//! where a complex pair of eigenvalues crosses the imaginary axis. Limit cycles born at a Hopf
+//! point are followed by single shooting with natural parameter steps.
+//!
+//! [`Continuation::run`] returns a [`Branch`]; [`Branch::follow_cycles`] adds the cycles of one of
+//! its Hopf points and [`Branch::write_csv`] exports the bifurcation diagram.
+
+use std::path::Path;
+
+use nalgebra::{Complex, DMatrix, DVector};
+use ode_solvers::{Dopri5, OutputType};
+
+use crate::model::{Error, Model, State};
+use crate::stability::{jacobian, parameter_derivative, steady_state, FixedPoint, Stability};
+
+const TOLERANCE: f64 = 1e-10;
+const NEWTON_ITERATIONS: usize = 12;
+
+fn rhs<M: Model>(model: &M, y: &State) -> State {
+    model.rhs(0.0, y)
+}
+
+/// A point on an equilibrium branch.
+#[derive(Clone, Debug)]
+pub struct BranchPoint {
+    pub parameter: f64,
+    pub state: State,
+    /// Eigenvalues of the Jacobian, sorted by decreasing real part.
+    pub eigenvalues: Vec<Complex<f64>>,
+    pub stability: Stability,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BifurcationKind {
+    /// The branch turns back in the parameter; a real eigenvalue passes through zero.
+    Fold,
+    /// A complex pair crosses the imaginary axis with angular frequency `frequency`.
+    Hopf { frequency: f64 },
+}
+
+/// A bifurcation located between two points of a branch by linear interpolation.
+#[derive(Clone, Debug)]
+pub struct Bifurcation {
+    pub kind: BifurcationKind,
+    pub parameter: f64,
+    pub state: State,
+    /// Index of the branch point just after the bifurcation.
+    pub index: usize,
+}
+
+/// A periodic orbit found by shooting.
+#[derive(Clone, Debug)]
+pub struct Cycle {
+    pub parameter: f64,
+    pub period: f64,
+    /// A point on the orbit.
+    pub state: State,
+    /// Componentwise minimum and maximum over one period.
+    pub min: State,
+    pub max: State,
+    /// Eigenvalues of the monodromy matrix, including the trivial multiplier 1.
+    pub multipliers: Vec<Complex<f64>>,
+    pub stable: bool,
+}
+
+/// Equilibria, bifurcations and limit cycles along one parameter.
+#[derive(Clone, Debug)]
+pub struct Branch {
+    pub parameter: String,
+    pub species: Vec<String>,
+    /// Points ordered along the branch.
+    pub points: Vec<BranchPoint>,
+    pub bifurcations: Vec<Bifurcation>,
+    pub cycles: Vec<Cycle>,
+}
+
+/// Settings of a pseudo-arclength continuation in one parameter.
+#[derive(Clone, Debug)]
+pub struct Continuation {
+    pub parameter: String,
+    pub min: f64,
+    pub max: f64,
+    /// Arclength step in scaled units, where the whole parameter range has length 1.
+    pub step: f64,
+    pub min_step: f64,
+    pub max_step: f64,
+    /// Maximum number of points in each direction from the start.
+    pub max_points: usize,
+}
+
+impl Continuation {
+    pub fn new(parameter: &str, min: f64, max: f64) -> Self {
+        Continuation {
+            parameter: parameter.to_string(),
+            min,
+            max,
+            step: 0.01,
+            min_step: 1e-6,
+            max_step: 0.05,
+            max_points: 2000,
+        }
+    }
+
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = step;
+        self.max_step = self.max_step.max(step);
+        self
+    }
+
+    pub fn max_points(mut self, max_points: usize) -> Self {
+        self.max_points = max_points;
+        self
+    }
+
+    /// Traces the equilibrium branch through the steady state reached from `guess` at the
+    /// current parameter value of `model`, in both directions until the ends of the range.
+    pub fn run<M: Model + Clone>(&self, model: &M, guess: &State) -> Result<Branch, Error> {
+        let p0 = model
+            .get(&self.parameter)
+            .ok_or_else(|| Error::UnknownParameter(self.parameter.clone()))?;
+        if !(self.min..=self.max).contains(&p0) {
+            return Err(Error::OutOfBounds {
+                name: self.parameter.clone(),
+                value: p0,
+            });
+        }
+        let y0 = steady_state(model, guess)?;
+        let scale = 1.0 + y0.amax();
+        let tracer = Tracer {
+            settings: self,
+            model: model.clone(),
+            scale,
+        };
+        let u0 = tracer.scaled(&y0, p0);
+        let tangent = tracer.initial_tangent(&u0)?;
+        let mut backward = tracer.trace(u0.clone(), -tangent.clone())?;
+        let forward = tracer.trace(u0, tangent)?;
+        backward.reverse();
+        backward.pop();
+        let mut points = Vec::new();
+        let mut tangents = Vec::new();
+        for (u, t, direction) in backward
+            .into_iter()
+            .map(|(u, t)| (u, t, -1.0))
+            .chain(forward.into_iter().map(|(u, t)| (u, t, 1.0)))
+        {
+            let (y, p) = tracer.unscaled(&u);
+            points.push(tracer.point(y, p));
+            tangents.push(t[t.len() - 1] * direction);
+        }
+        let bifurcations = detect(&points, &tangents);
+        Ok(Branch {
+            parameter: self.parameter.clone(),
+            species: model.species().iter().map(|s| s.to_string()).collect(),
+            points,
+            bifurcations,
+            cycles: Vec::new(),
+        })
+    }
+}
+
+/// A point of a branch in scaled variables with its unit tangent.
+type Traced = (DVector<f64>, DVector<f64>);
+
+/// State of a single continuation run, working in scaled variables `(y / scale, q)`.
+struct Tracer<'a, M> {
+    settings: &'a Continuation,
+    model: M,
+    scale: f64,
+}
+
+impl<M: Model + Clone> Tracer<'_, M> {
+    fn range(&self) -> f64 {
+        self.settings.max - self.settings.min
+    }
+
+    fn scaled(&self, y: &State, p: f64) -> DVector<f64> {
+        let n = y.len();
+        let mut u = DVector::zeros(n + 1);
+        u.rows_mut(0, n).copy_from(&(y / self.scale));
+        u[n] = (p - self.settings.min) / self.range();
+        u
+    }
+
+    fn unscaled(&self, u: &DVector<f64>) -> (State, f64) {
+        let n = u.len() - 1;
+        (
+            u.rows(0, n) * self.scale,
+            self.settings.min + u[n] * self.range(),
+        )
+    }
+
+    fn at(&self, p: f64) -> M {
+        let mut model = self.model.clone();
+        if let Some(value) = model.parameter_mut(&self.settings.parameter) {
+            *value = p;
+        }
+        model
+    }
+
+    fn point(&self, y: State, p: f64) -> BranchPoint {
+        let fixed = FixedPoint::new(&self.at(p), y);
+        BranchPoint {
+            parameter: p,
+            state: fixed.state,
+            eigenvalues: fixed.eigenvalues,
+            stability: fixed.stability,
+        }
+    }
+
+    /// Residual and `n x (n + 1)` Jacobian of the equilibrium condition in scaled variables.
+    fn linearize(&self, u: &DVector<f64>) -> Result<(DVector<f64>, DMatrix<f64>), Error> {
+        let n = u.len() - 1;
+        let (y, p) = self.unscaled(u);
+        let model = self.at(p);
+        let mut a = DMatrix::zeros(n, n + 1);
+        a.view_mut((0, 0), (n, n))
+            .copy_from(&(jacobian(&model, &y) * self.scale));
+        a.set_column(
+            n,
+            &(parameter_derivative(&model, &self.settings.parameter, 0.0, &y)? * self.range()),
+        );
+        Ok((rhs(&model, &y), a))
+    }
+
+    /// Unit tangent that increases the parameter.
+    fn initial_tangent(&self, u: &DVector<f64>) -> Result<DVector<f64>, Error> {
+        let n = u.len() - 1;
+        let mut seed = DVector::zeros(n + 1);
+        seed[n] = 1.0;
+        let tangent = self.tangent(u, &seed)?;
+        Ok(if tangent[n] < 0.0 { -tangent } else { tangent })
+    }
+
+    /// Unit tangent at `u`, oriented like `previous`.
+    fn tangent(&self, u: &DVector<f64>, previous: &DVector<f64>) -> Result<DVector<f64>, Error> {
+        let n = u.len() - 1;
+        let (_, a) = self.linearize(u)?;
+        let mut system = DMatrix::zeros(n + 1, n + 1);
+        system.view_mut((0, 0), (n, n + 1)).copy_from(&a);
+        system.set_row(n, &previous.transpose());
+        let mut rhs = DVector::zeros(n + 1);
+        rhs[n] = 1.0;
+        let tangent = system
+            .lu()
+            .solve(&rhs)
+            .ok_or_else(|| Error::NoConvergence("singular tangent system".to_string()))?;
+        Ok(tangent.normalize())
+    }
+
+    /// Newton on the equilibrium condition and `tangent . (u - predicted) = 0`.
+    fn correct(&self, predicted: &DVector<f64>, tangent: &DVector<f64>) -> Option<DVector<f64>> {
+        let n = predicted.len() - 1;
+        let mut u = predicted.clone();
+        for _ in 0..NEWTON_ITERATIONS {
+            let (f, a) = self.linearize(&u).ok()?;
+            let mut g = DVector::zeros(n + 1);
+            g.rows_mut(0, n).copy_from(&f);
+            g[n] = tangent.dot(&(&u - predicted));
+            let mut system = DMatrix::zeros(n + 1, n + 1);
+            system.view_mut((0, 0), (n, n + 1)).copy_from(&a);
+            system.set_row(n, &tangent.transpose());
+            let delta = system.lu().solve(&g)?;
+            u -= &delta;
+            if !u.iter().all(|v| v.is_finite()) {
+                return None;
+            }
+            if delta.norm() <= TOLERANCE * (1.0 + u.norm()) {
+                return Some(u);
+            }
+        }
+        None
+    }
+
+    /// Points and tangents from `u` along `tangent` until the range or the point budget ends.
+    fn trace(&self, mut u: DVector<f64>, mut tangent: DVector<f64>) -> Result<Vec<Traced>, Error> {
+        let n = u.len() - 1;
+        let settings = self.settings;
+        let mut step = settings.step;
+        let mut points = vec![(u.clone(), tangent.clone())];
+        while points.len() < settings.max_points {
+            let predicted = &u + &tangent * step;
+            match self.correct(&predicted, &tangent) {
+                Some(next) if !(0.0..=1.0).contains(&next[n]) => {
+                    // End the branch exactly on the boundary of the range.
+                    let q = next[n].clamp(0.0, 1.0);
+                    let w = (q - u[n]) / (next[n] - u[n]);
+                    let (guess, p) = self.unscaled(&(&u + (&next - &u) * w));
+                    if let Ok(y) = steady_state(&self.at(p), &guess) {
+                        points.push((self.scaled(&y, p), tangent.clone()));
+                    }
+                    break;
+                }
+                Some(next) => {
+                    tangent = self.tangent(&next, &tangent)?;
+                    u = next;
+                    points.push((u.clone(), tangent.clone()));
+                    step = (step * 1.5).min(settings.max_step);
+                }
+                None if step > settings.min_step => step /= 2.0,
+                None => break,
+            }
+        }
+        Ok(points)
+    }
+}
+
+/// Real part of the leading complex pair, if there is one.
+fn leading_pair(point: &BranchPoint) -> Option<Complex<f64>> {
+    let scale = point
+        .eigenvalues
+        .iter()
+        .map(|l| l.norm())
+        .fold(0.0, f64::max);
+    point
+        .eigenvalues
+        .iter()
+        .find(|l| l.im.abs() > 1e-9 * scale)
+        .copied()
+}
+
+fn interpolate(a: &BranchPoint, b: &BranchPoint, w: f64) -> (f64, State) {
+    (
+        a.parameter + w * (b.parameter - a.parameter),
+        &a.state + (&b.state - &a.state) * w,
+    )
+}
+
+fn detect(points: &[BranchPoint], tangents: &[f64]) -> Vec<Bifurcation> {
+    let mut bifurcations = Vec::new();
+    for i in 1..points.len() {
+        let (a, b) = (&points[i - 1], &points[i]);
+        if tangents[i - 1] * tangents[i] < 0.0 {
+            let w = tangents[i - 1] / (tangents[i - 1] - tangents[i]);
+            let (parameter, state) = interpolate(a, b, w);
+            bifurcations.push(Bifurcation {
+                kind: BifurcationKind::Fold,
+                parameter,
+                state,
+                index: i,
+            });
+        }
+        if let (Some(la), Some(lb)) = (leading_pair(a), leading_pair(b)) {
+            if la.re * lb.re < 0.0 {
+                let w = la.re / (la.re - lb.re);
+                let (parameter, state) = interpolate(a, b, w);
+                bifurcations.push(Bifurcation {
+                    kind: BifurcationKind::Hopf {
+                        frequency: la.im.abs() + w * (lb.im.abs() - la.im.abs()),
+                    },
+                    parameter,
+                    state,
+                    index: i,
+                });
+            }
+        }
+    }
+    bifurcations
+}
+
+/// Trajectory from `y0` over `[0, t_end]` sampled every `dx`.
+fn flow<M: Model + Clone>(
+    model: &M,
+    y0: &State,
+    t_end: f64,
+    dx: f64,
+) -> Result<(Vec<f64>, Vec<State>), Error> {
+    let mut stepper = Dopri5::new(model.clone(), 0.0, t_end, dx, y0.clone(), 1e-10, 1e-10);
+    stepper
+        .integrate()
+        .map_err(|e| Error::NoConvergence(e.to_string()))?;
+    Ok((stepper.x_out().clone(), stepper.y_out().clone()))
+}
+
+/// State at `t_end` from `y0`, taken from the last accepted step rather than the dense output.
+fn end_state<M: Model + Clone>(model: &M, y0: &State, t_end: f64) -> Option<State> {
+    let mut stepper = Dopri5::new(model.clone(), 0.0, t_end, t_end, y0.clone(), 1e-10, 1e-10);
+    stepper.set_output(OutputType::Sparse);
+    stepper.integrate().ok()?;
+    stepper.y_out().last().cloned()
+}
+
+/// Period of the oscillation at the end of a trajectory from upward crossings of the mean of
+/// the first species, and the state at the last crossing.
+fn estimate_period(t: &[f64], y: &[State]) -> Option<(f64, State)> {
+    let tail = y.len() / 2;
+    let mean = y[tail..].iter().map(|s| s[0]).sum::<f64>() / (y.len() - tail) as f64;
+    let crossings: Vec<usize> = (tail + 1..y.len())
+        .filter(|i| y[i - 1][0] < mean && y[*i][0] >= mean)
+        .collect();
+    if crossings.len() < 3 {
+        return None;
+    }
+    let (first, last) = (
+        crossings[crossings.len() - 3],
+        crossings[crossings.len() - 1],
+    );
+    Some(((t[last] - t[first]) / 2.0, y[last].clone()))
+}
+
+/// Periodic orbit near `(guess, period)` by Newton on `phi_T(y) - y = 0` with the phase fixed
+/// on the hyperplane through `guess` orthogonal to the flow.
+fn shoot<M: Model + Clone>(model: &M, parameter: f64, guess: &State, period: f64) -> Option<Cycle> {
+    let n = guess.len();
+    let normal = rhs(model, guess);
+    let end = |y: &State, t: f64| end_state(model, y, t);
+    let (mut y, mut period) = (guess.clone(), period);
+    for _ in 0..NEWTON_ITERATIONS {
+        let phi = end(&y, period)?;
+        let mut residual = DVector::zeros(n + 1);
+        residual.rows_mut(0, n).copy_from(&(&phi - &y));
+        residual[n] = normal.dot(&(&y - guess));
+
+        let mut a = DMatrix::zeros(n + 1, n + 1);
+        let mut monodromy = DMatrix::zeros(n, n);
+        for j in 0..n {
+            let h = f64::EPSILON.sqrt() * y[j].abs().max(1.0);
+            let mut shifted = y.clone();
+            shifted[j] += h;
+            let column = (end(&shifted, period)? - &phi) / h;
+            monodromy.set_column(j, &column);
+        }
+        a.view_mut((0, 0), (n, n))
+            .copy_from(&(&monodromy - DMatrix::identity(n, n)));
+        a.view_mut((0, n), (n, 1)).copy_from(&rhs(model, &phi));
+        a.view_mut((n, 0), (1, n)).copy_from(&normal.transpose());
+        let delta = a.lu().solve(&residual)?;
+        y -= delta.rows(0, n);
+        period -= delta[n];
+        if !(period > 0.0 && y.iter().all(|v| v.is_finite())) {
+            return None;
+        }
+        if delta.norm() <= 1e-8 * (1.0 + y.norm() + period) {
+            let (_, orbit) = flow(model, &y, period, period / 200.0).ok()?;
+            let min = orbit.iter().fold(y.clone(), |m, s| m.inf(s));
+            let max = orbit.iter().fold(y.clone(), |m, s| m.sup(s));
+            let multipliers: Vec<Complex<f64>> =
+                monodromy.complex_eigenvalues().iter().copied().collect();
+            // Every multiplier but the trivial one, which is closest to 1, must lie inside the
+            // unit circle.
+            let trivial = multipliers
+                .iter()
+                .enumerate()
+                .min_by(|a, b| (a.1 - 1.0).norm().total_cmp(&(b.1 - 1.0).norm()))
+                .map(|(i, _)| i);
+            let stable = multipliers
+                .iter()
+                .enumerate()
+                .all(|(i, m)| Some(i) == trivial || m.norm() < 1.0);
+            return Some(Cycle {
+                parameter,
+                period,
+                state: y,
+                min,
+                max,
+                multipliers,
+                stable,
+            });
+        }
+    }
+    None
+}
+
+impl Branch {
+    /// Follows the limit cycle born at the Hopf point `hopf` (an index into `bifurcations`) in
+    /// `points` equal parameter steps up to `end`, appending the cycles to `cycles`.
+    ///
+    /// The first cycle is found by simulating from the perturbed equilibrium, so the Hopf point
+    /// must be supercritical on the side of `end`. Each later cycle is continued from the last
+    /// one, halving the parameter step when shooting fails; a cycle that is still lost at a
+    /// step of `1e-6` of the spacing is an error, and so is an index past the end or of a fold.
+    /// Returns the number of cycles found.
+    pub fn follow_cycles<M: Model + Clone>(
+        &mut self,
+        model: &M,
+        hopf: usize,
+        end: f64,
+        points: usize,
+    ) -> Result<usize, Error> {
+        let bifurcation = self.bifurcations.get(hopf).ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "bifurcation {} of a branch with {}",
+                hopf,
+                self.bifurcations.len()
+            ))
+        })?;
+        let frequency = match bifurcation.kind {
+            BifurcationKind::Hopf { frequency } => frequency,
+            BifurcationKind::Fold => {
+                return Err(Error::InvalidArgument(format!(
+                    "bifurcation {} is a fold, but limit cycles start at Hopf points",
+                    hopf
+                )))
+            }
+        };
+        let start = bifurcation.parameter;
+        let spacing = (end - start) / points as f64;
+        let mut model = model.clone();
+        let mut found = 0;
+        // Parameter, point and period of the last cycle found.
+        let mut previous: Option<(f64, State, f64)> = None;
+        for i in 1..=points {
+            let p = start + spacing * i as f64;
+            let (mut q, mut guess, mut period) = match previous.take() {
+                Some(previous) => previous,
+                None => {
+                    model.set(&self.parameter, p)?;
+                    let equilibrium = steady_state(&model, &bifurcation.state)?;
+                    let y0 = &equilibrium + equilibrium.map(|v| 0.01 * v.abs() + 1e-3);
+                    let period = 2.0 * std::f64::consts::PI / frequency;
+                    let (t, y) = flow(&model, &y0, 200.0 * period, period / 50.0)?;
+                    match estimate_period(&t, &y) {
+                        Some((period, state)) => (p, state, period),
+                        None => continue,
+                    }
+                }
+            };
+            let mut step = spacing;
+            loop {
+                let next = if (p - q).abs() <= step.abs() {
+                    p
+                } else {
+                    q + step
+                };
+                model.set(&self.parameter, next)?;
+                match shoot(&model, next, &guess, period) {
+                    Some(cycle) if next == p => {
+                        previous = Some((p, cycle.state.clone(), cycle.period));
+                        self.cycles.push(cycle);
+                        found += 1;
+                        break;
+                    }
+                    Some(cycle) => {
+                        (q, guess, period) = (next, cycle.state, cycle.period);
+                        step *= 2.0;
+                    }
+                    // Near the Hopf point the simulation may not have settled on the cycle yet.
+                    None if found == 0 => break,
+                    None if step.abs() > 1e-6 * spacing.abs() => step /= 2.0,
+                    None => {
+                        return Err(Error::NoConvergence(format!(
+                            "limit cycle lost at {} = {}",
+                            self.parameter, next
+                        )))
+                    }
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    /// The bifurcation diagram as CSV with one row per equilibrium, bifurcation and cycle
+    /// extreme. The `kind` column is `equilibrium`, `fold`, `hopf`, `cycle_min` or `cycle_max`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!(
+            "kind,{},{},stability,period\n",
+            self.parameter,
+            self.species.join(",")
+        );
+        let row = |csv: &mut String, kind: &str, p: f64, y: &State, stability: &str, period| {
+            let values: Vec<String> = y.iter().map(|v| v.to_string()).collect();
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                kind,
+                p,
+                values.join(","),
+                stability,
+                period
+            ));
+        };
+        for (i, point) in self.points.iter().enumerate() {
+            for bifurcation in self.bifurcations.iter().filter(|b| b.index == i) {
+                let kind = match bifurcation.kind {
+                    BifurcationKind::Fold => "fold",
+                    BifurcationKind::Hopf { .. } => "hopf",
+                };
+                row(
+                    &mut csv,
+                    kind,
+                    bifurcation.parameter,
+                    &bifurcation.state,
+                    "",
+                    f64::NAN,
+                );
+            }
+            let stability = point.stability.to_string();
+            row(
+                &mut csv,
+                "equilibrium",
+                point.parameter,
+                &point.state,
+                &stability,
+                f64::NAN,
+            );
+        }
+        for cycle in &self.cycles {
+            let stability = if cycle.stable { "stable" } else { "unstable" };
+            for (kind, y) in [("cycle_min", &cycle.min), ("cycle_max", &cycle.max)] {
+                row(&mut csv, kind, cycle.parameter, y, stability, cycle.period);
+            }
+        }
+        csv
+    }
+
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        std::fs::write(path, self.to_csv()).map_err(|e| Error::Io(e.to_string()))
+    }
+}
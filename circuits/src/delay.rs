@@ -0,0 +1,199 @@
+//! Delay differential equations with constant delays.
+//!
+//! A [`DelaySystem`] sees, besides its current state, the states one delay ago. [`Dde`] solves it
+//! by the method of steps: Bogacki–Shampine steps never longer than the shortest delay, so every
+//! delayed state falls on already computed solution, which cubic Hermite interpolation between
+//! the accepted steps turns into a dense output. Before the initial time the solution is the
+//! history function. Steps land on the initial time shifted by sums of up to three delays, where
+//! the discontinuity of the derivative at the initial time propagates.
+
+use crate::model::{Error, State, Time};
+use crate::stiff::hermite;
+
+/// A system of delay differential equations `y'(t) = f(t, y(t), y(t - tau_1), ...)`.
+pub trait DelaySystem {
+    /// Constant delays, all positive.
+    fn delays(&self) -> Vec<f64>;
+
+    /// Derivative at `t` given the state `y` and `lagged[i]`, the state `delays()[i]` ago.
+    fn delayed_system(&self, t: Time, y: &State, lagged: &[State], dy: &mut State);
+}
+
+/// Adaptive solver of a [`DelaySystem`] from a history function.
+pub struct Dde<S, H> {
+    system: S,
+    history: H,
+    delays: Vec<f64>,
+    t0: f64,
+    t_end: f64,
+    rtol: f64,
+    atol: f64,
+    max_step: f64,
+    x_out: Vec<f64>,
+    y_out: Vec<State>,
+    /// Derivatives at the accepted steps, for the Hermite interpolation.
+    f_out: Vec<State>,
+}
+
+impl<S: DelaySystem, H: Fn(Time) -> State> Dde<S, H> {
+    /// Solver from `t0` to `t_end`, with the solution equal to `history(t)` for `t <= t0`.
+    pub fn new(system: S, history: H, t0: f64, t_end: f64) -> Self {
+        let delays = system.delays();
+        Dde {
+            system,
+            history,
+            delays,
+            t0,
+            t_end,
+            rtol: 1e-6,
+            atol: 1e-6,
+            max_step: f64::INFINITY,
+            x_out: Vec::new(),
+            y_out: Vec::new(),
+            f_out: Vec::new(),
+        }
+    }
+
+    pub fn tolerances(mut self, rtol: f64, atol: f64) -> Self {
+        self.rtol = rtol;
+        self.atol = atol;
+        self
+    }
+
+    /// Bounds the step size, which is always bounded by the shortest delay.
+    pub fn max_step(mut self, max_step: f64) -> Self {
+        self.max_step = max_step;
+        self
+    }
+
+    /// Times of the accepted steps.
+    pub fn x_out(&self) -> &Vec<f64> {
+        &self.x_out
+    }
+
+    /// States at the accepted steps.
+    pub fn y_out(&self) -> &Vec<State> {
+        &self.y_out
+    }
+
+    /// Solution at any time up to the last accepted step, from the history before `t0`.
+    pub fn at(&self, t: f64) -> State {
+        if t <= self.t0 || self.x_out.is_empty() {
+            return (self.history)(t.min(self.t0));
+        }
+        let k = self
+            .x_out
+            .partition_point(|x| *x < t)
+            .clamp(1, self.x_out.len() - 1);
+        hermite(
+            (self.x_out[k - 1], &self.y_out[k - 1], &self.f_out[k - 1]),
+            (self.x_out[k], &self.y_out[k], &self.f_out[k]),
+            t,
+        )
+    }
+
+    /// Solution resampled at `times`.
+    pub fn sample(&self, times: &[f64]) -> Vec<State> {
+        times.iter().map(|t| self.at(*t)).collect()
+    }
+
+    fn rhs(&self, t: f64, y: &State) -> State {
+        let lagged: Vec<State> = self.delays.iter().map(|tau| self.at(t - tau)).collect();
+        let mut dy = State::zeros(y.len());
+        self.system.delayed_system(t, y, &lagged, &mut dy);
+        dy
+    }
+
+    /// Points after `t0` where the solution may be non-smooth.
+    fn breakpoints(&self) -> Vec<f64> {
+        let mut points = vec![self.t0];
+        for _ in 0..3 {
+            let shifted: Vec<f64> = points
+                .iter()
+                .flat_map(|t| self.delays.iter().map(move |tau| t + tau))
+                .collect();
+            points.extend(shifted);
+            points.sort_by(|a, b| a.total_cmp(b));
+            points.dedup_by(|a, b| (*a - *b).abs() <= 1e-12 * (1.0 + b.abs()));
+        }
+        points.retain(|t| *t > self.t0 && *t < self.t_end);
+        points.push(self.t_end);
+        points
+    }
+
+    /// Integrates from `t0` to `t_end`, replacing any earlier solution.
+    pub fn integrate(&mut self) -> Result<(), Error> {
+        if let Some((i, tau)) = self
+            .delays
+            .iter()
+            .enumerate()
+            .find(|(_, tau)| tau.is_nan() || **tau <= 0.0)
+        {
+            return Err(Error::OutOfBounds {
+                name: format!("delay {}", i),
+                value: *tau,
+            });
+        }
+        let min_delay = self.delays.iter().copied().fold(f64::INFINITY, f64::min);
+        let max_step = self.max_step.min(min_delay);
+
+        let mut t = self.t0;
+        let mut y = (self.history)(self.t0);
+        self.x_out = Vec::new();
+        self.y_out = Vec::new();
+        self.f_out = Vec::new();
+        let mut f = self.rhs(t, &y);
+        self.x_out.push(t);
+        self.y_out.push(y.clone());
+        self.f_out.push(f.clone());
+
+        let breakpoints = self.breakpoints();
+        let mut next = 0;
+        let mut h = max_step.min((self.t_end - self.t0) / 100.0);
+        while t < self.t_end {
+            let h_min = 1e-12 * (1.0 + t.abs());
+            if h < h_min {
+                return Err(Error::NoConvergence(format!(
+                    "step size too small at t = {}",
+                    t
+                )));
+            }
+            let target = breakpoints[next];
+            let landing = t + h >= target - h_min;
+            let step = if landing { target - t } else { h };
+
+            // Bogacki–Shampine pair with the first stage reused from the last step.
+            let k2 = self.rhs(t + step / 2.0, &(&y + &f * (step / 2.0)));
+            let k3 = self.rhs(t + 0.75 * step, &(&y + &k2 * (0.75 * step)));
+            let y_new = &y + (&f * (2.0 / 9.0) + &k2 * (1.0 / 3.0) + &k3 * (4.0 / 9.0)) * step;
+            let k4 = self.rhs(t + step, &y_new);
+            let err = (&f * (-5.0 / 72.0) + &k2 * (1.0 / 12.0) + &k3 * (1.0 / 9.0)
+                - &k4 * (1.0 / 8.0))
+                * step;
+            let norm = err
+                .iter()
+                .zip(y.iter().zip(y_new.iter()))
+                .map(|(e, (a, b))| e.abs() / (self.atol + self.rtol * a.abs().max(b.abs())))
+                .fold(0.0, f64::max);
+
+            let factor = if norm > 0.0 {
+                (0.9 * norm.powf(-1.0 / 3.0)).clamp(0.2, 5.0)
+            } else {
+                5.0
+            };
+            if norm <= 1.0 {
+                t = if landing { target } else { t + step };
+                if landing {
+                    next += 1;
+                }
+                y = y_new;
+                f = k4;
+                self.x_out.push(t);
+                self.y_out.push(y.clone());
+                self.f_out.push(f.clone());
+            }
+            h = (step * factor).min(max_step);
+        }
+        Ok(())
+    }
+}
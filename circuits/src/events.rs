@@ -0,0 +1,131 @@
+//! Events located during integration, such as a species crossing a threshold or peaking.
+//!
+//! An [`Event`] happens where its function of `(t, y)` changes sign, optionally only in one
+//! [`Direction`]. The [`Stiff`](crate::stiff::Stiff) solver checks every event after each step,
+//! locates the root on the dense output of the step and records an [`Occurrence`]; a terminal
+//! event ends the integration there.
+//!
+//! `Event::threshold("extinct", 1, 1.0).direction(Direction::Falling).terminal()` stops the run
+//! when the second species drops below one.
+
+use crate::model::{State, Time};
+
+/// Sign changes of an event function that count.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// From negative to positive.
+    Rising,
+    /// From positive to negative.
+    Falling,
+    #[default]
+    Either,
+}
+
+impl Direction {
+    fn matches(&self, before: f64, after: f64) -> bool {
+        let rising = before < 0.0 && after >= 0.0;
+        let falling = before > 0.0 && after <= 0.0;
+        match self {
+            Direction::Rising => rising,
+            Direction::Falling => falling,
+            Direction::Either => rising || falling,
+        }
+    }
+}
+
+/// Function of time and state whose roots are the occurrences of an event.
+type EventFunction = dyn Fn(Time, &State) -> f64;
+
+/// A named root of a function of time and state.
+pub struct Event {
+    pub name: String,
+    function: Box<EventFunction>,
+    direction: Direction,
+    terminal: bool,
+}
+
+impl Event {
+    pub fn new(name: &str, function: impl Fn(Time, &State) -> f64 + 'static) -> Self {
+        Event {
+            name: name.to_string(),
+            function: Box::new(function),
+            direction: Direction::Either,
+            terminal: false,
+        }
+    }
+
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Stops the integration at the first occurrence.
+    pub fn terminal(mut self) -> Self {
+        self.terminal = true;
+        self
+    }
+
+    /// `species` crossing `level`.
+    pub fn threshold(name: &str, species: usize, level: f64) -> Self {
+        Event::new(name, move |_t, y| y[species] - level)
+    }
+
+    pub fn value(&self, t: Time, y: &State) -> f64 {
+        (self.function)(t, y)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.terminal
+    }
+
+    /// Whether a change of the event function from `before` to `after` is an occurrence.
+    pub fn triggers(&self, before: f64, after: f64) -> bool {
+        self.direction.matches(before, after)
+    }
+}
+
+/// An event found during integration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Occurrence {
+    /// Name of the event.
+    pub event: String,
+    pub t: Time,
+    pub y: State,
+    pub terminal: bool,
+}
+
+/// Root of `g` between `a` and `b`, where it changes sign, by the Illinois variant of regula
+/// falsi.
+pub(crate) fn locate(
+    g: impl Fn(f64) -> f64,
+    (mut a, mut g_a): (f64, f64),
+    (mut b, mut g_b): (f64, f64),
+) -> f64 {
+    let tol = 4.0 * f64::EPSILON * a.abs().max(b.abs()).max(1.0);
+    let mut side = 0;
+    for _ in 0..100 {
+        if g_b == 0.0 || (b - a).abs() <= tol {
+            break;
+        }
+        let c = b - g_b * (b - a) / (g_b - g_a);
+        let g_c = g(c);
+        if g_c == 0.0 {
+            return c;
+        }
+        if (g_c < 0.0) == (g_b < 0.0) {
+            // The root lies between a and c.
+            (b, g_b) = (c, g_c);
+            if side == -1 {
+                g_a /= 2.0;
+            }
+            side = -1;
+        } else {
+            (a, g_a) = (c, g_c);
+            if side == 1 {
+                g_b /= 2.0;
+            }
+            side = 1;
+        }
+    }
+    b
+}
@@ -0,0 +1,472 @@
+//! Parameter estimation from observed time series.
+//!
+//! [`Observations`] are read from a CSV file with a time column followed by one column per
+//! observed species. An [`Estimation`] minimizes the weighted sum of squared differences between
+//! the observations and the model, simulated from its initial state, with Levenberg–Marquardt or
+//! Nelder–Mead. Parameters are fitted in their logarithm, which keeps them positive and puts
+//! rates of very different magnitudes on the same footing. The covariance of the estimates comes
+//! from the Jacobian of the residuals at the optimum.
+//!
+//! The [`Fit`] holds the fitted model, the residuals and every estimate with its standard error
+//! and 95% interval.
+
+use std::path::Path;
+
+use nalgebra::{DMatrix, DVector};
+use ode_solvers::{Dopri5, OutputType};
+
+use crate::model::{Error, Model, State};
+
+const RTOL: f64 = 1e-8;
+const ATOL: f64 = 1e-8;
+/// Two sided 95% quantile of the normal distribution.
+const Z95: f64 = 1.959964;
+
+/// Observed species over time; missing values are NaN.
+#[derive(Clone, Debug)]
+pub struct Observations {
+    pub t: Vec<f64>,
+    pub columns: Vec<(String, Vec<f64>)>,
+}
+
+impl Observations {
+    /// Parses a CSV table whose header names the time column first and the observed species
+    /// after it. Empty fields are missing values.
+    pub fn from_csv_str(csv: &str) -> Result<Self, Error> {
+        let mut lines = csv.lines().map(str::trim).filter(|l| !l.is_empty());
+        let header: Vec<String> = lines
+            .next()
+            .ok_or_else(|| Error::Parse("observation table is empty".to_string()))?
+            .split(',')
+            .map(|h| h.trim().trim_matches('"').to_string())
+            .collect();
+        if header.len() < 2 {
+            return Err(Error::Parse(
+                "observations need a time column and at least one species".to_string(),
+            ));
+        }
+        let mut rows = Vec::new();
+        for line in lines {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != header.len() {
+                return Err(Error::Parse(format!("invalid observation line `{}`", line)));
+            }
+            let row = fields
+                .iter()
+                .map(|f| match *f {
+                    "" => Ok(f64::NAN),
+                    f => f.parse::<f64>(),
+                })
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| Error::Parse(format!("invalid observation line `{}`", line)))?;
+            if row[0].is_nan() || row[0] < 0.0 {
+                return Err(Error::Parse(format!(
+                    "invalid observation time `{}`",
+                    fields[0]
+                )));
+            }
+            rows.push(row);
+        }
+        rows.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        Ok(Observations {
+            t: rows.iter().map(|r| r[0]).collect(),
+            columns: header
+                .into_iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, name)| (name, rows.iter().map(|r| r[i]).collect()))
+                .collect(),
+        })
+    }
+
+    /// Positions of the observed columns in the state of `model`.
+    pub fn indices<M: Model>(&self, model: &M) -> Result<Vec<usize>, Error> {
+        let species = model.species();
+        self.columns
+            .iter()
+            .map(|(name, _)| {
+                species
+                    .iter()
+                    .position(|s| s == name)
+                    .ok_or_else(|| Error::Parse(format!("unknown species `{}`", name)))
+            })
+            .collect()
+    }
+
+    /// Reads observations from a CSV file, for example one written by polars' `CsvWriter`.
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let csv = std::fs::read_to_string(path).map_err(|e| Error::Io(e.to_string()))?;
+        Observations::from_csv_str(&csv)
+    }
+}
+
+/// States of `model` from its initial state at the sorted `times`.
+pub(crate) fn simulate<M: Model + Clone>(model: &M, times: &[f64]) -> Result<Vec<State>, Error> {
+    let mut t = 0.0;
+    let mut y = model.initial_state();
+    let mut states = Vec::with_capacity(times.len());
+    for &next in times {
+        if next > t {
+            let mut stepper = Dopri5::new(model.clone(), t, next, next - t, y.clone(), RTOL, ATOL);
+            stepper.set_output(OutputType::Sparse);
+            stepper
+                .integrate()
+                .map_err(|e| Error::NoConvergence(e.to_string()))?;
+            y = stepper.y_out().last().cloned().unwrap_or(y);
+            t = next;
+        }
+        states.push(y.clone());
+    }
+    Ok(states)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    LevenbergMarquardt,
+    NelderMead,
+}
+
+/// Estimated value of a parameter with its standard error and 95% confidence interval.
+///
+/// The interval is symmetric in the logarithm of the parameter, so it stays positive.
+#[derive(Clone, Debug)]
+pub struct Estimate {
+    pub name: String,
+    pub value: f64,
+    pub standard_error: f64,
+    pub interval: (f64, f64),
+}
+
+/// Outcome of an [`Estimation`].
+#[derive(Clone, Debug)]
+pub struct Fit<M> {
+    /// The model with the estimated parameters.
+    pub model: M,
+    pub estimates: Vec<Estimate>,
+    /// Model minus observation at every observation time, NaN where a value is missing.
+    pub residuals: Vec<(String, Vec<f64>)>,
+    /// Weighted sum of squared residuals.
+    pub cost: f64,
+    pub iterations: usize,
+    /// Covariance of the estimated parameters.
+    pub covariance: DMatrix<f64>,
+}
+
+/// Settings of a weighted least squares fit.
+#[derive(Clone, Debug)]
+pub struct Estimation {
+    pub parameters: Vec<String>,
+    pub algorithm: Algorithm,
+    /// Weights of the observed columns, 1 unless given.
+    pub weights: Vec<(String, f64)>,
+    pub max_iterations: usize,
+    pub tolerance: f64,
+}
+
+impl Estimation {
+    pub fn new(parameters: &[&str]) -> Self {
+        Estimation {
+            parameters: parameters.iter().map(|p| p.to_string()).collect(),
+            algorithm: Algorithm::LevenbergMarquardt,
+            weights: Vec::new(),
+            max_iterations: 200,
+            tolerance: 1e-10,
+        }
+    }
+
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Weight of the squared residuals of a column, typically one over its variance. Weights
+    /// must be positive and finite.
+    pub fn weight(mut self, column: &str, weight: f64) -> Result<Self, Error> {
+        if !(weight > 0.0 && weight.is_finite()) {
+            return Err(Error::OutOfBounds {
+                name: column.to_string(),
+                value: weight,
+            });
+        }
+        self.weights.retain(|(c, _)| c != column);
+        self.weights.push((column.to_string(), weight));
+        Ok(self)
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Fits the parameters of `model`, starting from their current values.
+    pub fn run<M: Model + Clone>(
+        &self,
+        model: &M,
+        observations: &Observations,
+    ) -> Result<Fit<M>, Error> {
+        let start = self
+            .parameters
+            .iter()
+            .map(|name| match model.get(name) {
+                Some(value) if value > 0.0 => Ok(value.ln()),
+                Some(value) => Err(Error::OutOfBounds {
+                    name: name.clone(),
+                    value,
+                }),
+                None => Err(Error::UnknownParameter(name.clone())),
+            })
+            .collect::<Result<Vec<f64>, Error>>()?;
+        let columns: Vec<(usize, f64, &Vec<f64>)> = observations
+            .indices(model)?
+            .into_iter()
+            .zip(&observations.columns)
+            .map(|(index, (name, values))| {
+                let weight = self
+                    .weights
+                    .iter()
+                    .find(|(c, _)| c == name)
+                    .map_or(1.0, |(_, w)| *w);
+                (index, weight.sqrt(), values)
+            })
+            .collect();
+
+        let with = |theta: &DVector<f64>| {
+            let mut model = model.clone();
+            for (name, theta) in self.parameters.iter().zip(theta.iter()) {
+                if let Some(p) = model.parameter_mut(name) {
+                    *p = theta.exp();
+                }
+            }
+            model
+        };
+        // Weighted residuals of the observed values, in a fixed order.
+        let residuals = |theta: &DVector<f64>| -> Result<DVector<f64>, Error> {
+            let states = simulate(&with(theta), &observations.t)?;
+            let mut r = Vec::new();
+            for (index, sqrt_w, values) in &columns {
+                for (state, observed) in states.iter().zip(values.iter()) {
+                    if observed.is_finite() {
+                        r.push(sqrt_w * (state[*index] - observed));
+                    }
+                }
+            }
+            Ok(DVector::from_vec(r))
+        };
+
+        let start = DVector::from_vec(start);
+        let (theta, iterations) = match self.algorithm {
+            Algorithm::LevenbergMarquardt => self.levenberg_marquardt(start, &residuals)?,
+            Algorithm::NelderMead => self.nelder_mead(start, &residuals)?,
+        };
+
+        let r = residuals(&theta)?;
+        let cost = r.norm_squared();
+        let jacobian = jacobian(&theta, &r, &residuals)?;
+        let dof = r.len().saturating_sub(theta.len()).max(1) as f64;
+        let covariance_log = (jacobian.transpose() * &jacobian)
+            .pseudo_inverse(1e-12)
+            .map_err(|e| Error::NoConvergence(e.to_string()))?
+            * (cost / dof);
+        let values = theta.map(f64::exp);
+        let covariance = DMatrix::from_fn(theta.len(), theta.len(), |i, j| {
+            values[i] * values[j] * covariance_log[(i, j)]
+        });
+        let estimates = self
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let se = covariance_log[(i, i)].max(0.0).sqrt();
+                Estimate {
+                    name: name.clone(),
+                    value: values[i],
+                    standard_error: values[i] * se,
+                    interval: ((theta[i] - Z95 * se).exp(), (theta[i] + Z95 * se).exp()),
+                }
+            })
+            .collect();
+
+        let model = with(&theta);
+        let states = simulate(&model, &observations.t)?;
+        let residuals = observations
+            .columns
+            .iter()
+            .zip(&columns)
+            .map(|((name, values), (index, _, _))| {
+                let r = states
+                    .iter()
+                    .zip(values)
+                    .map(|(state, observed)| state[*index] - observed)
+                    .collect();
+                (name.clone(), r)
+            })
+            .collect();
+
+        Ok(Fit {
+            model,
+            estimates,
+            residuals,
+            cost,
+            iterations,
+            covariance,
+        })
+    }
+
+    fn levenberg_marquardt<F>(
+        &self,
+        mut theta: DVector<f64>,
+        residuals: &F,
+    ) -> Result<(DVector<f64>, usize), Error>
+    where
+        F: Fn(&DVector<f64>) -> Result<DVector<f64>, Error>,
+    {
+        let mut r = residuals(&theta)?;
+        let mut cost = r.norm_squared();
+        let mut lambda = 1e-3;
+        for iteration in 1..=self.max_iterations {
+            let j = jacobian(&theta, &r, residuals)?;
+            let jtj = j.transpose() * &j;
+            let gradient = j.transpose() * &r;
+            if gradient.amax() <= self.tolerance * cost.max(f64::MIN_POSITIVE) {
+                return Ok((theta, iteration));
+            }
+            // Raise the damping until a step lowers the cost.
+            loop {
+                let mut damped = jtj.clone();
+                for i in 0..damped.nrows() {
+                    damped[(i, i)] += lambda * jtj[(i, i)].max(1e-12);
+                }
+                let step = damped.lu().solve(&-&gradient);
+                let trial = step.map(|s| &theta + s);
+                let trial_cost = match &trial {
+                    Some(trial) => residuals(trial).ok().map(|r| (r.norm_squared(), r)),
+                    None => None,
+                };
+                match (trial, trial_cost) {
+                    (Some(trial), Some((trial_cost, trial_r))) if trial_cost < cost => {
+                        let converged = cost - trial_cost <= self.tolerance * cost
+                            || (&trial - &theta).amax() <= self.tolerance;
+                        theta = trial;
+                        r = trial_r;
+                        cost = trial_cost;
+                        lambda = (lambda / 10.0).max(1e-12);
+                        if converged {
+                            return Ok((theta, iteration));
+                        }
+                        break;
+                    }
+                    _ if lambda > 1e12 => return Ok((theta, iteration)),
+                    _ => lambda *= 10.0,
+                }
+            }
+        }
+        Err(Error::NoConvergence(format!(
+            "Levenberg–Marquardt did not converge in {} iterations",
+            self.max_iterations
+        )))
+    }
+
+    fn nelder_mead<F>(
+        &self,
+        start: DVector<f64>,
+        residuals: &F,
+    ) -> Result<(DVector<f64>, usize), Error>
+    where
+        F: Fn(&DVector<f64>) -> Result<DVector<f64>, Error>,
+    {
+        // Failed simulations count as infinitely bad points.
+        let cost = |theta: &DVector<f64>| {
+            residuals(theta)
+                .map(|r| r.norm_squared())
+                .unwrap_or(f64::INFINITY)
+        };
+        let n = start.len();
+        let mut simplex: Vec<(DVector<f64>, f64)> = (0..=n)
+            .map(|i| {
+                let mut vertex = start.clone();
+                if i > 0 {
+                    // A tenth of a decade along each parameter.
+                    vertex[i - 1] += 0.1 * std::f64::consts::LN_10;
+                }
+                let c = cost(&vertex);
+                (vertex, c)
+            })
+            .collect();
+        if !simplex[0].1.is_finite() {
+            return Err(Error::NoConvergence(
+                "the model cannot be simulated at the starting point".to_string(),
+            ));
+        }
+        for iteration in 1..=self.max_iterations {
+            simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let (best, worst) = (simplex[0].1, simplex[n].1);
+            // An exact fit drives the cost to zero, so also stop once the simplex has shrunk.
+            let size = simplex[1..]
+                .iter()
+                .map(|(v, _)| (v - &simplex[0].0).amax())
+                .fold(0.0, f64::max);
+            if worst - best <= self.tolerance * best.abs().max(f64::MIN_POSITIVE)
+                || size <= self.tolerance
+            {
+                return Ok((simplex.swap_remove(0).0, iteration));
+            }
+            let centroid = simplex[..n]
+                .iter()
+                .fold(DVector::zeros(n), |c, (v, _)| c + v)
+                / n as f64;
+            let along = |t: f64| &centroid + (&simplex[n].0 - &centroid) * t;
+            let reflected = along(-1.0);
+            let fr = cost(&reflected);
+            if fr < best {
+                let expanded = along(-2.0);
+                let fe = cost(&expanded);
+                simplex[n] = if fe < fr {
+                    (expanded, fe)
+                } else {
+                    (reflected, fr)
+                };
+            } else if fr < simplex[n - 1].1 {
+                simplex[n] = (reflected, fr);
+            } else {
+                let contracted = if fr < worst { along(-0.5) } else { along(0.5) };
+                let fc = cost(&contracted);
+                if fc < fr.min(worst) {
+                    simplex[n] = (contracted, fc);
+                } else {
+                    let best = simplex[0].0.clone();
+                    for vertex in simplex.iter_mut().skip(1) {
+                        let shrunk = &best + (&vertex.0 - &best) * 0.5;
+                        let c = cost(&shrunk);
+                        *vertex = (shrunk, c);
+                    }
+                }
+            }
+        }
+        Err(Error::NoConvergence(format!(
+            "Nelder–Mead did not converge in {} iterations",
+            self.max_iterations
+        )))
+    }
+}
+
+/// Jacobian of the residuals with respect to the log parameters by central differences.
+fn jacobian<F>(theta: &DVector<f64>, r: &DVector<f64>, residuals: &F) -> Result<DMatrix<f64>, Error>
+where
+    F: Fn(&DVector<f64>) -> Result<DVector<f64>, Error>,
+{
+    let mut j = DMatrix::zeros(r.len(), theta.len());
+    for k in 0..theta.len() {
+        let h = f64::EPSILON.cbrt() * theta[k].abs().max(1.0);
+        let mut up = theta.clone();
+        up[k] += h;
+        let mut down = theta.clone();
+        down[k] -= h;
+        j.set_column(k, &((residuals(&up)? - residuals(&down)?) / (2.0 * h)));
+    }
+    Ok(j)
+}
@@ -0,0 +1,312 @@
+//! ODE models assembled from a gene-regulatory network.
+//!
+//! Each gene of a [`GeneNetwork`] is a protein level `x` with
+//!
+//! ```text
+//! dx/dt = basal + beta * logic(f_1, ..., f_m) - alpha * x
+//! ```
+//!
+//! where `f_j` is the [`Hill`] term of the `j`-th regulator, in the order the edges were added,
+//! and `logic` is the gate of the gene or else the default of the network. A gene without
+//! regulators is made at the constant rate `basal + beta`. The network is a petgraph
+//! [`DiGraph`] whose nodes are [`Gene`]s and whose edges are the Hill terms, so it can be
+//! written edge by edge:
+//!
+//! ```
+//! use circuits::gene_network::{Gene, GeneNetwork};
+//! use circuits::regulation::{Hill, Logic};
+//!
+//! # fn main() -> Result<(), circuits::Error> {
+//! let ffl = GeneNetwork::new("C1-FFL")
+//!     .gene(Gene::new("X").beta(0.0).alpha(0.0).initial(1.0))?
+//!     .gene(Gene::new("Z").logic(Logic::And))?
+//!     .regulation("X", "Y", Hill::activation(0.1, 2.0))?
+//!     .regulation("X", "Z", Hill::activation(0.1, 2.0))?
+//!     .regulation("Y", "Z", Hill::activation(0.5, 2.0))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! or built from an existing graph with [`GeneNetwork::from_graph`]. The parameters are
+//! `beta_G`, `alpha_G` and `basal_G` for each gene `G` and `k_R_T` and `n_R_T` for the
+//! regulation of `T` by `R`. Gene names may not contain `_`, so that these names are never
+//! ambiguous.
+
+use std::collections::HashMap;
+
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+
+use crate::model::{Error, Model, Parameter, State, Time};
+use crate::regulation::{Hill, Logic};
+
+/// A gene and the kinetics of its protein.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gene {
+    pub name: String,
+    /// Maximal regulated production rate.
+    pub beta: f64,
+    /// Removal rate by degradation and dilution.
+    pub alpha: f64,
+    /// Production independent of the regulators.
+    pub basal: f64,
+    /// Gate of the promoter; `None` uses the default of the network.
+    pub logic: Option<Logic>,
+    pub initial: f64,
+}
+
+impl Gene {
+    /// Gene with unit production and removal rates, no basal production and no protein.
+    pub fn new(name: &str) -> Self {
+        Gene {
+            name: name.to_string(),
+            beta: 1.0,
+            alpha: 1.0,
+            basal: 0.0,
+            logic: None,
+            initial: 0.0,
+        }
+    }
+
+    pub fn beta(mut self, beta: f64) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn basal(mut self, basal: f64) -> Self {
+        self.basal = basal;
+        self
+    }
+
+    pub fn logic(mut self, logic: Logic) -> Self {
+        self.logic = Some(logic);
+        self
+    }
+
+    pub fn initial(mut self, initial: f64) -> Self {
+        self.initial = initial;
+        self
+    }
+}
+
+/// Which number a parameter name refers to.
+enum Slot {
+    Beta(NodeIndex),
+    Alpha(NodeIndex),
+    Basal(NodeIndex),
+    K(EdgeIndex),
+    N(EdgeIndex),
+}
+
+/// ODE model of a gene-regulatory network.
+#[derive(Clone, Debug, Default)]
+pub struct GeneNetwork {
+    name: String,
+    graph: DiGraph<Gene, Hill>,
+    genes: HashMap<String, NodeIndex>,
+    logic: Logic,
+}
+
+impl GeneNetwork {
+    pub fn new(name: &str) -> Self {
+        GeneNetwork {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Network with the genes and regulations of `graph`. Fails on a name with `_` or a name
+    /// given to two genes.
+    pub fn from_graph(name: &str, graph: DiGraph<Gene, Hill>) -> Result<Self, Error> {
+        let mut genes = HashMap::new();
+        for i in graph.node_indices() {
+            let gene = &graph[i].name;
+            check_name(gene)?;
+            if genes.insert(gene.clone(), i).is_some() {
+                return Err(Error::Parse(format!("duplicate gene `{}`", gene)));
+            }
+        }
+        Ok(GeneNetwork {
+            name: name.to_string(),
+            graph,
+            genes,
+            logic: Logic::default(),
+        })
+    }
+
+    /// Gate of the genes that do not set their own.
+    pub fn logic(mut self, logic: Logic) -> Self {
+        self.logic = logic;
+        self
+    }
+
+    /// Adds a gene, or replaces the gene of the same name and keeps its regulation. Fails on
+    /// a name with `_`.
+    pub fn gene(mut self, gene: Gene) -> Result<Self, Error> {
+        check_name(&gene.name)?;
+        match self.genes.get(&gene.name) {
+            Some(i) => self.graph[*i] = gene,
+            None => {
+                let name = gene.name.clone();
+                let i = self.graph.add_node(gene);
+                self.genes.insert(name, i);
+            }
+        }
+        Ok(self)
+    }
+
+    /// Regulation of `target` by `regulator`, adding either gene with the defaults of
+    /// [`Gene::new`] when it is new. A second regulation of the same pair replaces the first.
+    /// Fails on a new name with `_`.
+    pub fn regulation(mut self, regulator: &str, target: &str, hill: Hill) -> Result<Self, Error> {
+        let a = self.index(regulator)?;
+        let b = self.index(target)?;
+        self.graph.update_edge(a, b, hill);
+        Ok(self)
+    }
+
+    fn index(&mut self, name: &str) -> Result<NodeIndex, Error> {
+        if let Some(i) = self.genes.get(name) {
+            return Ok(*i);
+        }
+        check_name(name)?;
+        let i = self.graph.add_node(Gene::new(name));
+        self.genes.insert(name.to_string(), i);
+        Ok(i)
+    }
+
+    pub fn graph(&self) -> &DiGraph<Gene, Hill> {
+        &self.graph
+    }
+
+    /// Gate of the promoter of gene `i`.
+    pub fn gate(&self, i: NodeIndex) -> Logic {
+        self.graph[i].logic.unwrap_or(self.logic)
+    }
+
+    /// Promoter activity of gene `i` at protein levels `x`, between 0 and 1 for AND and OR.
+    pub fn activity(&self, i: NodeIndex, x: &State) -> f64 {
+        let mut inputs: Vec<(EdgeIndex, f64)> = self
+            .graph
+            .edges_directed(i, Direction::Incoming)
+            .map(|e| (e.id(), e.weight().value(x[e.source().index()])))
+            .collect();
+        // petgraph lists incoming edges newest first.
+        inputs.sort_by_key(|(e, _)| *e);
+        let terms: Vec<f64> = inputs.into_iter().map(|(_, f)| f).collect();
+        self.gate(i).combine(&terms)
+    }
+
+    fn slot(&self, name: &str) -> Option<Slot> {
+        let (kind, rest) = name.split_once('_')?;
+        match kind {
+            "beta" | "alpha" | "basal" => {
+                let i = *self.genes.get(rest)?;
+                Some(match kind {
+                    "beta" => Slot::Beta(i),
+                    "alpha" => Slot::Alpha(i),
+                    _ => Slot::Basal(i),
+                })
+            }
+            "k" | "n" => {
+                let e = self.graph.edge_indices().find(|e| {
+                    let (a, b) = self.graph.edge_endpoints(*e).expect("edge of the graph");
+                    rest == format!("{}_{}", self.graph[a].name, self.graph[b].name)
+                })?;
+                Some(if kind == "k" { Slot::K(e) } else { Slot::N(e) })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Gene names are the suffixes of the parameter names after `_`.
+fn check_name(name: &str) -> Result<(), Error> {
+    if name.contains('_') {
+        return Err(Error::Parse(format!("`_` in gene name `{}`", name)));
+    }
+    Ok(())
+}
+
+impl ode_solvers::System<f64, State> for GeneNetwork {
+    fn system(&self, _t: Time, x: &State, dx: &mut State) {
+        for i in self.graph.node_indices() {
+            let gene = &self.graph[i];
+            dx[i.index()] =
+                gene.basal + gene.beta * self.activity(i, x) - gene.alpha * x[i.index()];
+        }
+    }
+}
+
+impl Model for GeneNetwork {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn species(&self) -> Vec<&str> {
+        self.graph
+            .node_weights()
+            .map(|gene| gene.name.as_str())
+            .collect()
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let mut parameters = Vec::new();
+        for gene in self.graph.node_weights() {
+            for (kind, value) in [
+                ("beta", gene.beta),
+                ("alpha", gene.alpha),
+                ("basal", gene.basal),
+            ] {
+                let name = format!("{}_{}", kind, gene.name);
+                parameters.push(Parameter::new(&name, value, value, 0.0, f64::INFINITY));
+            }
+        }
+        for edge in self.graph.edge_references() {
+            let id = format!(
+                "{}_{}",
+                self.graph[edge.source()].name,
+                self.graph[edge.target()].name
+            );
+            let hill = edge.weight();
+            parameters.push(Parameter::new(
+                &format!("k_{}", id),
+                hill.k,
+                hill.k,
+                0.0,
+                f64::INFINITY,
+            ));
+            parameters.push(Parameter::new(
+                &format!("n_{}", id),
+                hill.n,
+                hill.n,
+                0.0,
+                20.0,
+            ));
+        }
+        parameters
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match self.slot(name)? {
+            Slot::Beta(i) => Some(&mut self.graph[i].beta),
+            Slot::Alpha(i) => Some(&mut self.graph[i].alpha),
+            Slot::Basal(i) => Some(&mut self.graph[i].basal),
+            Slot::K(e) => Some(&mut self.graph[e].k),
+            Slot::N(e) => Some(&mut self.graph[e].n),
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_iterator(
+            self.graph.node_count(),
+            self.graph.node_weights().map(|gene| gene.initial),
+        )
+    }
+}
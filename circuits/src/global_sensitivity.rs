@@ -0,0 +1,272 @@
+//! Global sensitivity of a scalar model output over ranges of the parameters.
+//!
+//! [`Morris`] screens the parameters with elementary effects along random one-at-a-time
+//! trajectories. [`Sobol`] estimates first-order and total-order indices with the Saltelli
+//! sampling scheme and the Saltelli (2010) and Jansen estimators. Both draw from a caller
+//! supplied generator, so a seeded `ChaCha8Rng` gives reproducible results. Parameters whose
+//! range spans orders of magnitude can be sampled uniformly in their logarithm.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::model::{Error, Model};
+
+/// Range of a parameter, sampled uniformly or log-uniformly.
+#[derive(Clone, Debug)]
+pub struct Range {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+    pub log: bool,
+}
+
+impl Range {
+    pub fn new(name: &str, min: f64, max: f64) -> Self {
+        Range {
+            name: name.to_string(),
+            min,
+            max,
+            log: false,
+        }
+    }
+
+    /// Sample the logarithm of the parameter uniformly; both bounds must be positive.
+    pub fn log(mut self) -> Self {
+        self.log = true;
+        self
+    }
+
+    /// The parameter value at fraction `u` of the range.
+    pub fn value(&self, u: f64) -> f64 {
+        if self.log {
+            (self.min.ln() + u * (self.max.ln() - self.min.ln())).exp()
+        } else {
+            self.min + u * (self.max - self.min)
+        }
+    }
+}
+
+/// The output of `model` with its parameters at the point `u` of the unit cube.
+fn evaluate<M: Model + Clone, F: Fn(&M) -> f64>(
+    model: &M,
+    ranges: &[Range],
+    u: &[f64],
+    output: &F,
+) -> f64 {
+    let mut model = model.clone();
+    for (range, u) in ranges.iter().zip(u) {
+        if let Some(p) = model.parameter_mut(&range.name) {
+            *p = range.value(*u);
+        }
+    }
+    output(&model)
+}
+
+fn check<M: Model>(model: &M, ranges: &[Range]) -> Result<(), Error> {
+    for range in ranges {
+        if model.get(&range.name).is_none() {
+            return Err(Error::UnknownParameter(range.name.clone()));
+        }
+        if range.min >= range.max || range.min.is_nan() || (range.log && range.min <= 0.0) {
+            return Err(Error::InvalidRange {
+                name: range.name.clone(),
+                min: range.min,
+                max: range.max,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Mean, absolute mean and standard deviation of the elementary effects of a parameter.
+#[derive(Clone, Debug)]
+pub struct ElementaryEffects {
+    pub parameter: String,
+    pub mu: f64,
+    pub mu_star: f64,
+    pub sigma: f64,
+}
+
+/// Morris elementary-effects screening.
+#[derive(Clone, Debug)]
+pub struct Morris {
+    pub ranges: Vec<Range>,
+    /// Number of one-at-a-time trajectories.
+    pub trajectories: usize,
+    /// Number of grid levels along each parameter.
+    pub levels: usize,
+}
+
+impl Morris {
+    pub fn new(ranges: Vec<Range>) -> Self {
+        Morris {
+            ranges,
+            trajectories: 20,
+            levels: 4,
+        }
+    }
+
+    pub fn trajectories(mut self, trajectories: usize) -> Self {
+        self.trajectories = trajectories;
+        self
+    }
+
+    pub fn levels(mut self, levels: usize) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Elementary effects of every parameter on `output`, in units of the scaled ranges.
+    pub fn run<M, F, R>(
+        &self,
+        model: &M,
+        output: F,
+        rng: &mut R,
+    ) -> Result<Vec<ElementaryEffects>, Error>
+    where
+        M: Model + Clone,
+        F: Fn(&M) -> f64,
+        R: Rng,
+    {
+        check(model, &self.ranges)?;
+        let k = self.ranges.len();
+        let p = self.levels.max(2);
+        let delta = p as f64 / (2.0 * (p - 1) as f64);
+        // Grid levels from which a step of delta stays inside the unit interval.
+        let starts = p / 2;
+        let mut effects = vec![Vec::with_capacity(self.trajectories); k];
+        let mut order: Vec<usize> = (0..k).collect();
+        for _ in 0..self.trajectories {
+            let mut x: Vec<f64> = (0..k)
+                .map(|_| {
+                    let level = rng.random_range(0..starts) as f64 / (p - 1) as f64;
+                    if rng.random::<bool>() {
+                        level
+                    } else {
+                        level + delta
+                    }
+                })
+                .collect();
+            order.shuffle(rng);
+            let mut y = evaluate(model, &self.ranges, &x, &output);
+            for &i in &order {
+                let step = if x[i] + delta <= 1.0 + 1e-12 {
+                    delta
+                } else {
+                    -delta
+                };
+                x[i] += step;
+                let next = evaluate(model, &self.ranges, &x, &output);
+                effects[i].push((next - y) / step);
+                y = next;
+            }
+        }
+        Ok(self
+            .ranges
+            .iter()
+            .zip(effects)
+            .map(|(range, effects)| {
+                let n = effects.len().max(1) as f64;
+                let mu = effects.iter().sum::<f64>() / n;
+                let mu_star = effects.iter().map(|e| e.abs()).sum::<f64>() / n;
+                let sigma = (effects.iter().map(|e| (e - mu).powi(2)).sum::<f64>()
+                    / (n - 1.0).max(1.0))
+                .sqrt();
+                ElementaryEffects {
+                    parameter: range.name.clone(),
+                    mu,
+                    mu_star,
+                    sigma,
+                }
+            })
+            .collect())
+    }
+}
+
+/// First-order and total-order Sobol indices of a parameter.
+#[derive(Clone, Debug)]
+pub struct SobolIndices {
+    pub parameter: String,
+    pub first: f64,
+    pub total: f64,
+}
+
+/// Variance-based sensitivity indices by Saltelli sampling.
+#[derive(Clone, Debug)]
+pub struct Sobol {
+    pub ranges: Vec<Range>,
+    /// Rows of each of the two base samples; the model is evaluated `samples * (k + 2)` times.
+    pub samples: usize,
+}
+
+impl Sobol {
+    pub fn new(ranges: Vec<Range>) -> Self {
+        Sobol {
+            ranges,
+            samples: 1000,
+        }
+    }
+
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Indices of every parameter on `output`, all zero when the output is constant.
+    pub fn run<M, F, R>(
+        &self,
+        model: &M,
+        output: F,
+        rng: &mut R,
+    ) -> Result<Vec<SobolIndices>, Error>
+    where
+        M: Model + Clone,
+        F: Fn(&M) -> f64,
+        R: Rng,
+    {
+        check(model, &self.ranges)?;
+        let k = self.ranges.len();
+        let n = self.samples.max(2);
+        let mut sample = || -> Vec<Vec<f64>> {
+            (0..n)
+                .map(|_| (0..k).map(|_| rng.random::<f64>()).collect())
+                .collect()
+        };
+        let a = sample();
+        let b = sample();
+        let f = |x: &Vec<f64>| evaluate(model, &self.ranges, x, &output);
+        let mut fa: Vec<f64> = a.iter().map(f).collect();
+        let mut fb: Vec<f64> = b.iter().map(f).collect();
+
+        // Centring the outputs leaves the estimators unbiased and lowers their variance.
+        let mean = fa.iter().chain(&fb).sum::<f64>() / (2 * n) as f64;
+        fa.iter_mut().chain(&mut fb).for_each(|y| *y -= mean);
+        let variance = fa.iter().chain(&fb).map(|y| y * y).sum::<f64>() / (2 * n - 1) as f64;
+        // An output that does not vary is sensitive to none of the parameters.
+        let scale = if variance > 0.0 { 1.0 / variance } else { 0.0 };
+
+        Ok(self
+            .ranges
+            .iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let fab: Vec<f64> = a
+                    .iter()
+                    .zip(&b)
+                    .map(|(a, b)| {
+                        let mut x = a.clone();
+                        x[i] = b[i];
+                        f(&x) - mean
+                    })
+                    .collect();
+                let first = (0..n).map(|j| fb[j] * (fab[j] - fa[j])).sum::<f64>() / n as f64;
+                let total = (0..n).map(|j| (fa[j] - fab[j]).powi(2)).sum::<f64>() / (2 * n) as f64;
+                SobolIndices {
+                    parameter: range.name.clone(),
+                    first: first * scale,
+                    total: total * scale,
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,45 @@
+//! # Circuits
+//! Shared models of the biological circuits used in the chapters of this repository.
+//!
+//! Every model implements [`ode_solvers::System`] on a dynamic [`State`] vector and the
+//! [`Model`] trait, which exposes the names of the state variables, the parameters with their
+//! defaults and bounds and the initial conditions. Apps can build their inputs, tables and
+//! plots from this metadata instead of wiring every model by hand.
+//!
+//! ```
+//! use circuits::models::CoffeeRust;
+//! use circuits::Model;
+//!
+//! let mut model = CoffeeRust::default();
+//! model.set("d", 0.002)?;
+//! println!("{:?} {:?}", model.species(), model.initial_state());
+//! # Ok::<(), circuits::Error>(())
+//! ```
+
+pub mod analysis;
+pub mod approximation;
+pub mod boolean;
+pub mod continuation;
+pub mod delay;
+pub mod events;
+pub mod fitting;
+pub mod gene_network;
+pub mod global_sensitivity;
+pub mod mcmc;
+pub mod model;
+pub mod models;
+pub mod motifs;
+pub mod phase_plane;
+mod random;
+pub mod reactions;
+pub mod regulation;
+pub mod sbml;
+pub mod sensitivity;
+pub mod signal;
+pub mod stability;
+pub mod stiff;
+pub mod stochastic;
+pub mod sweep;
+pub mod trajectory;
+
+pub use model::{Error, Model, Parameter, State, Time};
@@ -0,0 +1,452 @@
+//! Bayesian inference of model parameters with an affine-invariant ensemble sampler.
+//!
+//! An [`Ensemble`] moves a set of walkers with the stretch move of Goodman and Weare, which
+//! needs no tuning and is unaffected by parameters of very different scales. The likelihood
+//! compares the model, simulated from its initial state, with [`Observations`] under Gaussian
+//! noise of a given standard deviation per column. The walkers are the chains of the
+//! [`Posterior`], from which the split R-hat, the effective sample size and posterior-predictive
+//! bands are computed. Randomness comes from the caller, so a seeded `ChaCha8Rng` reproduces the
+//! chains exactly.
+//!
+//! Each parameter has a [`Prior`]: uniform, log-uniform, normal or log-normal.
+
+use nalgebra::DVector;
+use rand::Rng;
+
+use crate::fitting::{simulate, Observations};
+use crate::model::{Error, Model, State};
+use crate::random::{normal, quantile};
+
+/// Fewest kept steps per chain for which [`Posterior::r_hat`] is defined.
+pub const MIN_R_HAT_STEPS: usize = 4;
+
+/// Prior distribution of a parameter; densities are not normalized.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Prior {
+    Uniform {
+        min: f64,
+        max: f64,
+    },
+    /// Uniform in the logarithm of the parameter.
+    LogUniform {
+        min: f64,
+        max: f64,
+    },
+    Normal {
+        mean: f64,
+        sd: f64,
+    },
+    /// Normal in the natural logarithm of the parameter.
+    LogNormal {
+        mu: f64,
+        sigma: f64,
+    },
+}
+
+impl Prior {
+    pub fn log_density(&self, x: f64) -> f64 {
+        match *self {
+            Prior::Uniform { min, max } if min <= x && x <= max => 0.0,
+            Prior::LogUniform { min, max } if min <= x && x <= max => -x.ln(),
+            Prior::Normal { mean, sd } => -0.5 * ((x - mean) / sd).powi(2),
+            Prior::LogNormal { mu, sigma } if x > 0.0 => {
+                -x.ln() - 0.5 * ((x.ln() - mu) / sigma).powi(2)
+            }
+            _ => f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Settings of an ensemble sampler.
+#[derive(Clone, Debug)]
+pub struct Ensemble {
+    pub priors: Vec<(String, Prior)>,
+    /// Standard deviations of the measurement noise per column, 1 unless given.
+    pub sigmas: Vec<(String, f64)>,
+    pub walkers: usize,
+    /// Steps kept per walker after the burn-in.
+    pub steps: usize,
+    pub burn_in: usize,
+    /// Scale of the stretch move.
+    pub stretch: f64,
+}
+
+impl Ensemble {
+    pub fn new(priors: Vec<(&str, Prior)>) -> Self {
+        let walkers = (4 * priors.len()).max(8);
+        Ensemble {
+            priors: priors
+                .into_iter()
+                .map(|(name, prior)| (name.to_string(), prior))
+                .collect(),
+            sigmas: Vec::new(),
+            walkers,
+            steps: 1000,
+            burn_in: 500,
+            stretch: 2.0,
+        }
+    }
+
+    pub fn sigma(mut self, column: &str, sigma: f64) -> Self {
+        self.sigmas.retain(|(c, _)| c != column);
+        self.sigmas.push((column.to_string(), sigma));
+        self
+    }
+
+    pub fn walkers(mut self, walkers: usize) -> Self {
+        self.walkers = walkers;
+        self
+    }
+
+    pub fn steps(mut self, steps: usize) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    pub fn burn_in(mut self, burn_in: usize) -> Self {
+        self.burn_in = burn_in;
+        self
+    }
+
+    /// Samples the posterior of the parameters, starting the walkers in a small ball around
+    /// their current values in `model`.
+    pub fn run<M, R>(
+        &self,
+        model: &M,
+        observations: &Observations,
+        rng: &mut R,
+    ) -> Result<Posterior, Error>
+    where
+        M: Model + Clone,
+        R: Rng,
+    {
+        let start = self
+            .priors
+            .iter()
+            .map(|(name, _)| {
+                model
+                    .get(name)
+                    .ok_or_else(|| Error::UnknownParameter(name.clone()))
+            })
+            .collect::<Result<Vec<f64>, Error>>()?;
+        let columns: Vec<(usize, f64, &Vec<f64>)> = observations
+            .indices(model)?
+            .into_iter()
+            .zip(&observations.columns)
+            .map(|(index, (name, values))| {
+                let sigma = self
+                    .sigmas
+                    .iter()
+                    .find(|(c, _)| c == name)
+                    .map_or(1.0, |(_, s)| *s);
+                (index, sigma, values)
+            })
+            .collect();
+
+        let log_posterior = |theta: &DVector<f64>| -> f64 {
+            let prior: f64 = self
+                .priors
+                .iter()
+                .zip(theta.iter())
+                .map(|((_, prior), x)| prior.log_density(*x))
+                .sum();
+            if !prior.is_finite() {
+                return f64::NEG_INFINITY;
+            }
+            let mut model = model.clone();
+            for ((name, _), x) in self.priors.iter().zip(theta.iter()) {
+                if let Some(p) = model.parameter_mut(name) {
+                    *p = *x;
+                }
+            }
+            let Ok(states) = simulate(&model, &observations.t) else {
+                return f64::NEG_INFINITY;
+            };
+            let mut log_likelihood = 0.0;
+            for (index, sigma, values) in &columns {
+                for (state, observed) in states.iter().zip(values.iter()) {
+                    if observed.is_finite() {
+                        log_likelihood -= 0.5 * ((state[*index] - observed) / sigma).powi(2);
+                    }
+                }
+            }
+            prior + log_likelihood
+        };
+
+        let k = self.walkers.max(2 * self.priors.len() + 2);
+        let mut positions = Vec::with_capacity(k);
+        let mut densities = Vec::with_capacity(k);
+        let mut attempts = 0;
+        while positions.len() < k {
+            let theta = DVector::from_iterator(
+                start.len(),
+                start
+                    .iter()
+                    .map(|x| x + 1e-3 * x.abs().max(1e-12) * normal(rng)),
+            );
+            let density = log_posterior(&theta);
+            if density.is_finite() {
+                positions.push(theta);
+                densities.push(density);
+            } else {
+                attempts += 1;
+                if attempts > 100 * k {
+                    return Err(Error::NoConvergence(
+                        "the posterior vanishes around the starting parameters".to_string(),
+                    ));
+                }
+            }
+        }
+
+        let a = self.stretch;
+        let mut chains = vec![Vec::with_capacity(self.steps); k];
+        let (mut proposed, mut accepted) = (0usize, 0usize);
+        for step in 0..self.burn_in + self.steps {
+            for i in 0..k {
+                let j = (i + 1 + rng.random_range(0..k - 1)) % k;
+                let z = ((a - 1.0) * rng.random::<f64>() + 1.0).powi(2) / a;
+                let trial = &positions[j] + (&positions[i] - &positions[j]) * z;
+                let density = log_posterior(&trial);
+                let log_ratio = (start.len() as f64 - 1.0) * z.ln() + density - densities[i];
+                let accept = density.is_finite() && rng.random::<f64>().ln() < log_ratio;
+                if step >= self.burn_in {
+                    proposed += 1;
+                    accepted += accept as usize;
+                }
+                if accept {
+                    positions[i] = trial;
+                    densities[i] = density;
+                }
+                if step >= self.burn_in {
+                    chains[i].push((positions[i].clone(), densities[i]));
+                }
+            }
+        }
+
+        Ok(Posterior {
+            parameters: self.priors.iter().map(|(name, _)| name.clone()).collect(),
+            chains: chains
+                .iter()
+                .map(|c| c.iter().map(|(x, _)| x.clone()).collect())
+                .collect(),
+            log_posterior: chains
+                .iter()
+                .map(|c| c.iter().map(|(_, d)| *d).collect())
+                .collect(),
+            acceptance: accepted as f64 / proposed.max(1) as f64,
+            sigmas: self.sigmas.clone(),
+        })
+    }
+}
+
+/// Credible band of a species over time.
+#[derive(Clone, Debug)]
+pub struct Band {
+    pub species: String,
+    pub t: Vec<f64>,
+    pub lower: Vec<f64>,
+    pub median: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+/// Samples of the posterior, one chain per walker.
+#[derive(Clone, Debug)]
+pub struct Posterior {
+    pub parameters: Vec<String>,
+    /// Positions of every walker at every kept step.
+    pub chains: Vec<Vec<DVector<f64>>>,
+    pub log_posterior: Vec<Vec<f64>>,
+    /// Fraction of accepted moves after the burn-in.
+    pub acceptance: f64,
+    /// Noise of the observed columns in the likelihood.
+    pub sigmas: Vec<(String, f64)>,
+}
+
+impl Posterior {
+    /// Values of parameter `i` in all chains, chain after chain.
+    pub fn samples(&self, i: usize) -> Vec<f64> {
+        self.chains.iter().flatten().map(|x| x[i]).collect()
+    }
+
+    pub fn mean(&self) -> Vec<f64> {
+        (0..self.parameters.len())
+            .map(|i| {
+                let samples = self.samples(i);
+                samples.iter().sum::<f64>() / samples.len().max(1) as f64
+            })
+            .collect()
+    }
+
+    /// Quantile `q` of every parameter.
+    pub fn quantile(&self, q: f64) -> Vec<f64> {
+        (0..self.parameters.len())
+            .map(|i| quantile(&mut self.samples(i), q))
+            .collect()
+    }
+
+    /// Split R-hat of Gelman and Rubin for every parameter, with each chain cut in halves.
+    /// `None` with fewer than [`MIN_R_HAT_STEPS`] kept steps per chain, where the halves have
+    /// no variance to compare.
+    pub fn r_hat(&self) -> Option<Vec<f64>> {
+        if self.chains.first().map_or(0, |c| c.len()) < MIN_R_HAT_STEPS {
+            return None;
+        }
+        let r_hat = (0..self.parameters.len())
+            .map(|i| {
+                let half = self.chains.first().map_or(0, |c| c.len() / 2);
+                let chains: Vec<Vec<f64>> = self
+                    .chains
+                    .iter()
+                    .flat_map(|c| [&c[..half], &c[half..2 * half]])
+                    .map(|c| c.iter().map(|x| x[i]).collect())
+                    .collect();
+                let (m, n) = (chains.len() as f64, half as f64);
+                let means: Vec<f64> = chains.iter().map(|c| c.iter().sum::<f64>() / n).collect();
+                let mean = means.iter().sum::<f64>() / m;
+                let between = n / (m - 1.0) * means.iter().map(|x| (x - mean).powi(2)).sum::<f64>();
+                let within = chains
+                    .iter()
+                    .zip(&means)
+                    .map(|(c, mu)| c.iter().map(|x| (x - mu).powi(2)).sum::<f64>() / (n - 1.0))
+                    .sum::<f64>()
+                    / m;
+                (((n - 1.0) / n * within + between / n) / within).sqrt()
+            })
+            .collect();
+        Some(r_hat)
+    }
+
+    /// Effective sample size of every parameter from the integrated autocorrelation time,
+    /// averaging the autocorrelation over the chains and cutting the sum with Sokal's window.
+    pub fn ess(&self) -> Vec<f64> {
+        let n = self.chains.first().map_or(0, |c| c.len());
+        (0..self.parameters.len())
+            .map(|i| {
+                let chains: Vec<Vec<f64>> = self
+                    .chains
+                    .iter()
+                    .map(|c| {
+                        let mean = c.iter().map(|x| x[i]).sum::<f64>() / n as f64;
+                        c.iter().map(|x| x[i] - mean).collect()
+                    })
+                    .collect();
+                let autocovariance = |lag: usize| {
+                    chains
+                        .iter()
+                        .map(|c| (0..n - lag).map(|t| c[t] * c[t + lag]).sum::<f64>() / n as f64)
+                        .sum::<f64>()
+                };
+                let variance = autocovariance(0);
+                let mut tau = 1.0;
+                if variance > 0.0 {
+                    for lag in 1..n {
+                        tau += 2.0 * autocovariance(lag) / variance;
+                        if lag as f64 >= 5.0 * tau {
+                            break;
+                        }
+                    }
+                }
+                (chains.len() * n) as f64 / tau.max(1.0)
+            })
+            .collect()
+    }
+
+    /// Bands of the model species at `times` containing `level` of the posterior predictive
+    /// distribution, from `draws` random samples. Observed species include their noise.
+    pub fn predictive<M, R>(
+        &self,
+        model: &M,
+        times: &[f64],
+        draws: usize,
+        level: f64,
+        rng: &mut R,
+    ) -> Result<Vec<Band>, Error>
+    where
+        M: Model + Clone,
+        R: Rng,
+    {
+        let samples: Vec<&DVector<f64>> = self.chains.iter().flatten().collect();
+        if samples.is_empty() {
+            return Err(Error::NoConvergence(
+                "the posterior has no samples".to_string(),
+            ));
+        }
+        let species = model.species();
+        let noise: Vec<f64> = species
+            .iter()
+            .map(|s| {
+                self.sigmas
+                    .iter()
+                    .find(|(c, _)| c == s)
+                    .map_or(0.0, |(_, v)| *v)
+            })
+            .collect();
+        let mut trajectories: Vec<Vec<State>> = Vec::with_capacity(draws);
+        for _ in 0..draws {
+            let theta = samples[rng.random_range(0..samples.len())];
+            let mut model = model.clone();
+            for (name, x) in self.parameters.iter().zip(theta.iter()) {
+                if let Some(p) = model.parameter_mut(name) {
+                    *p = *x;
+                }
+            }
+            let mut states = simulate(&model, times)?;
+            for state in &mut states {
+                for (v, sigma) in state.iter_mut().zip(&noise) {
+                    *v += sigma * normal(rng);
+                }
+            }
+            trajectories.push(states);
+        }
+        let tail = (1.0 - level) / 2.0;
+        Ok(species
+            .iter()
+            .enumerate()
+            .map(|(s, name)| {
+                let mut band = Band {
+                    species: name.to_string(),
+                    t: times.to_vec(),
+                    lower: Vec::with_capacity(times.len()),
+                    median: Vec::with_capacity(times.len()),
+                    upper: Vec::with_capacity(times.len()),
+                };
+                for k in 0..times.len() {
+                    let mut values: Vec<f64> = trajectories.iter().map(|y| y[k][s]).collect();
+                    band.lower.push(quantile(&mut values, tail));
+                    band.median.push(quantile(&mut values, 0.5));
+                    band.upper.push(quantile(&mut values, 1.0 - tail));
+                }
+                band
+            })
+            .collect())
+    }
+
+    /// Columns `walker`, `step` and one per parameter, ready for a DataFrame.
+    pub fn columns(&self) -> Vec<(String, Vec<f64>)> {
+        let mut columns = vec![
+            (
+                "walker".to_string(),
+                self.chains
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(w, c)| std::iter::repeat_n(w as f64, c.len()))
+                    .collect(),
+            ),
+            (
+                "step".to_string(),
+                self.chains
+                    .iter()
+                    .flat_map(|c| (0..c.len()).map(|s| s as f64))
+                    .collect(),
+            ),
+        ];
+        for (i, name) in self.parameters.iter().enumerate() {
+            columns.push((name.clone(), self.samples(i)));
+        }
+        columns.push((
+            "log_posterior".to_string(),
+            self.log_posterior.iter().flatten().copied().collect(),
+        ));
+        columns
+    }
+}
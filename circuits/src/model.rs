@@ -0,0 +1,155 @@
+use ode_solvers::{DVector, System};
+use std::fmt;
+
+pub type State = DVector<f64>;
+pub type Time = f64;
+
+/// A named model parameter with its current value, default and allowed range.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub value: f64,
+    pub default: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Parameter {
+    pub fn new(name: &str, value: f64, default: f64, min: f64, max: f64) -> Self {
+        Parameter {
+            name: name.to_string(),
+            value,
+            default,
+            min,
+            max,
+        }
+    }
+
+    /// Returns true when `value` lies inside the bounds of the parameter.
+    pub fn contains(&self, value: f64) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+/// Errors raised when a model is queried or changed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    UnknownParameter(String),
+    OutOfBounds {
+        name: String,
+        value: f64,
+    },
+    Parse(String),
+    Io(String),
+    /// An iterative solver did not converge.
+    NoConvergence(String),
+    /// An empty or, for log sampling, non-positive parameter range.
+    InvalidRange {
+        name: String,
+        min: f64,
+        max: f64,
+    },
+    /// A list of values of the wrong length.
+    Dimension {
+        expected: usize,
+        found: usize,
+    },
+    /// An argument a function cannot work with, other than a parameter value.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnknownParameter(name) => write!(f, "unknown parameter `{}`", name),
+            Error::OutOfBounds { name, value } => {
+                write!(
+                    f,
+                    "value {} is out of bounds for parameter `{}`",
+                    value, name
+                )
+            }
+            Error::Parse(message) => write!(f, "parse error: {}", message),
+            Error::Io(message) => write!(f, "io error: {}", message),
+            Error::NoConvergence(message) => write!(f, "no convergence: {}", message),
+            Error::InvalidRange { name, min, max } => {
+                write!(
+                    f,
+                    "invalid range [{}, {}] for parameter `{}`",
+                    min, max, name
+                )
+            }
+            Error::Dimension { expected, found } => {
+                write!(f, "expected {} values, found {}", expected, found)
+            }
+            Error::InvalidArgument(message) => write!(f, "invalid argument: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A parameterized system of ODEs with names for its state variables and parameters.
+pub trait Model: System<f64, State> {
+    /// Human readable name of the model.
+    fn name(&self) -> &str;
+
+    /// Names of the state variables, in the order of the state vector.
+    fn species(&self) -> Vec<&str>;
+
+    /// Parameters of the model with their current values.
+    fn parameters(&self) -> Vec<Parameter>;
+
+    /// Mutable access to a parameter by name.
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64>;
+
+    /// Default initial conditions.
+    fn initial_state(&self) -> State;
+
+    /// Number of state variables.
+    fn dim(&self) -> usize {
+        self.species().len()
+    }
+
+    /// Current value of a parameter.
+    fn get(&self, name: &str) -> Option<f64> {
+        self.parameters()
+            .into_iter()
+            .find(|p| p.name == name)
+            .map(|p| p.value)
+    }
+
+    /// Sets a parameter, checking its bounds.
+    fn set(&mut self, name: &str, value: f64) -> Result<(), Error> {
+        let parameter = self
+            .parameters()
+            .into_iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| Error::UnknownParameter(name.to_string()))?;
+        if !parameter.contains(value) {
+            return Err(Error::OutOfBounds {
+                name: name.to_string(),
+                value,
+            });
+        }
+        match self.parameter_mut(name) {
+            Some(p) => {
+                *p = value;
+                Ok(())
+            }
+            None => Err(Error::UnknownParameter(name.to_string())),
+        }
+    }
+
+    /// Index of a state variable by name.
+    fn species_index(&self, name: &str) -> Option<usize> {
+        self.species().iter().position(|s| *s == name)
+    }
+
+    /// Right hand side of the model evaluated at `(t, y)`.
+    fn rhs(&self, t: Time, y: &State) -> State {
+        let mut dy = State::zeros(y.len());
+        self.system(t, y, &mut dy);
+        dy
+    }
+}
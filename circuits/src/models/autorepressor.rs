@@ -0,0 +1,110 @@
+use crate::model::{Model, Parameter, State, Time};
+use crate::signal::Signal;
+
+/// Production of an autorepressed gene `x` activated by the inducer `s`, minus its decay.
+#[allow(clippy::too_many_arguments)]
+pub fn autorepressive(
+    x: f64,
+    beta0: f64,
+    gamma: f64,
+    k: f64,
+    n: f64,
+    ks: f64,
+    ns: f64,
+    s: f64,
+) -> f64 {
+    beta0 * (s / ks).powf(ns) / (1.0 + (s / ks).powf(ns)) / (1.0 + (x / k).powf(n)) - gamma * x
+}
+
+/// Analytic solution of unregulated expression starting from zero.
+pub fn unregulated(t: f64, beta0: f64, gamma: f64) -> f64 {
+    beta0 / gamma * (1.0 - (-gamma * t).exp())
+}
+
+/// Negative autoregulation driven by the input signal `s`.
+#[derive(Clone, Debug)]
+pub struct Autorepressor {
+    pub beta0: f64,
+    pub gamma: f64,
+    pub k: f64,
+    pub n: f64,
+    pub ks: f64,
+    pub ns: f64,
+    pub s: Signal,
+}
+
+impl Default for Autorepressor {
+    fn default() -> Self {
+        Autorepressor {
+            beta0: 100.0,
+            gamma: 1.0,
+            k: 1.0,
+            n: 1.0,
+            ks: 0.1,
+            ns: 10.0,
+            s: Signal::Constant(100.0),
+        }
+    }
+}
+
+impl ode_solvers::System<f64, State> for Autorepressor {
+    fn system(&self, t: Time, y: &State, dy: &mut State) {
+        dy[0] = autorepressive(
+            y[0],
+            self.beta0,
+            self.gamma,
+            self.k,
+            self.n,
+            self.ks,
+            self.ns,
+            self.s.value(t),
+        );
+    }
+}
+
+impl Model for Autorepressor {
+    fn name(&self) -> &str {
+        "Autorepressor"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["x"]
+    }
+
+    /// The input `s` is a parameter only while it is a constant signal.
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        let mut parameters = vec![
+            Parameter::new("beta0", self.beta0, d.beta0, 0.0, 1000.0),
+            Parameter::new("gamma", self.gamma, d.gamma, 0.0, 100.0),
+            Parameter::new("k", self.k, d.k, 0.0, 100.0),
+            Parameter::new("n", self.n, d.n, 0.0, 10.0),
+            Parameter::new("ks", self.ks, d.ks, 0.0, 100.0),
+            Parameter::new("ns", self.ns, d.ns, 0.0, 20.0),
+        ];
+        if let (Signal::Constant(s), Signal::Constant(default)) = (&self.s, &d.s) {
+            parameters.push(Parameter::new("s", *s, *default, 0.0, 1000.0));
+        }
+        parameters
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "beta0" => Some(&mut self.beta0),
+            "gamma" => Some(&mut self.gamma),
+            "k" => Some(&mut self.k),
+            "n" => Some(&mut self.n),
+            "ks" => Some(&mut self.ks),
+            "ns" => Some(&mut self.ns),
+            "s" => match &mut self.s {
+                Signal::Constant(s) => Some(s),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![0.0])
+    }
+}
@@ -0,0 +1,106 @@
+use crate::model::{Model, Parameter, State, Time};
+use crate::reactions::ReactionNetwork;
+
+/// Coffee leaf rust on susceptible `T_s` and infected `T_i` trees, controlled by snails `S`.
+///
+/// The default rates `a`, `beta`, `k`, `gamma` and `b` are the published daily values divided
+/// by `24 * 24 = 576`; the starvation rate `d` is used as published.
+#[derive(Copy, Clone, Debug)]
+pub struct CoffeeRust {
+    pub a: f64,
+    pub beta: f64,
+    pub k: f64,
+    pub gamma: f64,
+    pub b: f64,
+    pub d: f64,
+}
+
+impl Default for CoffeeRust {
+    fn default() -> Self {
+        CoffeeRust {
+            a: 0.0000283 / (24.0 * 24.0),
+            beta: 0.0298 / (24.0 * 24.0),
+            k: 0.07333 / (24.0 * 24.0),
+            gamma: 0.005 / (24.0 * 24.0),
+            b: 0.0025 / (24.0 * 24.0),
+            d: 0.00125,
+        }
+    }
+}
+
+impl CoffeeRust {
+    /// The same model as a reaction network of trees and snails.
+    pub fn network(&self) -> ReactionNetwork {
+        let y0 = self.initial_state();
+        let mut network = ReactionNetwork::new(self.name())
+            .species("T_s", y0[0])
+            .species("T_i", y0[1])
+            .species("S", y0[2]);
+        for parameter in self.parameters() {
+            network = network.parameter_with_bounds(
+                &parameter.name,
+                parameter.value,
+                parameter.min,
+                parameter.max,
+            );
+        }
+        network
+            .reaction("growth", "T_s => 2 T_s @ a")
+            .and_then(|n| n.reaction("infection", "T_s + T_i => 2 T_i @ beta"))
+            .and_then(|n| n.reaction("control", "S + T_i => S + T_s @ k"))
+            .and_then(|n| n.reaction("death", "T_i => @ gamma"))
+            .and_then(|n| n.reaction("predation", "S + T_i => 2 S + T_i @ b"))
+            .and_then(|n| n.reaction("starvation", "S => @ d"))
+            .expect("valid coffee rust reactions")
+    }
+}
+
+impl ode_solvers::System<f64, State> for CoffeeRust {
+    fn system(&self, _t: Time, y: &State, dy: &mut State) {
+        let y1 = y[0];
+        let y2 = y[1];
+        let y3 = y[2];
+
+        dy[0] = self.a * y1 - self.beta * y1 * y2 + self.k * y3 * y2;
+        dy[1] = self.beta * y1 * y2 - self.k * y3 * y2 - self.gamma * y2;
+        dy[2] = self.b * y3 * y2 - self.d * y3;
+    }
+}
+
+impl Model for CoffeeRust {
+    fn name(&self) -> &str {
+        "Coffee tree rust"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["T_s", "T_i", "S"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("a", self.a, d.a, 0.0, 1.0),
+            Parameter::new("beta", self.beta, d.beta, 0.0, 1.0),
+            Parameter::new("k", self.k, d.k, 0.0, 1.0),
+            Parameter::new("gamma", self.gamma, d.gamma, 0.0, 1.0),
+            Parameter::new("b", self.b, d.b, 0.0, 1.0),
+            Parameter::new("d", self.d, d.d, 0.0, 1.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "a" => Some(&mut self.a),
+            "beta" => Some(&mut self.beta),
+            "k" => Some(&mut self.k),
+            "gamma" => Some(&mut self.gamma),
+            "b" => Some(&mut self.b),
+            "d" => Some(&mut self.d),
+            _ => None,
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![1000.0, 500.0, 0.0])
+    }
+}
@@ -0,0 +1,298 @@
+use crate::delay::DelaySystem;
+use crate::model::{Model, Parameter, State, Time};
+use crate::reactions::ReactionNetwork;
+
+/// Unregulated expression of mRNA `m` and protein `p`.
+#[derive(Copy, Clone, Debug)]
+pub struct Unregulated {
+    pub beta_m: f64,
+    pub gamma_m: f64,
+    pub beta_p: f64,
+    pub gamma_p: f64,
+}
+
+impl Default for Unregulated {
+    fn default() -> Self {
+        Unregulated {
+            beta_m: 1.0,
+            gamma_m: 1.0,
+            beta_p: 1.0,
+            gamma_p: 0.1,
+        }
+    }
+}
+
+impl ode_solvers::System<f64, State> for Unregulated {
+    // x(t) =  (self.beta / self.gamma) * (1.0- (-self.gamma*_t).exp());
+    fn system(&self, _t: Time, x: &State, dx: &mut State) {
+        dx[0] = self.beta_m - self.gamma_m * x[0];
+        dx[1] = self.beta_p * x[0] - self.gamma_p * x[1];
+    }
+}
+
+impl Model for Unregulated {
+    fn name(&self) -> &str {
+        "Unregulated expression"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["m", "p"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("beta_m", self.beta_m, d.beta_m, 0.0, 100.0),
+            Parameter::new("gamma_m", self.gamma_m, d.gamma_m, 0.0, 100.0),
+            Parameter::new("beta_p", self.beta_p, d.beta_p, 0.0, 100.0),
+            Parameter::new("gamma_p", self.gamma_p, d.gamma_p, 0.0, 100.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "beta_m" => Some(&mut self.beta_m),
+            "gamma_m" => Some(&mut self.gamma_m),
+            "beta_p" => Some(&mut self.beta_p),
+            "gamma_p" => Some(&mut self.gamma_p),
+            _ => None,
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![0.0, 0.0])
+    }
+}
+
+/// Negative autoregulation of mRNA `m` by its own protein `p`.
+#[derive(Copy, Clone, Debug)]
+pub struct NegativeAutoregulation {
+    pub beta_m: f64,
+    pub gamma_m: f64,
+    pub beta_p: f64,
+    pub gamma_p: f64,
+    pub n: f64,
+    pub k: f64,
+}
+
+impl Default for NegativeAutoregulation {
+    fn default() -> Self {
+        NegativeAutoregulation {
+            beta_m: 1.0,
+            gamma_m: 1.0,
+            beta_p: 1.0,
+            gamma_p: 0.1,
+            n: 2.0,
+            k: 1.0,
+        }
+    }
+}
+
+impl NegativeAutoregulation {
+    /// The same model as a reaction network with a repressive Hill transcription rate.
+    pub fn network(&self) -> ReactionNetwork {
+        let y0 = self.initial_state();
+        let mut network = ReactionNetwork::new(self.name())
+            .species("m", y0[0])
+            .species("p", y0[1]);
+        for parameter in self.parameters() {
+            network = network.parameter_with_bounds(
+                &parameter.name,
+                parameter.value,
+                parameter.min,
+                parameter.max,
+            );
+        }
+        network
+            .reaction("transcription", "=> m @ hill_rep(beta_m, p, k, n)")
+            .and_then(|n| n.reaction("mrna_decay", "m => @ gamma_m"))
+            .and_then(|n| n.reaction("translation", "m => m + p @ beta_p"))
+            .and_then(|n| n.reaction("protein_decay", "p => @ gamma_p"))
+            .expect("valid negative autoregulation reactions")
+    }
+}
+
+impl ode_solvers::System<f64, State> for NegativeAutoregulation {
+    fn system(&self, _t: Time, x: &State, dx: &mut State) {
+        let m = x[0];
+        let p: f64 = x[1];
+        let repression = 1.0 / (1.0 + (p / self.k).powf(self.n)); // repressive hill function
+        dx[0] = self.beta_m * repression - self.gamma_m * m;
+        dx[1] = self.beta_p * m - self.gamma_p * p;
+    }
+}
+
+impl Model for NegativeAutoregulation {
+    fn name(&self) -> &str {
+        "Negative autoregulation"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["m", "p"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("beta_m", self.beta_m, d.beta_m, 0.0, 100.0),
+            Parameter::new("gamma_m", self.gamma_m, d.gamma_m, 0.0, 100.0),
+            Parameter::new("beta_p", self.beta_p, d.beta_p, 0.0, 100.0),
+            Parameter::new("gamma_p", self.gamma_p, d.gamma_p, 0.0, 100.0),
+            Parameter::new("n", self.n, d.n, 0.0, 10.0),
+            Parameter::new("k", self.k, d.k, 0.0, 100.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "beta_m" => Some(&mut self.beta_m),
+            "gamma_m" => Some(&mut self.gamma_m),
+            "beta_p" => Some(&mut self.beta_p),
+            "gamma_p" => Some(&mut self.gamma_p),
+            "n" => Some(&mut self.n),
+            "k" => Some(&mut self.k),
+            _ => None,
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![0.0, 0.0])
+    }
+}
+
+/// Negative autoregulation in which transcription responds to the protein a delay `tau` ago,
+/// the time taken by transcription, translation and maturation.
+///
+/// As a [`Model`] it is the undelayed limit, which has the same steady state.
+#[derive(Copy, Clone, Debug)]
+pub struct DelayedAutoregulation {
+    pub beta_m: f64,
+    pub gamma_m: f64,
+    pub beta_p: f64,
+    pub gamma_p: f64,
+    pub n: f64,
+    pub k: f64,
+    pub tau: f64,
+}
+
+impl Default for DelayedAutoregulation {
+    fn default() -> Self {
+        let NegativeAutoregulation {
+            beta_m,
+            gamma_m,
+            beta_p,
+            gamma_p,
+            n,
+            k,
+        } = NegativeAutoregulation::default();
+        DelayedAutoregulation {
+            beta_m,
+            gamma_m,
+            beta_p,
+            gamma_p,
+            n,
+            k,
+            tau: 10.0,
+        }
+    }
+}
+
+impl DelayedAutoregulation {
+    fn repression(&self, p: f64) -> f64 {
+        1.0 / (1.0 + (p / self.k).powf(self.n))
+    }
+
+    /// Steady-state protein level, found by bisection.
+    pub fn steady_state_protein(&self) -> f64 {
+        let gain = self.beta_m * self.beta_p / (self.gamma_m * self.gamma_p);
+        let (mut low, mut high) = (0.0, gain);
+        for _ in 0..200 {
+            let p = 0.5 * (low + high);
+            if p < gain * self.repression(p) {
+                low = p;
+            } else {
+                high = p;
+            }
+        }
+        0.5 * (low + high)
+    }
+
+    /// Smallest delay at which the steady state loses stability to oscillations, or `None` when
+    /// the feedback is too weak to oscillate at any delay.
+    ///
+    /// Linearizing around the steady state gives the characteristic equation
+    /// `(l + gamma_m)(l + gamma_p) + K exp(-l tau) = 0`; the delay is the first at which a pair of
+    /// roots crosses the imaginary axis.
+    pub fn critical_delay(&self) -> Option<f64> {
+        let p = self.steady_state_protein();
+        let x = (p / self.k).powf(self.n);
+        let slope = self.n * x / p.max(f64::MIN_POSITIVE) / (1.0 + x).powi(2);
+        let gain = self.beta_m * self.beta_p * slope;
+        let (a, b) = (self.gamma_m, self.gamma_p);
+        if gain <= a * b {
+            return None;
+        }
+        let sum = a * a + b * b;
+        let omega2 = 0.5 * (-sum + (sum * sum - 4.0 * (a * a * b * b - gain * gain)).sqrt());
+        let omega = omega2.sqrt();
+        Some((std::f64::consts::PI - (omega / a).atan() - (omega / b).atan()) / omega)
+    }
+}
+
+impl ode_solvers::System<f64, State> for DelayedAutoregulation {
+    fn system(&self, _t: Time, x: &State, dx: &mut State) {
+        dx[0] = self.beta_m * self.repression(x[1]) - self.gamma_m * x[0];
+        dx[1] = self.beta_p * x[0] - self.gamma_p * x[1];
+    }
+}
+
+impl DelaySystem for DelayedAutoregulation {
+    fn delays(&self) -> Vec<f64> {
+        vec![self.tau]
+    }
+
+    fn delayed_system(&self, _t: Time, x: &State, lagged: &[State], dx: &mut State) {
+        dx[0] = self.beta_m * self.repression(lagged[0][1]) - self.gamma_m * x[0];
+        dx[1] = self.beta_p * x[0] - self.gamma_p * x[1];
+    }
+}
+
+impl Model for DelayedAutoregulation {
+    fn name(&self) -> &str {
+        "Delayed negative autoregulation"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["m", "p"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("beta_m", self.beta_m, d.beta_m, 0.0, 100.0),
+            Parameter::new("gamma_m", self.gamma_m, d.gamma_m, 0.0, 100.0),
+            Parameter::new("beta_p", self.beta_p, d.beta_p, 0.0, 100.0),
+            Parameter::new("gamma_p", self.gamma_p, d.gamma_p, 0.0, 100.0),
+            Parameter::new("n", self.n, d.n, 0.0, 10.0),
+            Parameter::new("k", self.k, d.k, 0.0, 100.0),
+            Parameter::new("tau", self.tau, d.tau, 0.0, 1000.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "beta_m" => Some(&mut self.beta_m),
+            "gamma_m" => Some(&mut self.gamma_m),
+            "beta_p" => Some(&mut self.beta_p),
+            "gamma_p" => Some(&mut self.gamma_p),
+            "n" => Some(&mut self.n),
+            "k" => Some(&mut self.k),
+            "tau" => Some(&mut self.tau),
+            _ => None,
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![0.0, 0.0])
+    }
+}
@@ -0,0 +1,298 @@
+use crate::analysis::Response;
+use crate::model::{Error, Model, Parameter, State, Time};
+use crate::regulation::{Hill, Logic, Sign};
+use crate::signal::Signal;
+use crate::stiff::Stiff;
+use crate::trajectory::{uniform, Trajectory};
+
+/// The eight feed-forward loops of Alon, by the signs of the edges `X -> Y`, `X -> Z` and
+/// `Y -> Z`. A loop is coherent when the direct edge has the sign of the indirect path.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FflType {
+    #[default]
+    C1,
+    C2,
+    C3,
+    C4,
+    I1,
+    I2,
+    I3,
+    I4,
+}
+
+impl FflType {
+    pub const ALL: [FflType; 8] = [
+        FflType::C1,
+        FflType::C2,
+        FflType::C3,
+        FflType::C4,
+        FflType::I1,
+        FflType::I2,
+        FflType::I3,
+        FflType::I4,
+    ];
+
+    /// Signs of `X -> Y`, `X -> Z` and `Y -> Z`.
+    pub fn signs(&self) -> [Sign; 3] {
+        use Sign::{Activation as A, Repression as R};
+        match self {
+            FflType::C1 => [A, A, A],
+            FflType::C2 => [R, R, A],
+            FflType::C3 => [A, R, R],
+            FflType::C4 => [R, A, R],
+            FflType::I1 => [A, A, R],
+            FflType::I2 => [R, R, R],
+            FflType::I3 => [A, R, A],
+            FflType::I4 => [R, A, A],
+        }
+    }
+
+    pub fn is_coherent(&self) -> bool {
+        let [xy, xz, yz] = self.signs();
+        xz.value() == xy.value() * yz.value()
+    }
+}
+
+/// Half times of the response of `Z` to `Sx` switching on and off, next to the half time
+/// `ln 2 / alpha_z` of simple regulation by `X` alone.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Delays {
+    pub on: f64,
+    pub off: f64,
+    pub reference: f64,
+}
+
+/// Transient excursion of `Z` after `Sx` switches on: a peak, or a dip when `Z` falls, that
+/// goes further from the initial level than `Z` ends up.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pulse {
+    pub t_peak: f64,
+    pub peak: f64,
+    pub initial: f64,
+    pub steady_state: f64,
+}
+
+impl Pulse {
+    /// Height of the excursion above, or depth below, the initial level.
+    pub fn amplitude(&self) -> f64 {
+        self.peak - self.initial
+    }
+
+    /// Fraction of the excursion that is undone by the steady state: 1 when `Z` returns to
+    /// its initial level, as in perfect adaptation, and 0 without a pulse.
+    pub fn adaptation(&self) -> f64 {
+        (self.peak - self.steady_state) / (self.peak - self.initial)
+    }
+}
+
+/// Changes of `Z` smaller than this fraction of its levels count as no change.
+const NEGLIGIBLE: f64 = 1e-2;
+
+/// Feed-forward loop in which `X` regulates `Y` and both regulate `Z`.
+///
+/// `X` is present at level `x` and active in proportion to the input signal `sx`; `Y` is
+/// active in proportion to `sy`. Each edge is a Hill term with coefficient `n`, and the
+/// promoter of `Z` combines its two terms with `logic`.
+#[derive(Clone, Debug)]
+pub struct FeedForwardLoop {
+    pub kind: FflType,
+    pub logic: Logic,
+    pub x: f64,
+    pub beta_y: f64,
+    pub alpha_y: f64,
+    pub beta_z: f64,
+    pub alpha_z: f64,
+    pub k_xy: f64,
+    pub k_xz: f64,
+    pub k_yz: f64,
+    pub n: f64,
+    pub sx: Signal,
+    pub sy: Signal,
+}
+
+impl Default for FeedForwardLoop {
+    fn default() -> Self {
+        FeedForwardLoop {
+            kind: FflType::C1,
+            logic: Logic::And,
+            x: 1.0,
+            beta_y: 1.0,
+            alpha_y: 1.0,
+            beta_z: 1.0,
+            alpha_z: 1.0,
+            k_xy: 0.1,
+            k_xz: 0.1,
+            k_yz: 0.5,
+            n: 2.0,
+            sx: Signal::step_on(1.0, 1.0),
+            sy: Signal::Constant(1.0),
+        }
+    }
+}
+
+impl FeedForwardLoop {
+    pub fn new(kind: FflType, logic: Logic) -> Self {
+        FeedForwardLoop {
+            kind,
+            logic,
+            ..Default::default()
+        }
+    }
+
+    fn edges(&self) -> [Hill; 3] {
+        let [xy, xz, yz] = self.kind.signs();
+        [
+            Hill::new(xy, self.k_xy, self.n),
+            Hill::new(xz, self.k_xz, self.n),
+            Hill::new(yz, self.k_yz, self.n),
+        ]
+    }
+
+    /// Production rates of `Y` and `Z` with inputs `sx` and `sy` and `Y` at level `y`.
+    fn production(&self, sx: f64, sy: f64, y: f64) -> (f64, f64) {
+        let [xy, xz, yz] = self.edges();
+        let x_active = self.x * sx;
+        let y_active = y * sy;
+        (
+            self.beta_y * xy.value(x_active),
+            self.beta_z
+                * self
+                    .logic
+                    .combine(&[xz.value(x_active), yz.value(y_active)]),
+        )
+    }
+
+    /// Steady state of `Y` and `Z` with constant inputs `sx` and `sy`.
+    pub fn steady_state(&self, sx: f64, sy: f64) -> State {
+        let (production_y, _) = self.production(sx, sy, 0.0);
+        let y = production_y / self.alpha_y;
+        let (_, production_z) = self.production(sx, sy, y);
+        State::from_vec(vec![y, production_z / self.alpha_z])
+    }
+
+    /// Response from the steady state of `Sx` off (or on) to `Sx` switching on (or off) at
+    /// `t = 0`, with `Sy` present, up to `t_end`.
+    pub fn sx_step(&self, on: bool, t_end: f64) -> Result<Trajectory, Error> {
+        let (before, after) = if on { (0.0, 1.0) } else { (1.0, 0.0) };
+        let circuit = FeedForwardLoop {
+            sx: Signal::Constant(after),
+            sy: Signal::Constant(1.0),
+            ..self.clone()
+        };
+        let y0 = self.steady_state(before, 1.0);
+        let mut stepper = Stiff::new(circuit, 0.0, t_end, 0.0, y0, 1e-8, 1e-8);
+        stepper.integrate()?;
+        Ok(stepper.trajectory().clone())
+    }
+
+    /// `Z` sampled from the response to an `Sx` step over ten lifetimes of the slower protein.
+    fn z_response(&self, on: bool) -> Result<(Vec<Time>, Vec<f64>), Error> {
+        let t_end = 10.0 / self.alpha_y.min(self.alpha_z);
+        let times = uniform(0.0, t_end, 4001);
+        let z = self
+            .sx_step(on, t_end)?
+            .sample(&times)
+            .iter()
+            .map(|y| y[1])
+            .collect();
+        Ok((times, z))
+    }
+
+    /// Half times of `Z` after `Sx` switches on and off. A delay longer than the reference in
+    /// one direction only is the sign-sensitive delay of the coherent loops. The half time is
+    /// `NaN` when the net change of `Z` is negligible next to its levels, as when a pulse
+    /// returns to where it started.
+    pub fn delays(&self) -> Result<Delays, Error> {
+        let half_time = |on| -> Result<f64, Error> {
+            let (t, z) = self.z_response(on)?;
+            let (initial, last) = (z[0], z[z.len() - 1]);
+            let scale = z.iter().fold(0.0_f64, |m, zi| m.max((zi - initial).abs()));
+            if (last - initial).abs() <= NEGLIGIBLE * scale.max(initial.abs()) {
+                return Ok(f64::NAN);
+            }
+            Ok(Response::new(&t, &z).map_or(f64::NAN, |r| r.rise_time))
+        };
+        Ok(Delays {
+            on: half_time(true)?,
+            off: half_time(false)?,
+            reference: std::f64::consts::LN_2 / self.alpha_z,
+        })
+    }
+
+    /// Largest excursion of `Z` from its initial level after `Sx` switches on, when more than
+    /// `threshold` of it is undone by the steady state, as in the incoherent loops.
+    pub fn pulse(&self, threshold: f64) -> Result<Option<Pulse>, Error> {
+        let (t, z) = self.z_response(true)?;
+        let (initial, steady_state) = (z[0], z[z.len() - 1]);
+        let (i, peak) = z
+            .iter()
+            .enumerate()
+            .max_by(|a, b| (a.1 - initial).abs().total_cmp(&(b.1 - initial).abs()))
+            .map(|(i, z)| (i, *z))
+            .expect("samples of the response");
+        if (peak - initial).abs() <= NEGLIGIBLE * initial.abs() {
+            return Ok(None);
+        }
+        let pulse = Pulse {
+            t_peak: t[i],
+            peak,
+            initial,
+            steady_state,
+        };
+        Ok((pulse.adaptation() > threshold).then_some(pulse))
+    }
+}
+
+impl ode_solvers::System<f64, State> for FeedForwardLoop {
+    fn system(&self, t: Time, y: &State, dy: &mut State) {
+        let (production_y, production_z) =
+            self.production(self.sx.value(t), self.sy.value(t), y[0]);
+        dy[0] = production_y - self.alpha_y * y[0];
+        dy[1] = production_z - self.alpha_z * y[1];
+    }
+}
+
+impl Model for FeedForwardLoop {
+    fn name(&self) -> &str {
+        "Feed-forward loop"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["Y", "Z"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("x", self.x, d.x, 0.0, 100.0),
+            Parameter::new("beta_y", self.beta_y, d.beta_y, 0.0, 100.0),
+            Parameter::new("alpha_y", self.alpha_y, d.alpha_y, 0.0, 100.0),
+            Parameter::new("beta_z", self.beta_z, d.beta_z, 0.0, 100.0),
+            Parameter::new("alpha_z", self.alpha_z, d.alpha_z, 0.0, 100.0),
+            Parameter::new("k_xy", self.k_xy, d.k_xy, 0.0, 100.0),
+            Parameter::new("k_xz", self.k_xz, d.k_xz, 0.0, 100.0),
+            Parameter::new("k_yz", self.k_yz, d.k_yz, 0.0, 100.0),
+            Parameter::new("n", self.n, d.n, 0.0, 10.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "x" => Some(&mut self.x),
+            "beta_y" => Some(&mut self.beta_y),
+            "alpha_y" => Some(&mut self.alpha_y),
+            "beta_z" => Some(&mut self.beta_z),
+            "alpha_z" => Some(&mut self.alpha_z),
+            "k_xy" => Some(&mut self.k_xy),
+            "k_xz" => Some(&mut self.k_xz),
+            "k_yz" => Some(&mut self.k_yz),
+            "n" => Some(&mut self.n),
+            _ => None,
+        }
+    }
+
+    /// Steady state for the inputs at `t = 0`.
+    fn initial_state(&self) -> State {
+        self.steady_state(self.sx.value(0.0), self.sy.value(0.0))
+    }
+}
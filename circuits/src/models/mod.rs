@@ -0,0 +1,29 @@
+//! The circuits of the chapters, with the parameters of the papers they come from.
+//!
+//! Gene expression and its autoregulation ([`Unregulated`], [`NegativeAutoregulation`],
+//! [`Autorepressor`], [`DelayedAutoregulation`]), the feed-forward loops of Alon
+//! ([`FeedForwardLoop`]), Gardner's [`ToggleSwitch`], Elowitz's [`Repressilator`], the
+//! [`Goodwin`] oscillator, the activator-repressor clock of Vilar et al.
+//! ([`ActivatorRepressor`]), the [`Sir`] epidemic and the [`CoffeeRust`] epidemic of coffee
+//! trees.
+
+mod autorepressor;
+pub use autorepressor::{autorepressive, unregulated, Autorepressor};
+
+mod expression;
+pub use expression::{DelayedAutoregulation, NegativeAutoregulation, Unregulated};
+
+mod sir;
+pub use sir::Sir;
+
+mod coffee_rust;
+pub use coffee_rust::CoffeeRust;
+
+mod toggle_switch;
+pub use toggle_switch::ToggleSwitch;
+
+mod oscillators;
+pub use oscillators::{ActivatorRepressor, Goodwin, Repressilator};
+
+mod feed_forward;
+pub use feed_forward::{Delays, FeedForwardLoop, FflType, Pulse};
@@ -0,0 +1,300 @@
+use crate::model::{Model, Parameter, State, Time};
+
+/// Elowitz and Leibler's repressilator: LacI represses TetR, TetR represses cI and cI represses
+/// LacI, in the dimensionless units of the original paper.
+///
+/// mRNA is scaled by the translation efficiency and time by the mRNA lifetime. The defaults
+/// (`alpha = 216`, `alpha0 = 0.216`, `beta = 0.2`, `n = 2`) are those of Elowitz and Leibler
+/// (2000) and give sustained oscillations.
+#[derive(Copy, Clone, Debug)]
+pub struct Repressilator {
+    /// Transcription from a free promoter, in proteins per mRNA lifetime.
+    pub alpha: f64,
+    /// Leaky transcription from a repressed promoter.
+    pub alpha0: f64,
+    /// Ratio of the protein to the mRNA decay rate.
+    pub beta: f64,
+    /// Hill coefficient of repression.
+    pub n: f64,
+}
+
+impl Default for Repressilator {
+    fn default() -> Self {
+        Repressilator {
+            alpha: 216.0,
+            alpha0: 0.216,
+            beta: 0.2,
+            n: 2.0,
+        }
+    }
+}
+
+impl ode_solvers::System<f64, State> for Repressilator {
+    fn system(&self, _t: Time, x: &State, dx: &mut State) {
+        for i in 0..3 {
+            // Gene i is repressed by the protein of gene i - 1.
+            let repressor = x[3 + (i + 2) % 3].max(0.0);
+            dx[i] = -x[i] + self.alpha / (1.0 + repressor.powf(self.n)) + self.alpha0;
+            dx[3 + i] = -self.beta * (x[3 + i] - x[i]);
+        }
+    }
+}
+
+impl Model for Repressilator {
+    fn name(&self) -> &str {
+        "Repressilator"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["m_lacI", "m_tetR", "m_cI", "lacI", "tetR", "cI"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("alpha", self.alpha, d.alpha, 0.0, 10000.0),
+            Parameter::new("alpha0", self.alpha0, d.alpha0, 0.0, 100.0),
+            Parameter::new("beta", self.beta, d.beta, 0.0, 100.0),
+            Parameter::new("n", self.n, d.n, 0.0, 10.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "alpha" => Some(&mut self.alpha),
+            "alpha0" => Some(&mut self.alpha0),
+            "beta" => Some(&mut self.beta),
+            "n" => Some(&mut self.n),
+            _ => None,
+        }
+    }
+
+    /// A little LacI mRNA, so the symmetric steady state is left at once.
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![1.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+    }
+}
+
+/// Goodwin's oscillator: an mRNA `x` is translated into an enzyme `y`, which makes a product
+/// `z` that represses transcription of `x`.
+///
+/// With equal decay rates the loop only oscillates for Hill coefficients above 8, the secant
+/// condition. The defaults follow Gonze and Abou-Jaoudé (2013): unit synthesis rates, decay
+/// rates of 0.1 and `n = 10`.
+#[derive(Copy, Clone, Debug)]
+pub struct Goodwin {
+    pub k1: f64,
+    pub k2: f64,
+    pub k3: f64,
+    pub k4: f64,
+    pub k5: f64,
+    pub k6: f64,
+    /// Repression threshold of `z`.
+    pub ki: f64,
+    pub n: f64,
+}
+
+impl Default for Goodwin {
+    fn default() -> Self {
+        Goodwin {
+            k1: 1.0,
+            k2: 0.1,
+            k3: 1.0,
+            k4: 0.1,
+            k5: 1.0,
+            k6: 0.1,
+            ki: 1.0,
+            n: 10.0,
+        }
+    }
+}
+
+impl ode_solvers::System<f64, State> for Goodwin {
+    fn system(&self, _t: Time, x: &State, dx: &mut State) {
+        let (m, e, p) = (x[0], x[1], x[2]);
+        dx[0] = self.k1 / (1.0 + (p.max(0.0) / self.ki).powf(self.n)) - self.k2 * m;
+        dx[1] = self.k3 * m - self.k4 * e;
+        dx[2] = self.k5 * e - self.k6 * p;
+    }
+}
+
+impl Model for Goodwin {
+    fn name(&self) -> &str {
+        "Goodwin oscillator"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["x", "y", "z"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("k1", self.k1, d.k1, 0.0, 100.0),
+            Parameter::new("k2", self.k2, d.k2, 0.0, 100.0),
+            Parameter::new("k3", self.k3, d.k3, 0.0, 100.0),
+            Parameter::new("k4", self.k4, d.k4, 0.0, 100.0),
+            Parameter::new("k5", self.k5, d.k5, 0.0, 100.0),
+            Parameter::new("k6", self.k6, d.k6, 0.0, 100.0),
+            Parameter::new("ki", self.ki, d.ki, 0.0, 100.0),
+            Parameter::new("n", self.n, d.n, 0.0, 20.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "k1" => Some(&mut self.k1),
+            "k2" => Some(&mut self.k2),
+            "k3" => Some(&mut self.k3),
+            "k4" => Some(&mut self.k4),
+            "k5" => Some(&mut self.k5),
+            "k6" => Some(&mut self.k6),
+            "ki" => Some(&mut self.ki),
+            "n" => Some(&mut self.n),
+            _ => None,
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![0.0, 0.0, 0.0])
+    }
+}
+
+/// Vilar, Kueh, Barkai and Leibler's activator–repressor clock. The activator `A` binds its own
+/// promoter and that of the repressor `R`, and `R` sequesters `A` into an inactive complex `C`.
+///
+/// The genes `D_A`, `D_R` switch to the active forms `D'_A`, `D'_R` when `A` binds. The
+/// defaults are those of Vilar et al. (2002), which oscillate with a period of about a day.
+#[derive(Copy, Clone, Debug)]
+pub struct ActivatorRepressor {
+    pub alpha_a: f64,
+    /// Transcription of the activator from its activated promoter.
+    pub alpha_a_active: f64,
+    pub alpha_r: f64,
+    /// Transcription of the repressor from its activated promoter.
+    pub alpha_r_active: f64,
+    pub beta_a: f64,
+    pub beta_r: f64,
+    pub delta_ma: f64,
+    pub delta_mr: f64,
+    pub delta_a: f64,
+    pub delta_r: f64,
+    pub gamma_a: f64,
+    pub gamma_r: f64,
+    pub gamma_c: f64,
+    pub theta_a: f64,
+    pub theta_r: f64,
+}
+
+impl Default for ActivatorRepressor {
+    fn default() -> Self {
+        ActivatorRepressor {
+            alpha_a: 50.0,
+            alpha_a_active: 500.0,
+            alpha_r: 0.01,
+            alpha_r_active: 50.0,
+            beta_a: 50.0,
+            beta_r: 5.0,
+            delta_ma: 10.0,
+            delta_mr: 0.5,
+            delta_a: 1.0,
+            delta_r: 0.2,
+            gamma_a: 1.0,
+            gamma_r: 1.0,
+            gamma_c: 2.0,
+            theta_a: 50.0,
+            theta_r: 100.0,
+        }
+    }
+}
+
+impl ode_solvers::System<f64, State> for ActivatorRepressor {
+    fn system(&self, _t: Time, x: &State, dx: &mut State) {
+        let (d_a, d_a1, d_r, d_r1) = (x[0], x[1], x[2], x[3]);
+        let (m_a, a, m_r, r, c) = (x[4], x[5], x[6], x[7], x[8]);
+        let binding_a = self.gamma_a * d_a * a;
+        let binding_r = self.gamma_r * d_r * a;
+        dx[0] = self.theta_a * d_a1 - binding_a;
+        dx[1] = binding_a - self.theta_a * d_a1;
+        dx[2] = self.theta_r * d_r1 - binding_r;
+        dx[3] = binding_r - self.theta_r * d_r1;
+        dx[4] = self.alpha_a_active * d_a1 + self.alpha_a * d_a - self.delta_ma * m_a;
+        dx[5] = self.beta_a * m_a + self.theta_a * d_a1 + self.theta_r * d_r1
+            - binding_a
+            - binding_r
+            - self.gamma_c * a * r
+            - self.delta_a * a;
+        dx[6] = self.alpha_r_active * d_r1 + self.alpha_r * d_r - self.delta_mr * m_r;
+        dx[7] = self.beta_r * m_r - self.gamma_c * a * r + self.delta_a * c - self.delta_r * r;
+        dx[8] = self.gamma_c * a * r - self.delta_a * c;
+    }
+}
+
+impl Model for ActivatorRepressor {
+    fn name(&self) -> &str {
+        "Activator-repressor oscillator"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["D_A", "D'_A", "D_R", "D'_R", "M_A", "A", "M_R", "R", "C"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("alpha_a", self.alpha_a, d.alpha_a, 0.0, 1000.0),
+            Parameter::new(
+                "alpha_a_active",
+                self.alpha_a_active,
+                d.alpha_a_active,
+                0.0,
+                10000.0,
+            ),
+            Parameter::new("alpha_r", self.alpha_r, d.alpha_r, 0.0, 1000.0),
+            Parameter::new(
+                "alpha_r_active",
+                self.alpha_r_active,
+                d.alpha_r_active,
+                0.0,
+                10000.0,
+            ),
+            Parameter::new("beta_a", self.beta_a, d.beta_a, 0.0, 1000.0),
+            Parameter::new("beta_r", self.beta_r, d.beta_r, 0.0, 1000.0),
+            Parameter::new("delta_ma", self.delta_ma, d.delta_ma, 0.0, 100.0),
+            Parameter::new("delta_mr", self.delta_mr, d.delta_mr, 0.0, 100.0),
+            Parameter::new("delta_a", self.delta_a, d.delta_a, 0.0, 100.0),
+            Parameter::new("delta_r", self.delta_r, d.delta_r, 0.0, 100.0),
+            Parameter::new("gamma_a", self.gamma_a, d.gamma_a, 0.0, 100.0),
+            Parameter::new("gamma_r", self.gamma_r, d.gamma_r, 0.0, 100.0),
+            Parameter::new("gamma_c", self.gamma_c, d.gamma_c, 0.0, 100.0),
+            Parameter::new("theta_a", self.theta_a, d.theta_a, 0.0, 1000.0),
+            Parameter::new("theta_r", self.theta_r, d.theta_r, 0.0, 1000.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "alpha_a" => Some(&mut self.alpha_a),
+            "alpha_a_active" => Some(&mut self.alpha_a_active),
+            "alpha_r" => Some(&mut self.alpha_r),
+            "alpha_r_active" => Some(&mut self.alpha_r_active),
+            "beta_a" => Some(&mut self.beta_a),
+            "beta_r" => Some(&mut self.beta_r),
+            "delta_ma" => Some(&mut self.delta_ma),
+            "delta_mr" => Some(&mut self.delta_mr),
+            "delta_a" => Some(&mut self.delta_a),
+            "delta_r" => Some(&mut self.delta_r),
+            "gamma_a" => Some(&mut self.gamma_a),
+            "gamma_r" => Some(&mut self.gamma_r),
+            "gamma_c" => Some(&mut self.gamma_c),
+            "theta_a" => Some(&mut self.theta_a),
+            "theta_r" => Some(&mut self.theta_r),
+            _ => None,
+        }
+    }
+
+    /// One free copy of each gene and no transcripts or proteins.
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+    }
+}
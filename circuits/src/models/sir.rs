@@ -0,0 +1,75 @@
+use crate::model::{Model, Parameter, State, Time};
+use crate::reactions::ReactionNetwork;
+
+/// Susceptible, infected and recovered populations with mass-action infection.
+#[derive(Copy, Clone, Debug)]
+pub struct Sir {
+    pub beta: f64,
+    pub gamma: f64,
+}
+
+impl Default for Sir {
+    fn default() -> Self {
+        Sir {
+            beta: 0.1 / 1000.0,
+            gamma: 0.01,
+        }
+    }
+}
+
+impl Sir {
+    /// The same model as a reaction network, for deterministic and exact stochastic simulation.
+    pub fn network(&self) -> ReactionNetwork {
+        let y0 = self.initial_state();
+        ReactionNetwork::new("SIR")
+            .species("S", y0[0])
+            .species("I", y0[1])
+            .species("R", y0[2])
+            .parameter_with_bounds("beta", self.beta, 0.0, 1.0)
+            .parameter_with_bounds("gamma", self.gamma, 0.0, 1.0)
+            .reaction("infection", "S + I => 2 I @ beta")
+            .and_then(|n| n.reaction("healing", "I => R @ gamma"))
+            .expect("valid SIR reactions")
+    }
+}
+
+impl ode_solvers::System<f64, State> for Sir {
+    fn system(&self, _t: Time, x: &State, dx: &mut State) {
+        let s = x[0];
+        let i = x[1];
+
+        dx[0] = -self.beta * s * i;
+        dx[1] = self.beta * s * i - self.gamma * i;
+        dx[2] = self.gamma * i;
+    }
+}
+
+impl Model for Sir {
+    fn name(&self) -> &str {
+        "SIR"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["S", "I", "R"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("beta", self.beta, d.beta, 0.0, 1.0),
+            Parameter::new("gamma", self.gamma, d.gamma, 0.0, 1.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "beta" => Some(&mut self.beta),
+            "gamma" => Some(&mut self.gamma),
+            _ => None,
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![999.0, 1.0, 0.0])
+    }
+}
@@ -0,0 +1,112 @@
+use crate::model::{Model, Parameter, State, Time};
+use crate::stability::{fixed_points, FixedPoint};
+
+/// Gardner, Cantor and Collins' genetic toggle switch: two repressors `u` (LacI) and `v`
+/// (TetR/cI) that repress each other, in dimensionless units.
+///
+/// IPTG binds LacI, so the repression of `v` sees `u / (1 + iptg / k)^eta`. The defaults are
+/// the fit of Gardner et al. (2000) to the pTAK117 toggle, which is bistable without inducer.
+#[derive(Copy, Clone, Debug)]
+pub struct ToggleSwitch {
+    /// Effective synthesis rate of `u`.
+    pub alpha1: f64,
+    /// Effective synthesis rate of `v`.
+    pub alpha2: f64,
+    /// Cooperativity of the repression of `u` by `v`.
+    pub beta: f64,
+    /// Cooperativity of the repression of `v` by `u`.
+    pub gamma: f64,
+    /// Inducer concentration in M.
+    pub iptg: f64,
+    /// Dissociation constant of IPTG from LacI in M.
+    pub k: f64,
+    /// Cooperativity of IPTG binding.
+    pub eta: f64,
+}
+
+impl Default for ToggleSwitch {
+    fn default() -> Self {
+        ToggleSwitch {
+            alpha1: 156.25,
+            alpha2: 15.6,
+            beta: 2.5,
+            gamma: 1.0,
+            iptg: 0.0,
+            k: 2.9618e-5,
+            eta: 2.0015,
+        }
+    }
+}
+
+impl ToggleSwitch {
+    /// Fixed points found from a grid of guesses over the reachable range of both repressors.
+    pub fn steady_states(&self) -> Vec<FixedPoint> {
+        let levels = |top: f64| [0.0, 0.01, 0.1, 0.5, 1.0].map(|f| f * top);
+        let guesses: Vec<State> = levels(self.alpha1)
+            .iter()
+            .flat_map(|u| levels(self.alpha2).map(|v| State::from_vec(vec![*u, v])))
+            .collect();
+        let mut points = fixed_points(self, &guesses);
+        points.sort_by(|a, b| a.state[0].total_cmp(&b.state[0]));
+        points
+    }
+
+    /// Whether two stable states coexist.
+    pub fn is_bistable(&self) -> bool {
+        self.steady_states()
+            .iter()
+            .filter(|p| p.stability.is_stable())
+            .count()
+            >= 2
+    }
+}
+
+impl ode_solvers::System<f64, State> for ToggleSwitch {
+    fn system(&self, _t: Time, x: &State, dx: &mut State) {
+        let (u, v) = (x[0], x[1]);
+        let free = u / (1.0 + self.iptg / self.k).powf(self.eta);
+        dx[0] = self.alpha1 / (1.0 + v.max(0.0).powf(self.beta)) - u;
+        dx[1] = self.alpha2 / (1.0 + free.max(0.0).powf(self.gamma)) - v;
+    }
+}
+
+impl Model for ToggleSwitch {
+    fn name(&self) -> &str {
+        "Toggle switch"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["u", "v"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        let d = Self::default();
+        vec![
+            Parameter::new("alpha1", self.alpha1, d.alpha1, 0.0, 1000.0),
+            Parameter::new("alpha2", self.alpha2, d.alpha2, 0.0, 1000.0),
+            Parameter::new("beta", self.beta, d.beta, 0.0, 10.0),
+            Parameter::new("gamma", self.gamma, d.gamma, 0.0, 10.0),
+            Parameter::new("iptg", self.iptg, d.iptg, 0.0, 1e-2),
+            Parameter::new("k", self.k, d.k, 1e-9, 1e-2),
+            Parameter::new("eta", self.eta, d.eta, 0.0, 10.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "alpha1" => Some(&mut self.alpha1),
+            "alpha2" => Some(&mut self.alpha2),
+            "beta" => Some(&mut self.beta),
+            "gamma" => Some(&mut self.gamma),
+            "iptg" => Some(&mut self.iptg),
+            "k" => Some(&mut self.k),
+            "eta" => Some(&mut self.eta),
+            _ => None,
+        }
+    }
+
+    /// LacI high, the state the switch is kept in before induction.
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![self.alpha1, 0.0])
+    }
+}
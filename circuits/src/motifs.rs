@@ -0,0 +1,680 @@
+//! Network motifs of transcription networks.
+//!
+//! A [`TranscriptionNetwork`] is a directed graph of genes whose edges carry the sign of the
+//! regulation, read from an edge list or GraphML. [`TranscriptionNetwork::census`] counts the
+//! connected induced subgraphs of three or four genes by isomorphism class, and [`Motifs`]
+//! compares those counts with networks randomized by degree-preserving edge switches, after
+//! Milo et al. (2002). Classes whose Z-score is large, such as the feed-forward loop and the
+//! bi-fan in E. coli, are the network motifs.
+//!
+//! Edge lists have one `regulator,target,sign` line per edge, with the sign as `+`, `-` and `+-`
+//! or Alon's `1`, `2` and `3`. [`TranscriptionNetwork::ffl_types`] splits the feed-forward loops
+//! by the signs of their edges and [`TranscriptionNetwork::single_input_modules`] lists the
+//! regulators whose targets have no other regulator.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use rand::Rng;
+use roxmltree::Document;
+
+use crate::model::Error;
+use crate::models::FflType;
+use crate::regulation::Sign;
+
+/// Sign of an edge, `None` for dual or unknown regulation.
+pub type Interaction = Option<Sign>;
+
+/// Reads a sign as written in edge lists: `+`, `1` or `activation`; `-`, `2`, `-1` or
+/// `repression`; `+-`, `3` or `dual`, as in the files of Alon's lab.
+pub fn parse_interaction(token: &str) -> Result<Interaction, Error> {
+    match token.trim().to_ascii_lowercase().as_str() {
+        "+" | "1" | "a" | "activation" | "activator" => Ok(Some(Sign::Activation)),
+        "-" | "2" | "-1" | "r" | "repression" | "repressor" => Ok(Some(Sign::Repression)),
+        "+-" | "-+" | "3" | "0" | "d" | "dual" | "unknown" | "?" | "" => Ok(None),
+        _ => Err(Error::Parse(format!("invalid interaction `{}`", token))),
+    }
+}
+
+/// Directed network of transcriptional regulation.
+#[derive(Clone, Debug, Default)]
+pub struct TranscriptionNetwork {
+    graph: DiGraph<String, Interaction>,
+    genes: HashMap<String, NodeIndex>,
+}
+
+impl TranscriptionNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index of the gene `name`, added when it is new.
+    pub fn add_gene(&mut self, name: &str) -> NodeIndex {
+        if let Some(i) = self.genes.get(name) {
+            return *i;
+        }
+        let i = self.graph.add_node(name.to_string());
+        self.genes.insert(name.to_string(), i);
+        i
+    }
+
+    /// Adds regulation of `target` by `regulator`; a repeated edge keeps its first sign.
+    pub fn add_edge(&mut self, regulator: &str, target: &str, interaction: Interaction) {
+        let a = self.add_gene(regulator);
+        let b = self.add_gene(target);
+        if self.graph.find_edge(a, b).is_none() {
+            self.graph.add_edge(a, b, interaction);
+        }
+    }
+
+    pub fn gene(mut self, name: &str) -> Self {
+        self.add_gene(name);
+        self
+    }
+
+    pub fn edge(mut self, regulator: &str, target: &str, interaction: Interaction) -> Self {
+        self.add_edge(regulator, target, interaction);
+        self
+    }
+
+    pub fn graph(&self) -> &DiGraph<String, Interaction> {
+        &self.graph
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Edge list of `regulator,target[,sign]` lines, separated by commas, tabs or spaces. A
+    /// first line of column names such as `regulator,target,sign`, or whose sign does not
+    /// parse, is taken as a header; `#` starts a comment.
+    pub fn from_edge_list_str(text: &str) -> Result<Self, Error> {
+        let mut network = TranscriptionNetwork::new();
+        let mut first = true;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = if line.contains(',') {
+                line.split(',').map(str::trim).collect()
+            } else {
+                line.split_whitespace().collect()
+            };
+            let header = std::mem::take(&mut first);
+            if header && fields.iter().all(|f| is_column_name(f)) {
+                continue;
+            }
+            let interaction = match fields.len() {
+                2 => Ok(None),
+                n if n > 2 => parse_interaction(fields[2]),
+                _ => Err(Error::Parse(format!("edge without target `{}`", line))),
+            };
+            match interaction {
+                Ok(interaction) => network.add_edge(fields[0], fields[1], interaction),
+                Err(_) if header && fields.len() > 2 => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(network)
+    }
+
+    pub fn from_edge_list<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|e| Error::Io(e.to_string()))?;
+        TranscriptionNetwork::from_edge_list_str(&text)
+    }
+
+    /// GraphML with the sign of an edge in a `<data>` element whose key is named `sign`,
+    /// `interaction`, `regulation`, `type` or `mode`. Genes are named by a `name` or `label`
+    /// key when present, and by their id otherwise.
+    pub fn from_graphml_str(xml: &str) -> Result<Self, Error> {
+        let doc = Document::parse(xml).map_err(|e| Error::Parse(e.to_string()))?;
+        let elements = |name: &'static str| {
+            doc.descendants()
+                .filter(move |n| n.is_element() && n.tag_name().name() == name)
+        };
+        let key = |domain: &str, names: &[&str]| {
+            elements("key")
+                .filter(|k| matches!(k.attribute("for"), Some(f) if f == domain || f == "all"))
+                .find(|k| {
+                    k.attribute("attr.name")
+                        .is_some_and(|a| names.contains(&a.to_ascii_lowercase().as_str()))
+                })
+                .and_then(|k| k.attribute("id"))
+        };
+        let name_key = key("node", &["name", "label"]);
+        let sign_key = key(
+            "edge",
+            &["sign", "interaction", "regulation", "type", "mode"],
+        );
+        let mut network = TranscriptionNetwork::new();
+        let mut names = HashMap::new();
+        for node in elements("node") {
+            let id = node
+                .attribute("id")
+                .ok_or_else(|| Error::Parse("<node> without id".to_string()))?;
+            let name = data(node, name_key).map_or(id, str::trim);
+            network.add_gene(name);
+            names.insert(id, name);
+        }
+        for edge in elements("edge") {
+            let end = |attribute| {
+                edge.attribute(attribute)
+                    .map(|id| *names.get(id).unwrap_or(&id))
+                    .ok_or_else(|| Error::Parse(format!("<edge> without {}", attribute)))
+            };
+            let interaction = match data(edge, sign_key) {
+                Some(sign) => parse_interaction(sign)?,
+                None => None,
+            };
+            network.add_edge(end("source")?, end("target")?, interaction);
+        }
+        Ok(network)
+    }
+
+    pub fn from_graphml<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let xml = std::fs::read_to_string(path).map_err(|e| Error::Io(e.to_string()))?;
+        TranscriptionNetwork::from_graphml_str(&xml)
+    }
+
+    fn adjacency(&self) -> Adjacency {
+        Adjacency::new(&self.graph)
+    }
+
+    /// Number of connected induced subgraphs of `size` genes, 3 or 4, per isomorphism class.
+    /// Self-regulation is left out.
+    pub fn census(&self, size: usize) -> Result<BTreeMap<Subgraph, usize>, Error> {
+        check_size(size)?;
+        Ok(self.adjacency().census(size, &Classes::new(size)))
+    }
+
+    /// Number of feed-forward loops of each sign type; loops with a dual or unknown edge are
+    /// not counted.
+    pub fn ffl_types(&self) -> Vec<(FflType, usize)> {
+        let adjacency = self.adjacency();
+        let mut counts = vec![0; FflType::ALL.len()];
+        let interaction = |a: usize, b: usize| {
+            let edge = self
+                .graph
+                .find_edge(NodeIndex::new(a), NodeIndex::new(b))
+                .expect("edge of the loop");
+            self.graph[edge]
+        };
+        adjacency.subgraphs(3, |nodes| {
+            let degree = |i: usize, outgoing: bool| {
+                nodes
+                    .iter()
+                    .filter(|j| {
+                        let (a, b) = if outgoing {
+                            (nodes[i], **j)
+                        } else {
+                            (**j, nodes[i])
+                        };
+                        a != b && adjacency.has_edge(a, b)
+                    })
+                    .count()
+            };
+            let edges: usize = (0..3).map(|i| degree(i, true)).sum();
+            if edges != 3 {
+                return;
+            }
+            let x = (0..3).find(|i| degree(*i, true) == 2);
+            let z = (0..3).find(|i| degree(*i, false) == 2);
+            let (Some(x), Some(z)) = (x, z) else {
+                return;
+            };
+            // Three edges with a node of out-degree two and one of in-degree two: not a cycle.
+            let y = 3 - x - z;
+            let (x, y, z) = (nodes[x], nodes[y], nodes[z]);
+            if let (Some(xy), Some(xz), Some(yz)) =
+                (interaction(x, y), interaction(x, z), interaction(y, z))
+            {
+                if let Some(i) = FflType::ALL.iter().position(|t| t.signs() == [xy, xz, yz]) {
+                    counts[i] += 1;
+                }
+            }
+        });
+        FflType::ALL.into_iter().zip(counts).collect()
+    }
+
+    /// Single-input modules: regulators with at least `min_targets` targets that have no other
+    /// regulator and are all regulated with the same sign.
+    pub fn single_input_modules(&self, min_targets: usize) -> Vec<(String, Vec<String>)> {
+        let mut modules = Vec::new();
+        for regulator in self.graph.node_indices() {
+            let mut targets = Vec::new();
+            let mut signs = HashSet::new();
+            for edge in self.graph.edges_directed(regulator, Direction::Outgoing) {
+                let target = edge.target();
+                let only_input = target != regulator
+                    && self
+                        .graph
+                        .neighbors_directed(target, Direction::Incoming)
+                        .all(|r| r == regulator || r == target);
+                if only_input {
+                    targets.push(self.graph[target].clone());
+                    signs.insert(*edge.weight());
+                }
+            }
+            if targets.len() >= min_targets && signs.len() == 1 {
+                targets.sort();
+                modules.push((self.graph[regulator].clone(), targets));
+            }
+        }
+        modules.sort();
+        modules
+    }
+
+    /// Copy in which pairs of edges `a -> b`, `c -> d` are switched to `a -> d`, `c -> b`
+    /// about `swaps_per_edge` times per edge, keeping the in- and out-degree of every gene.
+    /// Switches that would create a self-loop or a repeated edge are rejected; edges keep
+    /// their signs and self-loops stay in place.
+    pub fn randomized<R: Rng>(&self, swaps_per_edge: usize, rng: &mut R) -> Self {
+        let mut edges: Vec<(usize, usize, Interaction)> = Vec::new();
+        let mut loops = Vec::new();
+        for edge in self.graph.edge_references() {
+            let (a, b) = (edge.source().index(), edge.target().index());
+            if a == b {
+                loops.push((a, b, *edge.weight()));
+            } else {
+                edges.push((a, b, *edge.weight()));
+            }
+        }
+        let mut present: HashSet<(usize, usize)> = edges.iter().map(|(a, b, _)| (*a, *b)).collect();
+        if edges.len() > 1 {
+            for _ in 0..swaps_per_edge * edges.len() {
+                let i = rng.random_range(0..edges.len());
+                let j = rng.random_range(0..edges.len());
+                let (a, b, sign_ab) = edges[i];
+                let (c, d, sign_cd) = edges[j];
+                if a == c || b == d || a == d || c == b {
+                    continue;
+                }
+                if present.contains(&(a, d)) || present.contains(&(c, b)) {
+                    continue;
+                }
+                present.remove(&(a, b));
+                present.remove(&(c, d));
+                present.insert((a, d));
+                present.insert((c, b));
+                edges[i] = (a, d, sign_ab);
+                edges[j] = (c, b, sign_cd);
+            }
+        }
+        let mut graph = self.graph.clone();
+        graph.clear_edges();
+        for (a, b, interaction) in edges.into_iter().chain(loops) {
+            graph.add_edge(NodeIndex::new(a), NodeIndex::new(b), interaction);
+        }
+        TranscriptionNetwork {
+            graph,
+            genes: self.genes.clone(),
+        }
+    }
+}
+
+/// Text of the `<data>` child of `node` with `key`.
+fn data<'a>(node: roxmltree::Node<'a, '_>, key: Option<&str>) -> Option<&'a str> {
+    let key = key?;
+    node.children()
+        .find(|d| d.is_element() && d.attribute("key") == Some(key))
+        .and_then(|d| d.text())
+}
+
+/// Whether `field` names a column of an edge list rather than a gene.
+fn is_column_name(field: &str) -> bool {
+    matches!(
+        field.trim_matches('"').to_ascii_lowercase().as_str(),
+        "regulator"
+            | "source"
+            | "from"
+            | "tf"
+            | "target"
+            | "to"
+            | "gene"
+            | "sign"
+            | "interaction"
+            | "regulation"
+            | "type"
+            | "mode"
+    )
+}
+
+fn check_size(size: usize) -> Result<(), Error> {
+    if (3..=4).contains(&size) {
+        Ok(())
+    } else {
+        Err(Error::InvalidArgument(format!(
+            "subgraphs of {} genes, only 3 or 4 are counted",
+            size
+        )))
+    }
+}
+
+/// Isomorphism class of a directed subgraph of `size` nodes, by the smallest adjacency code
+/// over all orderings of its nodes. Bit `i * size + j` of the code is the edge `i -> j`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Subgraph {
+    pub size: usize,
+    pub code: u16,
+}
+
+const NAMED: [(&str, &[(usize, usize)]); 8] = [
+    ("feed-forward loop", &[(0, 1), (0, 2), (1, 2)]),
+    ("cascade", &[(0, 1), (1, 2)]),
+    ("fan-out", &[(0, 1), (0, 2)]),
+    ("fan-in", &[(0, 2), (1, 2)]),
+    ("feedback loop", &[(0, 1), (1, 2), (2, 0)]),
+    ("bi-fan", &[(0, 2), (0, 3), (1, 2), (1, 3)]),
+    ("bi-parallel", &[(0, 1), (0, 2), (1, 3), (2, 3)]),
+    ("single-input module", &[(0, 1), (0, 2), (0, 3)]),
+];
+
+impl Subgraph {
+    /// Class of the subgraph with `edges` between nodes `0..size`.
+    pub fn from_edges(size: usize, edges: &[(usize, usize)]) -> Self {
+        let code = edges
+            .iter()
+            .fold(0u16, |code, (i, j)| code | 1 << (i * size + j));
+        Subgraph {
+            size,
+            code: canonical(size, code),
+        }
+    }
+
+    /// Edges of a representative of the class.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        let n = self.size;
+        (0..n * n)
+            .filter(|bit| self.code & 1 << bit != 0)
+            .map(|bit| (bit / n, bit % n))
+            .collect()
+    }
+
+    /// Common name of the class, if it has one.
+    pub fn name(&self) -> Option<&'static str> {
+        NAMED
+            .iter()
+            .find(|(_, edges)| {
+                let size = edges.iter().map(|(i, j)| i.max(j) + 1).max().unwrap_or(0);
+                size == self.size && Subgraph::from_edges(size, edges) == *self
+            })
+            .map(|(name, _)| *name)
+    }
+}
+
+impl fmt::Display for Subgraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name),
+            None => {
+                let edges: Vec<String> = self
+                    .edges()
+                    .iter()
+                    .map(|(i, j)| format!("{}{}", i, j))
+                    .collect();
+                write!(f, "{}-node [{}]", self.size, edges.join(" "))
+            }
+        }
+    }
+}
+
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    let mut all = Vec::new();
+    for p in permutations(n - 1) {
+        for i in 0..n {
+            let mut q = p.clone();
+            q.insert(i, n - 1);
+            all.push(q);
+        }
+    }
+    all
+}
+
+fn canonical(size: usize, code: u16) -> u16 {
+    permutations(size)
+        .iter()
+        .map(|p| {
+            (0..size * size)
+                .filter(|bit| code & 1 << bit != 0)
+                .fold(0u16, |c, bit| {
+                    c | 1 << (p[bit / size] * size + p[bit % size])
+                })
+        })
+        .min()
+        .unwrap_or(code)
+}
+
+/// Canonical code of every adjacency code of one size.
+struct Classes {
+    size: usize,
+    canonical: Vec<u16>,
+}
+
+impl Classes {
+    fn new(size: usize) -> Self {
+        let canonical = (0..1u32 << (size * size))
+            .map(|code| canonical(size, code as u16))
+            .collect();
+        Classes { size, canonical }
+    }
+
+    fn class(&self, code: u16) -> Subgraph {
+        Subgraph {
+            size: self.size,
+            code: self.canonical[code as usize],
+        }
+    }
+}
+
+/// Edges without self-loops, and the neighbours of each node in either direction.
+struct Adjacency {
+    edges: HashSet<(usize, usize)>,
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl Adjacency {
+    fn new(graph: &DiGraph<String, Interaction>) -> Self {
+        let mut edges = HashSet::new();
+        let mut neighbours = vec![Vec::new(); graph.node_count()];
+        for edge in graph.edge_references() {
+            let (a, b) = (edge.source().index(), edge.target().index());
+            if a != b {
+                edges.insert((a, b));
+                neighbours[a].push(b);
+                neighbours[b].push(a);
+            }
+        }
+        for n in neighbours.iter_mut() {
+            n.sort_unstable();
+            n.dedup();
+        }
+        Adjacency { edges, neighbours }
+    }
+
+    fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.edges.contains(&(a, b))
+    }
+
+    /// Calls `visit` once with the nodes of every connected induced subgraph of `size` nodes,
+    /// by the ESU algorithm of Wernicke (2006).
+    fn subgraphs<F: FnMut(&[usize])>(&self, size: usize, mut visit: F) {
+        let mut nodes = Vec::with_capacity(size);
+        for v in 0..self.neighbours.len() {
+            let extension: Vec<usize> = self.neighbours[v]
+                .iter()
+                .copied()
+                .filter(|u| *u > v)
+                .collect();
+            nodes.push(v);
+            self.extend(size, &mut nodes, extension, v, &mut visit);
+            nodes.pop();
+        }
+    }
+
+    fn extend<F: FnMut(&[usize])>(
+        &self,
+        size: usize,
+        nodes: &mut Vec<usize>,
+        mut extension: Vec<usize>,
+        root: usize,
+        visit: &mut F,
+    ) {
+        if nodes.len() == size {
+            visit(nodes);
+            return;
+        }
+        while let Some(w) = extension.pop() {
+            // Neighbours of w that are new to the subgraph and its neighbourhood.
+            let mut next = extension.clone();
+            for u in &self.neighbours[w] {
+                let exclusive = *u > root
+                    && !nodes.contains(u)
+                    && nodes
+                        .iter()
+                        .all(|s| self.neighbours[*s].binary_search(u).is_err());
+                if exclusive && !next.contains(u) {
+                    next.push(*u);
+                }
+            }
+            nodes.push(w);
+            self.extend(size, nodes, next, root, visit);
+            nodes.pop();
+        }
+    }
+
+    fn census(&self, size: usize, classes: &Classes) -> BTreeMap<Subgraph, usize> {
+        let mut counts = BTreeMap::new();
+        self.subgraphs(size, |nodes| {
+            let mut code = 0u16;
+            for (i, a) in nodes.iter().enumerate() {
+                for (j, b) in nodes.iter().enumerate() {
+                    if i != j && self.has_edge(*a, *b) {
+                        code |= 1 << (i * size + j);
+                    }
+                }
+            }
+            *counts.entry(classes.class(code)).or_insert(0) += 1;
+        });
+        counts
+    }
+}
+
+/// Count of one subgraph class in a network against its counts in randomized networks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MotifScore {
+    pub subgraph: Subgraph,
+    pub count: usize,
+    pub random_mean: f64,
+    pub random_std: f64,
+    /// `(count - random_mean) / random_std`; infinite when the random counts do not vary
+    /// but differ from the count.
+    pub z_score: f64,
+}
+
+impl MotifScore {
+    /// Whether the class is a motif: over-represented with a Z-score of at least `z`.
+    pub fn is_motif(&self, z: f64) -> bool {
+        self.z_score >= z
+    }
+}
+
+/// Comparison of the subgraph census of a network with degree-preserving randomizations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Motifs {
+    size: usize,
+    networks: usize,
+    swaps_per_edge: usize,
+}
+
+impl Motifs {
+    /// Analysis of subgraphs of `size` genes, 3 or 4.
+    pub fn new(size: usize) -> Self {
+        Motifs {
+            size,
+            networks: 100,
+            swaps_per_edge: 10,
+        }
+    }
+
+    /// Number of randomized networks.
+    pub fn networks(mut self, networks: usize) -> Self {
+        self.networks = networks;
+        self
+    }
+
+    pub fn swaps_per_edge(mut self, swaps_per_edge: usize) -> Self {
+        self.swaps_per_edge = swaps_per_edge;
+        self
+    }
+
+    /// Scores of every class found in the network or in one of its randomizations, in order
+    /// of decreasing Z-score.
+    pub fn run<R: Rng>(
+        &self,
+        network: &TranscriptionNetwork,
+        rng: &mut R,
+    ) -> Result<Vec<MotifScore>, Error> {
+        check_size(self.size)?;
+        let classes = Classes::new(self.size);
+        let real = network.adjacency().census(self.size, &classes);
+        let random: Vec<BTreeMap<Subgraph, usize>> = (0..self.networks)
+            .map(|_| {
+                network
+                    .randomized(self.swaps_per_edge, rng)
+                    .adjacency()
+                    .census(self.size, &classes)
+            })
+            .collect();
+        let found: HashSet<Subgraph> = real
+            .keys()
+            .chain(random.iter().flat_map(|c| c.keys()))
+            .copied()
+            .collect();
+        let n = random.len().max(1) as f64;
+        let mut scores: Vec<MotifScore> = found
+            .into_iter()
+            .map(|subgraph| {
+                let count = real.get(&subgraph).copied().unwrap_or(0);
+                let counts = random
+                    .iter()
+                    .map(|c| c.get(&subgraph).copied().unwrap_or(0) as f64);
+                let random_mean = counts.clone().sum::<f64>() / n;
+                let random_std =
+                    (counts.map(|c| (c - random_mean).powi(2)).sum::<f64>() / n).sqrt();
+                let difference = count as f64 - random_mean;
+                let z_score = if random_std > 0.0 {
+                    difference / random_std
+                } else if difference == 0.0 {
+                    0.0
+                } else {
+                    difference.signum() * f64::INFINITY
+                };
+                MotifScore {
+                    subgraph,
+                    count,
+                    random_mean,
+                    random_std,
+                    z_score,
+                }
+            })
+            .collect();
+        scores.sort_by(|a, b| {
+            b.z_score
+                .total_cmp(&a.z_score)
+                .then(a.subgraph.cmp(&b.subgraph))
+        });
+        Ok(scores)
+    }
+}
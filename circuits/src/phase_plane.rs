@@ -0,0 +1,245 @@
+//! Phase-plane portraits of two state variables.
+//!
+//! A [`PhasePlane`] samples the direction field on a grid, traces the nullclines with marching
+//! squares, integrates trajectories from a grid of initial conditions and locates the fixed
+//! points inside the window. The result is a plain [`Portrait`] that apps render with plotters
+//! or charming. Variables other than the two axes are held at their values in the model's
+//! initial state.
+
+use ode_solvers::Dopri5;
+
+use crate::model::{Model, State};
+use crate::stability::{fixed_points, FixedPoint};
+
+/// A line segment between two points of the plane.
+pub type Segment = [(f64, f64); 2];
+
+/// Direction of the flow at a point of the plane.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Arrow {
+    pub x: f64,
+    pub y: f64,
+    pub dx: f64,
+    pub dy: f64,
+}
+
+/// Everything needed to draw a phase portrait.
+#[derive(Clone, Debug)]
+pub struct Portrait {
+    /// Indices of the state variables on the axes.
+    pub axes: (usize, usize),
+    pub x_label: String,
+    pub y_label: String,
+    pub x_range: (f64, f64),
+    pub y_range: (f64, f64),
+    pub arrows: Vec<Arrow>,
+    /// Where the derivative of the x variable vanishes.
+    pub x_nullcline: Vec<Segment>,
+    /// Where the derivative of the y variable vanishes.
+    pub y_nullcline: Vec<Segment>,
+    pub trajectories: Vec<Vec<(f64, f64)>>,
+    pub fixed_points: Vec<FixedPoint>,
+}
+
+/// Settings of a phase portrait.
+#[derive(Clone, Debug)]
+pub struct PhasePlane {
+    /// Indices of the state variables on the axes.
+    pub x: usize,
+    pub y: usize,
+    pub x_range: (f64, f64),
+    pub y_range: (f64, f64),
+    /// Arrows along each axis of the direction field.
+    pub field: usize,
+    /// Grid cells along each axis used to trace the nullclines.
+    pub resolution: usize,
+    /// Initial conditions along each axis of the trajectory grid.
+    pub starts: usize,
+    pub t_end: f64,
+}
+
+impl PhasePlane {
+    /// Portrait of the first two state variables.
+    pub fn new(x_range: (f64, f64), y_range: (f64, f64)) -> Self {
+        PhasePlane {
+            x: 0,
+            y: 1,
+            x_range,
+            y_range,
+            field: 20,
+            resolution: 200,
+            starts: 4,
+            t_end: 20.0,
+        }
+    }
+
+    pub fn axes(mut self, x: usize, y: usize) -> Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+
+    pub fn field(mut self, field: usize) -> Self {
+        self.field = field;
+        self
+    }
+
+    pub fn resolution(mut self, resolution: usize) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    pub fn starts(mut self, starts: usize) -> Self {
+        self.starts = starts;
+        self
+    }
+
+    pub fn t_end(mut self, t_end: f64) -> Self {
+        self.t_end = t_end;
+        self
+    }
+
+    /// Point `(i, j)` of an `n x n` grid spanning the window.
+    fn grid(&self, i: usize, j: usize, n: usize) -> (f64, f64) {
+        let step = |(lo, hi): (f64, f64), k: usize| lo + (hi - lo) * k as f64 / n.max(1) as f64;
+        (step(self.x_range, i), step(self.y_range, j))
+    }
+
+    fn state<M: Model>(&self, model: &M, x: f64, y: f64) -> State {
+        let mut state = model.initial_state();
+        state[self.x] = x;
+        state[self.y] = y;
+        state
+    }
+
+    fn contains(&self, (x, y): (f64, f64)) -> bool {
+        let inside = |v: f64, (lo, hi): (f64, f64)| lo <= v && v <= hi;
+        inside(x, self.x_range) && inside(y, self.y_range)
+    }
+
+    /// Derivatives of the two axis variables on an `n x n` grid of arrows.
+    pub fn direction_field<M: Model>(&self, model: &M) -> Vec<Arrow> {
+        let n = self.field.max(2) - 1;
+        let mut arrows = Vec::new();
+        for i in 0..=n {
+            for j in 0..=n {
+                let (x, y) = self.grid(i, j, n);
+                let dy = model.rhs(0.0, &self.state(model, x, y));
+                arrows.push(Arrow {
+                    x,
+                    y,
+                    dx: dy[self.x],
+                    dy: dy[self.y],
+                });
+            }
+        }
+        arrows
+    }
+
+    /// Zero level sets of the derivatives of the x and y variables.
+    pub fn nullclines<M: Model>(&self, model: &M) -> (Vec<Segment>, Vec<Segment>) {
+        let n = self.resolution.max(2);
+        let mut fx = vec![vec![0.0; n + 1]; n + 1];
+        let mut fy = vec![vec![0.0; n + 1]; n + 1];
+        for i in 0..=n {
+            for j in 0..=n {
+                let (x, y) = self.grid(i, j, n);
+                let dy = model.rhs(0.0, &self.state(model, x, y));
+                fx[i][j] = dy[self.x];
+                fy[i][j] = dy[self.y];
+            }
+        }
+        (self.contour(&fx, n), self.contour(&fy, n))
+    }
+
+    /// Marching squares on the values `f` at the nodes of an `n x n` grid.
+    fn contour(&self, f: &[Vec<f64>], n: usize) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let mut crossings = Vec::new();
+                for k in 0..4 {
+                    let (a, b) = (corners[k], corners[(k + 1) % 4]);
+                    let (fa, fb) = (f[a.0][a.1], f[b.0][b.1]);
+                    if (fa <= 0.0) != (fb <= 0.0) && fa.is_finite() && fb.is_finite() {
+                        let w = fa / (fa - fb);
+                        let (xa, ya) = self.grid(a.0, a.1, n);
+                        let (xb, yb) = self.grid(b.0, b.1, n);
+                        crossings.push((xa + w * (xb - xa), ya + w * (yb - ya)));
+                    }
+                }
+                for pair in crossings.chunks_exact(2) {
+                    segments.push([pair[0], pair[1]]);
+                }
+            }
+        }
+        segments
+    }
+
+    /// Trajectories from an `n x n` grid of initial conditions, cut where they leave the
+    /// window.
+    pub fn trajectories<M: Model + Clone>(&self, model: &M) -> Vec<Vec<(f64, f64)>> {
+        let n = self.starts.max(2) - 1;
+        let mut trajectories = Vec::new();
+        for i in 0..=n {
+            for j in 0..=n {
+                let (x, y) = self.grid(i, j, n);
+                let y0 = self.state(model, x, y);
+                let mut stepper = Dopri5::new(
+                    model.clone(),
+                    0.0,
+                    self.t_end,
+                    self.t_end / 500.0,
+                    y0,
+                    1e-6,
+                    1e-6,
+                );
+                if stepper.integrate().is_err() {
+                    continue;
+                }
+                let points: Vec<(f64, f64)> = stepper
+                    .y_out()
+                    .iter()
+                    .map(|s| (s[self.x], s[self.y]))
+                    .take_while(|p| self.contains(*p))
+                    .collect();
+                trajectories.push(points);
+            }
+        }
+        trajectories
+    }
+
+    /// Fixed points inside the window, searched from the nodes of the direction field.
+    pub fn fixed_points<M: Model>(&self, model: &M) -> Vec<FixedPoint> {
+        let n = self.field.max(2) - 1;
+        let guesses: Vec<State> = (0..=n)
+            .flat_map(|i| (0..=n).map(move |j| (i, j)))
+            .map(|(i, j)| {
+                let (x, y) = self.grid(i, j, n);
+                self.state(model, x, y)
+            })
+            .collect();
+        fixed_points(model, &guesses)
+            .into_iter()
+            .filter(|p| self.contains((p.state[self.x], p.state[self.y])))
+            .collect()
+    }
+
+    pub fn portrait<M: Model + Clone>(&self, model: &M) -> Portrait {
+        let species = model.species();
+        let (x_nullcline, y_nullcline) = self.nullclines(model);
+        Portrait {
+            axes: (self.x, self.y),
+            x_label: species[self.x].to_string(),
+            y_label: species[self.y].to_string(),
+            x_range: self.x_range,
+            y_range: self.y_range,
+            arrows: self.direction_field(model),
+            x_nullcline,
+            y_nullcline,
+            trajectories: self.trajectories(model),
+            fixed_points: self.fixed_points(model),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Random variates and sample quantiles shared by the stochastic simulators and the sampler.
+
+use rand::Rng;
+
+/// Exponential variate of unit mean.
+pub(crate) fn exponential<R: Rng>(rng: &mut R) -> f64 {
+    -(1.0 - rng.random::<f64>()).ln()
+}
+
+/// Standard normal variate by the Box–Muller transform.
+pub(crate) fn normal<R: Rng>(rng: &mut R) -> f64 {
+    let u = 1.0 - rng.random::<f64>();
+    let v = rng.random::<f64>();
+    (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+}
+
+/// Logarithm of `k!`, exact for small `k` and by Stirling's series above.
+fn ln_factorial(k: f64) -> f64 {
+    if k < 10.0 {
+        (2..=k as u64).map(|i| (i as f64).ln()).sum()
+    } else {
+        let k2 = k * k;
+        k * k.ln() - k + 0.5 * (std::f64::consts::TAU * k).ln() + 1.0 / (12.0 * k)
+            - 1.0 / (360.0 * k2 * k)
+            + 1.0 / (1260.0 * k2 * k2 * k)
+    }
+}
+
+/// Poisson variate: multiplication of uniforms for small means, Hörmann's transformed
+/// rejection (PTRS) for large ones.
+pub(crate) fn poisson<R: Rng>(rng: &mut R, lambda: f64) -> f64 {
+    if lambda <= 0.0 {
+        return 0.0;
+    }
+    if lambda < 10.0 {
+        let limit = (-lambda).exp();
+        let mut k = 0.0;
+        let mut p = rng.random::<f64>();
+        while p > limit {
+            k += 1.0;
+            p *= rng.random::<f64>();
+        }
+        return k;
+    }
+    let (slam, loglam) = (lambda.sqrt(), lambda.ln());
+    let b = 0.931 + 2.53 * slam;
+    let a = -0.059 + 0.02483 * b;
+    let inv_alpha = 1.1239 + 1.1328 / (b - 3.4);
+    let vr = 0.9277 - 3.6224 / (b - 2.0);
+    loop {
+        let u = rng.random::<f64>() - 0.5;
+        let v = rng.random::<f64>();
+        let us = 0.5 - u.abs();
+        let k = ((2.0 * a / us + b) * u + lambda + 0.43).floor();
+        if us >= 0.07 && v <= vr {
+            return k;
+        }
+        if k < 0.0 || (us < 0.013 && v > us) {
+            continue;
+        }
+        if v.ln() + inv_alpha.ln() - (a / (us * us) + b).ln()
+            <= -lambda + k * loglam - ln_factorial(k)
+        {
+            return k;
+        }
+    }
+}
+
+/// Linearly interpolated quantile; sorts `values` in place.
+pub(crate) fn quantile(values: &mut [f64], q: f64) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let position = q.clamp(0.0, 1.0) * (values.len() - 1) as f64;
+    let (lo, hi) = (position.floor() as usize, position.ceil() as usize);
+    values[lo] + (values[hi] - values[lo]) * (position - lo as f64)
+}
@@ -0,0 +1,330 @@
+//! Reaction networks described by species, parameters and reactions.
+//!
+//! A network is written once, with reactions in the same notation as rebop's `define_system!`:
+//!
+//! ```
+//! use circuits::reactions::ReactionNetwork;
+//!
+//! let sir = ReactionNetwork::new("SIR")
+//!     .species("S", 999.0)
+//!     .species("I", 1.0)
+//!     .species("R", 0.0)
+//!     .parameter("r_infection", 0.1 / 1000.0)
+//!     .parameter("r_healing", 0.01)
+//!     .reaction("infection", "S + I => 2 I @ r_infection")?
+//!     .reaction("healing", "I => R @ r_healing")?;
+//! # Ok::<(), circuits::Error>(())
+//! ```
+//!
+//! The network implements [`ode_solvers::System`] with the deterministic rate laws and can be
+//! simulated exactly with [`crate::stochastic::Gillespie`], so both views share one description.
+//!
+//! Rates after the `@` are either a parameter name (mass action) or a Hill function of one
+//! species, `hill_act(beta, X, K, n)` or `hill_rep(beta, X, K, n)`, with parameter names for
+//! `beta`, `K` and `n`.
+
+use crate::model::{Error, Model, Parameter, State, Time};
+
+/// Rate law of a reaction. Parameters and species are stored by index.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RateLaw {
+    /// `k` times the product of the reactant amounts.
+    MassAction { k: usize },
+    /// `beta x^n / (K^n + x^n)` for the activating species `x`.
+    HillActivation {
+        beta: usize,
+        species: usize,
+        k: usize,
+        n: usize,
+    },
+    /// `beta K^n / (K^n + x^n)` for the repressing species `x`.
+    HillRepression {
+        beta: usize,
+        species: usize,
+        k: usize,
+        n: usize,
+    },
+}
+
+/// A single reaction with its stoichiometry, given as `(species, coefficient)` pairs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reaction {
+    pub name: String,
+    pub reactants: Vec<(usize, u32)>,
+    pub products: Vec<(usize, u32)>,
+    pub rate: RateLaw,
+}
+
+impl Reaction {
+    /// Species whose amounts the rate of the reaction depends on.
+    pub fn dependencies(&self) -> Vec<usize> {
+        match self.rate {
+            RateLaw::MassAction { .. } => self.reactants.iter().map(|(s, _)| *s).collect(),
+            RateLaw::HillActivation { species, .. } | RateLaw::HillRepression { species, .. } => {
+                vec![species]
+            }
+        }
+    }
+
+    /// Net change of every species when the reaction fires once.
+    pub fn net_change(&self, n_species: usize) -> Vec<i64> {
+        let mut change = vec![0; n_species];
+        for (s, nu) in &self.reactants {
+            change[*s] -= *nu as i64;
+        }
+        for (s, nu) in &self.products {
+            change[*s] += *nu as i64;
+        }
+        change
+    }
+}
+
+/// Species, parameters and reactions of a chemical reaction network.
+#[derive(Clone, Debug, Default)]
+pub struct ReactionNetwork {
+    pub name: String,
+    pub species: Vec<String>,
+    pub initial: Vec<f64>,
+    pub parameters: Vec<Parameter>,
+    pub reactions: Vec<Reaction>,
+}
+
+impl ReactionNetwork {
+    pub fn new(name: &str) -> Self {
+        ReactionNetwork {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a species with its initial amount.
+    pub fn species(mut self, name: &str, initial: f64) -> Self {
+        self.species.push(name.to_string());
+        self.initial.push(initial);
+        self
+    }
+
+    /// Adds a non-negative parameter.
+    pub fn parameter(self, name: &str, value: f64) -> Self {
+        self.parameter_with_bounds(name, value, 0.0, f64::INFINITY)
+    }
+
+    /// Adds a parameter with explicit bounds.
+    pub fn parameter_with_bounds(mut self, name: &str, value: f64, min: f64, max: f64) -> Self {
+        self.parameters
+            .push(Parameter::new(name, value, value, min, max));
+        self
+    }
+
+    /// Parses and adds a reaction such as `S + I => 2 I @ r_infection`.
+    pub fn reaction(mut self, name: &str, equation: &str) -> Result<Self, Error> {
+        let reaction = self.parse_reaction(name, equation)?;
+        self.reactions.push(reaction);
+        Ok(self)
+    }
+
+    /// Adds an already built reaction.
+    pub fn push(mut self, reaction: Reaction) -> Self {
+        self.reactions.push(reaction);
+        self
+    }
+
+    fn species_id(&self, name: &str) -> Result<usize, Error> {
+        self.species
+            .iter()
+            .position(|s| s == name)
+            .ok_or_else(|| Error::Parse(format!("unknown species `{}`", name)))
+    }
+
+    fn parameter_id(&self, name: &str) -> Result<usize, Error> {
+        self.parameters
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| Error::UnknownParameter(name.to_string()))
+    }
+
+    fn parse_side(&self, side: &str) -> Result<Vec<(usize, u32)>, Error> {
+        let mut terms = Vec::new();
+        for term in side.split('+').map(str::trim) {
+            if term.is_empty() || term == "0" || term == "∅" {
+                continue;
+            }
+            let mut parts = term.split_whitespace();
+            let first = parts.next().unwrap_or_default();
+            let (nu, species) = match (first.parse::<u32>(), parts.next()) {
+                (Ok(nu), Some(species)) => (nu, species),
+                (Err(_), None) => (1, first),
+                _ => return Err(Error::Parse(format!("cannot parse term `{}`", term))),
+            };
+            terms.push((self.species_id(species)?, nu));
+        }
+        Ok(terms)
+    }
+
+    fn parse_rate(&self, rate: &str) -> Result<RateLaw, Error> {
+        let rate = rate.trim();
+        if let Some((function, args)) = rate.strip_suffix(')').and_then(|r| r.split_once('(')) {
+            let args: Vec<&str> = args.split(',').map(str::trim).collect();
+            if args.len() != 4 {
+                return Err(Error::Parse(format!(
+                    "`{}` expects (beta, species, K, n)",
+                    function
+                )));
+            }
+            let beta = self.parameter_id(args[0])?;
+            let species = self.species_id(args[1])?;
+            let k = self.parameter_id(args[2])?;
+            let n = self.parameter_id(args[3])?;
+            match function.trim() {
+                "hill_act" => Ok(RateLaw::HillActivation {
+                    beta,
+                    species,
+                    k,
+                    n,
+                }),
+                "hill_rep" => Ok(RateLaw::HillRepression {
+                    beta,
+                    species,
+                    k,
+                    n,
+                }),
+                other => Err(Error::Parse(format!("unknown rate law `{}`", other))),
+            }
+        } else {
+            Ok(RateLaw::MassAction {
+                k: self.parameter_id(rate)?,
+            })
+        }
+    }
+
+    fn parse_reaction(&self, name: &str, equation: &str) -> Result<Reaction, Error> {
+        let (equation, rate) = equation
+            .split_once('@')
+            .ok_or_else(|| Error::Parse(format!("missing rate in `{}`", equation)))?;
+        let (lhs, rhs) = equation
+            .split_once("=>")
+            .ok_or_else(|| Error::Parse(format!("missing `=>` in `{}`", equation)))?;
+        Ok(Reaction {
+            name: name.to_string(),
+            reactants: self.parse_side(lhs)?,
+            products: self.parse_side(rhs)?,
+            rate: self.parse_rate(rate)?,
+        })
+    }
+
+    fn hill(&self, beta: usize, x: f64, k: usize, n: usize, activating: bool) -> f64 {
+        let xn = (x.max(0.0) / self.parameters[k].value).powf(self.parameters[n].value);
+        let f = if activating {
+            xn / (1.0 + xn)
+        } else {
+            1.0 / (1.0 + xn)
+        };
+        self.parameters[beta].value * f
+    }
+
+    /// Deterministic rate of reaction `j` at concentrations `y`.
+    pub fn rate(&self, j: usize, y: &[f64]) -> f64 {
+        let reaction = &self.reactions[j];
+        match reaction.rate {
+            RateLaw::MassAction { k } => reaction
+                .reactants
+                .iter()
+                .fold(self.parameters[k].value, |a, (s, nu)| {
+                    a * y[*s].powi(*nu as i32)
+                }),
+            RateLaw::HillActivation {
+                beta,
+                species,
+                k,
+                n,
+            } => self.hill(beta, y[species], k, n, true),
+            RateLaw::HillRepression {
+                beta,
+                species,
+                k,
+                n,
+            } => self.hill(beta, y[species], k, n, false),
+        }
+    }
+
+    /// Stochastic propensity of reaction `j` for the copy numbers `x`.
+    ///
+    /// Mass action uses falling factorials, so `2 A` contributes `A (A - 1)`.
+    pub fn propensity(&self, j: usize, x: &[f64]) -> f64 {
+        let reaction = &self.reactions[j];
+        match reaction.rate {
+            RateLaw::MassAction { k } => reaction
+                .reactants
+                .iter()
+                .fold(self.parameters[k].value, |a, (s, nu)| {
+                    (0..*nu).fold(a, |a, i| a * (x[*s] - i as f64).max(0.0))
+                }),
+            _ => self.rate(j, x),
+        }
+    }
+
+    /// Stoichiometry matrix as one net-change vector per reaction.
+    pub fn stoichiometry(&self) -> Vec<Vec<i64>> {
+        self.reactions
+            .iter()
+            .map(|r| r.net_change(self.species.len()))
+            .collect()
+    }
+
+    /// For every reaction, the reactions whose propensities change when it fires, itself
+    /// included.
+    pub fn dependency_graph(&self) -> Vec<Vec<usize>> {
+        let stoichiometry = self.stoichiometry();
+        let dependencies: Vec<Vec<usize>> =
+            self.reactions.iter().map(Reaction::dependencies).collect();
+        stoichiometry
+            .iter()
+            .enumerate()
+            .map(|(j, change)| {
+                (0..self.reactions.len())
+                    .filter(|k| *k == j || dependencies[*k].iter().any(|s| change[*s] != 0))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl ode_solvers::System<f64, State> for ReactionNetwork {
+    fn system(&self, _t: Time, y: &State, dy: &mut State) {
+        dy.fill(0.0);
+        for (j, reaction) in self.reactions.iter().enumerate() {
+            let rate = self.rate(j, y.as_slice());
+            for (s, nu) in &reaction.reactants {
+                dy[*s] -= *nu as f64 * rate;
+            }
+            for (s, nu) in &reaction.products {
+                dy[*s] += *nu as f64 * rate;
+            }
+        }
+    }
+}
+
+impl Model for ReactionNetwork {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn species(&self) -> Vec<&str> {
+        self.species.iter().map(String::as_str).collect()
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        self.parameters.clone()
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        self.parameters
+            .iter_mut()
+            .find(|p| p.name == name)
+            .map(|p| &mut p.value)
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(self.initial.clone())
+    }
+}
@@ -0,0 +1,91 @@
+//! Input functions of transcriptional regulation.
+//!
+//! A [`Hill`] term gives the fraction of promoter activity due to one transcription factor,
+//! rising with an activator and falling with a repressor. A gene with several inputs combines
+//! its terms with a [`Logic`] gate: the product for AND, the probability that either term is
+//! active for OR, or their sum for additive regulation.
+
+/// Effect of a transcription factor on its target.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Sign {
+    Activation,
+    Repression,
+}
+
+impl Sign {
+    /// `+1` for activation, `-1` for repression.
+    pub fn value(&self) -> i32 {
+        match self {
+            Sign::Activation => 1,
+            Sign::Repression => -1,
+        }
+    }
+}
+
+/// Activating Hill function `(x / k)^n / (1 + (x / k)^n)`.
+pub fn activation(x: f64, k: f64, n: f64) -> f64 {
+    let r = (x.max(0.0) / k).powf(n);
+    r / (1.0 + r)
+}
+
+/// Repressing Hill function `1 / (1 + (x / k)^n)`.
+pub fn repression(x: f64, k: f64, n: f64) -> f64 {
+    1.0 / (1.0 + (x.max(0.0) / k).powf(n))
+}
+
+/// Regulation of a promoter by one transcription factor.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hill {
+    pub sign: Sign,
+    /// Concentration of half-maximal effect.
+    pub k: f64,
+    /// Hill coefficient.
+    pub n: f64,
+}
+
+impl Hill {
+    pub fn new(sign: Sign, k: f64, n: f64) -> Self {
+        Hill { sign, k, n }
+    }
+
+    pub fn activation(k: f64, n: f64) -> Self {
+        Hill::new(Sign::Activation, k, n)
+    }
+
+    pub fn repression(k: f64, n: f64) -> Self {
+        Hill::new(Sign::Repression, k, n)
+    }
+
+    /// Promoter activity between 0 and 1 at regulator level `x`.
+    pub fn value(&self, x: f64) -> f64 {
+        match self.sign {
+            Sign::Activation => activation(x, self.k, self.n),
+            Sign::Repression => repression(x, self.k, self.n),
+        }
+    }
+}
+
+/// How a promoter combines the terms of several regulators.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Logic {
+    /// All inputs are needed: the product of the terms.
+    #[default]
+    And,
+    /// Any input suffices: `1 - prod(1 - f_i)`.
+    Or,
+    /// Independent contributions: the sum of the terms.
+    Sum,
+}
+
+impl Logic {
+    /// Combined activity of the `terms`; a promoter without inputs is fully active.
+    pub fn combine(&self, terms: &[f64]) -> f64 {
+        match self {
+            Logic::And => terms.iter().product(),
+            Logic::Or if terms.is_empty() => 1.0,
+            Logic::Or => 1.0 - terms.iter().map(|f| 1.0 - f).product::<f64>(),
+            Logic::Sum if terms.is_empty() => 1.0,
+            Logic::Sum => terms.iter().sum(),
+        }
+    }
+}
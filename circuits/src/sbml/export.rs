@@ -0,0 +1,264 @@
+use super::mathml::{Expr, Op};
+use super::{Compartment, SbmlModel, SbmlReaction, Species};
+use crate::model::{Model, Parameter};
+use crate::models::{Autorepressor, CoffeeRust, NegativeAutoregulation, Sir};
+use crate::reactions::{RateLaw, ReactionNetwork};
+use crate::signal::Signal;
+
+/// Models that can be written as SBML.
+pub trait ToSbml {
+    fn to_sbml(&self) -> SbmlModel;
+}
+
+/// Identifier made of the letters, digits and underscores of `name`.
+fn sbml_id(name: &str) -> String {
+    let id: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if id.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", id)
+    } else {
+        id
+    }
+}
+
+/// A model with one compartment of unit size holding every species as a concentration.
+fn single_compartment<M: Model>(model: &M) -> SbmlModel {
+    let y0 = model.initial_state();
+    SbmlModel {
+        id: sbml_id(model.name()),
+        name: model.name().to_string(),
+        compartments: vec![Compartment {
+            id: "cell".to_string(),
+            size: 1.0,
+        }],
+        species: model
+            .species()
+            .iter()
+            .zip(y0.iter())
+            .map(|(s, y)| Species {
+                id: s.to_string(),
+                name: s.to_string(),
+                compartment: 0,
+                initial: *y,
+                only_substance_units: false,
+                boundary_condition: false,
+                constant: false,
+            })
+            .collect(),
+        parameters: model.parameters(),
+        reactions: Vec::new(),
+        assignment_rules: Vec::new(),
+        rate_rules: Vec::new(),
+    }
+}
+
+fn hill(beta: usize, species: usize, k: usize, n: usize, activating: bool) -> Expr {
+    let xn = (Expr::species(species) / Expr::parameter(k)).pow(Expr::parameter(n));
+    let numerator = if activating { xn.clone() } else { 1.0.into() };
+    Expr::parameter(beta) * numerator / (Expr::from(1.0) + xn)
+}
+
+fn compare(op: Op, lhs: Expr, rhs: f64) -> Expr {
+    Expr::Apply(op, vec![lhs, rhs.into()])
+}
+
+/// A time-varying input written out as a MathML expression of `time`.
+fn signal(s: &Signal) -> Expr {
+    match s {
+        Signal::Constant(v) => (*v).into(),
+        Signal::Step { t, before, after } => Expr::Piecewise(
+            vec![((*after).into(), compare(Op::Geq, Expr::Time, *t))],
+            Some(Box::new((*before).into())),
+        ),
+        Signal::Pulse {
+            t_on,
+            t_off,
+            low,
+            high,
+        } => Expr::Piecewise(
+            vec![(
+                (*high).into(),
+                Expr::Apply(
+                    Op::And,
+                    vec![
+                        compare(Op::Geq, Expr::Time, *t_on),
+                        compare(Op::Lt, Expr::Time, *t_off),
+                    ],
+                ),
+            )],
+            Some(Box::new((*low).into())),
+        ),
+        Signal::PulseTrain {
+            t_on,
+            width,
+            period,
+            low,
+            high,
+        } => {
+            let since = Expr::Time - (*t_on).into();
+            let cycles = Expr::Apply(Op::Floor, vec![since.clone() / (*period).into()]);
+            let phase = since - Expr::from(*period) * cycles;
+            Expr::Piecewise(
+                vec![(
+                    (*high).into(),
+                    Expr::Apply(
+                        Op::And,
+                        vec![
+                            compare(Op::Geq, Expr::Time, *t_on),
+                            compare(Op::Lt, phase, *width),
+                        ],
+                    ),
+                )],
+                Some(Box::new((*low).into())),
+            )
+        }
+        Signal::Ramp {
+            t_start,
+            t_end,
+            from,
+            to,
+        } => {
+            let slope = (to - from) / (t_end - t_start);
+            Expr::Piecewise(
+                vec![
+                    ((*from).into(), compare(Op::Leq, Expr::Time, *t_start)),
+                    ((*to).into(), compare(Op::Geq, Expr::Time, *t_end)),
+                ],
+                Some(Box::new(
+                    Expr::from(*from) + Expr::from(slope) * (Expr::Time - (*t_start).into()),
+                )),
+            )
+        }
+        Signal::Sine {
+            mean,
+            amplitude,
+            period,
+            phase,
+        } => {
+            let angle =
+                Expr::from(2.0 * std::f64::consts::PI / period) * Expr::Time + (*phase).into();
+            Expr::from(*mean) + Expr::from(*amplitude) * Expr::Apply(Op::Sin, vec![angle])
+        }
+        Signal::Table(points) => {
+            let (first, last) = match (points.first(), points.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => return 0.0.into(),
+            };
+            let mut pieces = vec![(first.1.into(), compare(Op::Lt, Expr::Time, first.0))];
+            for w in points.windows(2) {
+                let ((t0, v0), (t1, v1)) = (w[0], w[1]);
+                let value =
+                    Expr::from(v0) + Expr::from((v1 - v0) / (t1 - t0)) * (Expr::Time - t0.into());
+                pieces.push((value, compare(Op::Lt, Expr::Time, t1)));
+            }
+            Expr::Piecewise(pieces, Some(Box::new(last.1.into())))
+        }
+    }
+}
+
+impl ToSbml for ReactionNetwork {
+    fn to_sbml(&self) -> SbmlModel {
+        let mut model = single_compartment(self);
+        let as_f64 = |terms: &[(usize, u32)]| -> Vec<(usize, f64)> {
+            terms.iter().map(|(s, nu)| (*s, *nu as f64)).collect()
+        };
+        model.reactions = self
+            .reactions
+            .iter()
+            .map(|r| {
+                let rate = match r.rate {
+                    RateLaw::MassAction { k } => {
+                        r.reactants
+                            .iter()
+                            .fold(Expr::parameter(k), |rate, (s, nu)| {
+                                let x = Expr::species(*s);
+                                rate * if *nu == 1 {
+                                    x
+                                } else {
+                                    x.pow(Expr::from(*nu as f64))
+                                }
+                            })
+                    }
+                    RateLaw::HillActivation {
+                        beta,
+                        species,
+                        k,
+                        n,
+                    } => hill(beta, species, k, n, true),
+                    RateLaw::HillRepression {
+                        beta,
+                        species,
+                        k,
+                        n,
+                    } => hill(beta, species, k, n, false),
+                };
+                SbmlReaction {
+                    id: sbml_id(&r.name),
+                    reactants: as_f64(&r.reactants),
+                    products: as_f64(&r.products),
+                    rate,
+                }
+            })
+            .collect();
+        model
+    }
+}
+
+impl ToSbml for Autorepressor {
+    fn to_sbml(&self) -> SbmlModel {
+        let mut model = single_compartment(self);
+        let index = |name: &str| {
+            model
+                .parameters
+                .iter()
+                .position(|p: &Parameter| p.name == name)
+                .expect("autorepressor parameter")
+        };
+        let p = |name: &str| Expr::parameter(index(name));
+        let s = match self.s {
+            Signal::Constant(_) => p("s"),
+            ref s => signal(s),
+        };
+        let input = (s / p("ks")).pow(p("ns"));
+        let activation = input.clone() / (Expr::from(1.0) + input);
+        let repression =
+            Expr::from(1.0) / (Expr::from(1.0) + (Expr::species(0) / p("k")).pow(p("n")));
+        let production = p("beta0") * activation * repression;
+        let degradation = p("gamma") * Expr::species(0);
+        model.reactions = vec![
+            SbmlReaction {
+                id: "production".to_string(),
+                reactants: vec![],
+                products: vec![(0, 1.0)],
+                rate: production,
+            },
+            SbmlReaction {
+                id: "degradation".to_string(),
+                reactants: vec![(0, 1.0)],
+                products: vec![],
+                rate: degradation,
+            },
+        ];
+        model
+    }
+}
+
+impl ToSbml for NegativeAutoregulation {
+    fn to_sbml(&self) -> SbmlModel {
+        self.network().to_sbml()
+    }
+}
+
+impl ToSbml for CoffeeRust {
+    fn to_sbml(&self) -> SbmlModel {
+        self.network().to_sbml()
+    }
+}
+
+impl ToSbml for Sir {
+    fn to_sbml(&self) -> SbmlModel {
+        self.network().to_sbml()
+    }
+}
@@ -0,0 +1,477 @@
+//! The subset of content MathML used by SBML kinetic laws and rules.
+
+use roxmltree::Node;
+
+use crate::model::Error;
+
+/// Operators of an `<apply>` element.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Op {
+    Plus,
+    Minus,
+    Times,
+    Divide,
+    Power,
+    Root,
+    Exp,
+    Ln,
+    Log,
+    Abs,
+    Floor,
+    Ceiling,
+    Sin,
+    Cos,
+    Tan,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Leq,
+    Geq,
+    And,
+    Or,
+    Not,
+}
+
+impl Op {
+    fn from_tag(tag: &str) -> Option<Op> {
+        let op = match tag {
+            "plus" => Op::Plus,
+            "minus" => Op::Minus,
+            "times" => Op::Times,
+            "divide" => Op::Divide,
+            "power" => Op::Power,
+            "root" => Op::Root,
+            "exp" => Op::Exp,
+            "ln" => Op::Ln,
+            "log" => Op::Log,
+            "abs" => Op::Abs,
+            "floor" => Op::Floor,
+            "ceiling" => Op::Ceiling,
+            "sin" => Op::Sin,
+            "cos" => Op::Cos,
+            "tan" => Op::Tan,
+            "eq" => Op::Eq,
+            "neq" => Op::Neq,
+            "lt" => Op::Lt,
+            "gt" => Op::Gt,
+            "leq" => Op::Leq,
+            "geq" => Op::Geq,
+            "and" => Op::And,
+            "or" => Op::Or,
+            "not" => Op::Not,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    /// MathML tag of the operator.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Op::Plus => "plus",
+            Op::Minus => "minus",
+            Op::Times => "times",
+            Op::Divide => "divide",
+            Op::Power => "power",
+            Op::Root => "root",
+            Op::Exp => "exp",
+            Op::Ln => "ln",
+            Op::Log => "log",
+            Op::Abs => "abs",
+            Op::Floor => "floor",
+            Op::Ceiling => "ceiling",
+            Op::Sin => "sin",
+            Op::Cos => "cos",
+            Op::Tan => "tan",
+            Op::Eq => "eq",
+            Op::Neq => "neq",
+            Op::Lt => "lt",
+            Op::Gt => "gt",
+            Op::Leq => "leq",
+            Op::Geq => "geq",
+            Op::And => "and",
+            Op::Or => "or",
+            Op::Not => "not",
+        }
+    }
+
+    /// Tag of the qualifier the operator accepts, `<degree>` for roots and `<logbase>` for
+    /// logarithms.
+    pub fn qualifier(&self) -> Option<&'static str> {
+        match self {
+            Op::Root => Some("degree"),
+            Op::Log => Some("logbase"),
+            _ => None,
+        }
+    }
+
+    /// Smallest and largest number of arguments, counting a `<degree>` or `<logbase>`
+    /// qualifier as the first one.
+    pub fn arity(&self) -> (usize, usize) {
+        match self {
+            Op::Plus | Op::Times | Op::And | Op::Or => (0, usize::MAX),
+            Op::Minus | Op::Root | Op::Log => (1, 2),
+            Op::Divide | Op::Power => (2, 2),
+            Op::Eq | Op::Neq | Op::Lt | Op::Gt | Op::Leq | Op::Geq => (2, 2),
+            Op::Exp | Op::Ln | Op::Abs | Op::Floor | Op::Ceiling => (1, 1),
+            Op::Sin | Op::Cos | Op::Tan | Op::Not => (1, 1),
+        }
+    }
+}
+
+/// A resolved reference to a value of the model.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Var {
+    Species(usize),
+    Parameter(usize),
+    Compartment(usize),
+}
+
+/// Expression tree of a MathML formula.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    /// An identifier that has not been resolved yet.
+    Symbol(String),
+    Var(Var),
+    Time,
+    Apply(Op, Vec<Expr>),
+    /// Call of a function definition, inlined by [`Expr::inline`].
+    Call(String, Vec<Expr>),
+    Piecewise(Vec<(Expr, Expr)>, Option<Box<Expr>>),
+}
+
+/// Values an expression is evaluated against.
+pub struct Context<'a> {
+    pub t: f64,
+    pub species: &'a [f64],
+    pub parameters: &'a [f64],
+    pub compartments: &'a [f64],
+}
+
+fn truth(b: bool) -> f64 {
+    if b {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Expr {
+    /// Parses the single expression inside a `<math>` element.
+    pub fn from_math(math: Node) -> Result<Expr, Error> {
+        let node = math
+            .children()
+            .find(|n| n.is_element())
+            .ok_or_else(|| Error::Parse("empty <math> element".to_string()))?;
+        Expr::parse(node)
+    }
+
+    /// Parses a MathML content element.
+    pub fn parse(node: Node) -> Result<Expr, Error> {
+        let tag = node.tag_name().name();
+        match tag {
+            "cn" => parse_number(node).map(Expr::Number),
+            "ci" => Ok(Expr::Symbol(
+                node.text().unwrap_or_default().trim().to_string(),
+            )),
+            "csymbol" => match node.attribute("definitionURL") {
+                Some(url) if url.ends_with("/time") => Ok(Expr::Time),
+                Some(url) if url.ends_with("/avogadro") => Ok(Expr::Number(6.02214076e23)),
+                other => Err(Error::Parse(format!("unsupported csymbol {:?}", other))),
+            },
+            "pi" => Ok(Expr::Number(std::f64::consts::PI)),
+            "exponentiale" => Ok(Expr::Number(std::f64::consts::E)),
+            "true" => Ok(Expr::Number(1.0)),
+            "false" => Ok(Expr::Number(0.0)),
+            "infinity" => Ok(Expr::Number(f64::INFINITY)),
+            "notanumber" => Ok(Expr::Number(f64::NAN)),
+            "apply" => parse_apply(node),
+            "piecewise" => parse_piecewise(node),
+            other => Err(Error::Parse(format!(
+                "unsupported MathML element <{}>",
+                other
+            ))),
+        }
+    }
+
+    /// Replaces calls of function definitions by their bodies.
+    pub fn inline(&self, functions: &[(String, Vec<String>, Expr)]) -> Result<Expr, Error> {
+        Ok(match self {
+            Expr::Call(name, args) => {
+                let (_, params, body) = functions
+                    .iter()
+                    .find(|(id, _, _)| id == name)
+                    .ok_or_else(|| Error::Parse(format!("unknown function `{}`", name)))?;
+                if params.len() != args.len() {
+                    return Err(Error::Parse(format!(
+                        "function `{}` expects {} arguments",
+                        name,
+                        params.len()
+                    )));
+                }
+                let args = args
+                    .iter()
+                    .map(|a| a.inline(functions))
+                    .collect::<Result<Vec<_>, _>>()?;
+                body.substitute(params, &args).inline(functions)?
+            }
+            Expr::Apply(op, args) => Expr::Apply(
+                *op,
+                args.iter()
+                    .map(|a| a.inline(functions))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Expr::Piecewise(pieces, otherwise) => Expr::Piecewise(
+                pieces
+                    .iter()
+                    .map(|(v, c)| Ok((v.inline(functions)?, c.inline(functions)?)))
+                    .collect::<Result<_, Error>>()?,
+                match otherwise {
+                    Some(o) => Some(Box::new(o.inline(functions)?)),
+                    None => None,
+                },
+            ),
+            other => other.clone(),
+        })
+    }
+
+    fn substitute(&self, params: &[String], args: &[Expr]) -> Expr {
+        match self {
+            Expr::Symbol(s) => match params.iter().position(|p| p == s) {
+                Some(i) => args[i].clone(),
+                None => self.clone(),
+            },
+            Expr::Apply(op, a) => {
+                Expr::Apply(*op, a.iter().map(|e| e.substitute(params, args)).collect())
+            }
+            Expr::Call(name, a) => Expr::Call(
+                name.clone(),
+                a.iter().map(|e| e.substitute(params, args)).collect(),
+            ),
+            Expr::Piecewise(pieces, otherwise) => Expr::Piecewise(
+                pieces
+                    .iter()
+                    .map(|(v, c)| (v.substitute(params, args), c.substitute(params, args)))
+                    .collect(),
+                otherwise
+                    .as_ref()
+                    .map(|o| Box::new(o.substitute(params, args))),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// Resolves every symbol with `lookup`.
+    pub fn resolve<F>(&self, lookup: &F) -> Result<Expr, Error>
+    where
+        F: Fn(&str) -> Option<Var>,
+    {
+        Ok(match self {
+            Expr::Symbol(s) => Expr::Var(
+                lookup(s).ok_or_else(|| Error::Parse(format!("unknown identifier `{}`", s)))?,
+            ),
+            Expr::Apply(op, args) => Expr::Apply(
+                *op,
+                args.iter()
+                    .map(|a| a.resolve(lookup))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Expr::Call(name, _) => {
+                return Err(Error::Parse(format!("function `{}` was not inlined", name)))
+            }
+            Expr::Piecewise(pieces, otherwise) => Expr::Piecewise(
+                pieces
+                    .iter()
+                    .map(|(v, c)| Ok((v.resolve(lookup)?, c.resolve(lookup)?)))
+                    .collect::<Result<_, Error>>()?,
+                match otherwise {
+                    Some(o) => Some(Box::new(o.resolve(lookup)?)),
+                    None => None,
+                },
+            ),
+            other => other.clone(),
+        })
+    }
+
+    /// Evaluates a resolved expression.
+    pub fn eval(&self, ctx: &Context) -> f64 {
+        match self {
+            Expr::Number(x) => *x,
+            Expr::Symbol(_) | Expr::Call(_, _) => f64::NAN,
+            Expr::Var(Var::Species(i)) => ctx.species[*i],
+            Expr::Var(Var::Parameter(i)) => ctx.parameters[*i],
+            Expr::Var(Var::Compartment(i)) => ctx.compartments[*i],
+            Expr::Time => ctx.t,
+            Expr::Piecewise(pieces, otherwise) => pieces
+                .iter()
+                .find(|(_, c)| c.eval(ctx) != 0.0)
+                .map(|(v, _)| v.eval(ctx))
+                .unwrap_or_else(|| otherwise.as_ref().map_or(f64::NAN, |o| o.eval(ctx))),
+            Expr::Apply(op, args) => {
+                let x = |i: usize| args[i].eval(ctx);
+                match op {
+                    Op::Plus => args.iter().map(|a| a.eval(ctx)).sum(),
+                    Op::Times => args.iter().map(|a| a.eval(ctx)).product(),
+                    Op::Minus if args.len() == 1 => -x(0),
+                    Op::Minus => x(0) - x(1),
+                    Op::Divide => x(0) / x(1),
+                    Op::Power => x(0).powf(x(1)),
+                    Op::Root if args.len() == 2 => x(1).powf(1.0 / x(0)),
+                    Op::Root => x(0).sqrt(),
+                    Op::Exp => x(0).exp(),
+                    Op::Ln => x(0).ln(),
+                    Op::Log if args.len() == 2 => x(1).log(x(0)),
+                    Op::Log => x(0).log10(),
+                    Op::Abs => x(0).abs(),
+                    Op::Floor => x(0).floor(),
+                    Op::Ceiling => x(0).ceil(),
+                    Op::Sin => x(0).sin(),
+                    Op::Cos => x(0).cos(),
+                    Op::Tan => x(0).tan(),
+                    Op::Eq => truth(x(0) == x(1)),
+                    Op::Neq => truth(x(0) != x(1)),
+                    Op::Lt => truth(x(0) < x(1)),
+                    Op::Gt => truth(x(0) > x(1)),
+                    Op::Leq => truth(x(0) <= x(1)),
+                    Op::Geq => truth(x(0) >= x(1)),
+                    Op::And => truth(args.iter().all(|a| a.eval(ctx) != 0.0)),
+                    Op::Or => truth(args.iter().any(|a| a.eval(ctx) != 0.0)),
+                    Op::Not => truth(x(0) == 0.0),
+                }
+            }
+        }
+    }
+}
+
+impl Expr {
+    pub fn species(i: usize) -> Expr {
+        Expr::Var(Var::Species(i))
+    }
+
+    pub fn parameter(i: usize) -> Expr {
+        Expr::Var(Var::Parameter(i))
+    }
+
+    pub fn pow(self, exponent: Expr) -> Expr {
+        Expr::Apply(Op::Power, vec![self, exponent])
+    }
+}
+
+impl From<f64> for Expr {
+    fn from(x: f64) -> Expr {
+        Expr::Number(x)
+    }
+}
+
+impl std::ops::Add for Expr {
+    type Output = Expr;
+    fn add(self, rhs: Expr) -> Expr {
+        Expr::Apply(Op::Plus, vec![self, rhs])
+    }
+}
+
+impl std::ops::Sub for Expr {
+    type Output = Expr;
+    fn sub(self, rhs: Expr) -> Expr {
+        Expr::Apply(Op::Minus, vec![self, rhs])
+    }
+}
+
+impl std::ops::Mul for Expr {
+    type Output = Expr;
+    fn mul(self, rhs: Expr) -> Expr {
+        Expr::Apply(Op::Times, vec![self, rhs])
+    }
+}
+
+impl std::ops::Div for Expr {
+    type Output = Expr;
+    fn div(self, rhs: Expr) -> Expr {
+        Expr::Apply(Op::Divide, vec![self, rhs])
+    }
+}
+
+fn parse_number(node: Node) -> Result<f64, Error> {
+    let parts: Vec<String> = node
+        .children()
+        .filter(|n| n.is_text())
+        .map(|n| n.text().unwrap_or_default().trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let number = |s: &str| {
+        s.parse::<f64>()
+            .map_err(|_| Error::Parse(format!("invalid number `{}`", s)))
+    };
+    match (node.attribute("type"), parts.as_slice()) {
+        (Some("e-notation"), [m, e]) => Ok(number(m)? * 10f64.powf(number(e)?)),
+        (Some("rational"), [n, d]) => Ok(number(n)? / number(d)?),
+        (_, [x]) => number(x),
+        _ => Err(Error::Parse("invalid <cn> element".to_string())),
+    }
+}
+
+fn parse_apply(node: Node) -> Result<Expr, Error> {
+    let mut children = node.children().filter(|n| n.is_element());
+    let head = children
+        .next()
+        .ok_or_else(|| Error::Parse("empty <apply> element".to_string()))?;
+    let mut args = Vec::new();
+    let mut qualifier = None;
+    for child in children {
+        match child.tag_name().name() {
+            tag @ ("logbase" | "degree") => {
+                if qualifier.is_some() {
+                    return Err(Error::Parse("more than one qualifier".to_string()));
+                }
+                let inner = child
+                    .children()
+                    .find(|n| n.is_element())
+                    .ok_or_else(|| Error::Parse("empty qualifier".to_string()))?;
+                qualifier = Some((tag, Expr::parse(inner)?));
+            }
+            _ => args.push(Expr::parse(child)?),
+        }
+    }
+    let name = head.tag_name().name();
+    if name == "ci" {
+        if let Some((tag, _)) = qualifier {
+            return Err(Error::Parse(format!("<{}> on a function call", tag)));
+        }
+        let function = head.text().unwrap_or_default().trim().to_string();
+        return Ok(Expr::Call(function, args));
+    }
+    let op = Op::from_tag(name)
+        .ok_or_else(|| Error::Parse(format!("unsupported MathML operator <{}>", name)))?;
+    let wrong_arity = |n: usize| Error::Parse(format!("<{}> with {} arguments", name, n));
+    match (op.qualifier(), qualifier) {
+        // A qualified operator takes a single plain argument, with or without its qualifier.
+        (Some(_), _) if args.len() != 1 => return Err(wrong_arity(args.len())),
+        (Some(expected), Some((tag, q))) if tag == expected => args.insert(0, q),
+        (_, Some((tag, _))) => {
+            return Err(Error::Parse(format!("<{}> qualifier on <{}>", tag, name)))
+        }
+        (_, None) => {}
+    }
+    let (min, max) = op.arity();
+    if args.len() < min || args.len() > max {
+        return Err(wrong_arity(args.len()));
+    }
+    Ok(Expr::Apply(op, args))
+}
+
+fn parse_piecewise(node: Node) -> Result<Expr, Error> {
+    let mut pieces = Vec::new();
+    let mut otherwise = None;
+    for child in node.children().filter(|n| n.is_element()) {
+        let inner: Vec<Node> = child.children().filter(|n| n.is_element()).collect();
+        match (child.tag_name().name(), inner.as_slice()) {
+            ("piece", [value, condition]) => {
+                pieces.push((Expr::parse(*value)?, Expr::parse(*condition)?))
+            }
+            ("otherwise", [value]) => otherwise = Some(Box::new(Expr::parse(*value)?)),
+            (other, _) => return Err(Error::Parse(format!("invalid <{}> in piecewise", other))),
+        }
+    }
+    Ok(Expr::Piecewise(pieces, otherwise))
+}
@@ -0,0 +1,162 @@
+//! Reading and writing SBML Level 3 files.
+//!
+//! Species, compartments, global and local parameters, reactions with MathML kinetic laws,
+//! function definitions, assignment rules and rate rules for species are supported. The
+//! resulting [`SbmlModel`] implements [`ode_solvers::System`] and [`Model`], so it runs through
+//! the same Dopri5/Dop853 steppers as the built-in circuits.
+//!
+//! The built-in circuits and any [`crate::reactions::ReactionNetwork`] implement [`ToSbml`] and
+//! can be handed to COPASI or Tellurium with [`write_file`].
+//!
+//! Species that are not declared with `hasOnlySubstanceUnits` are concentrations: their rate of
+//! change is the sum of the reaction rates divided by the size of their compartment.
+
+mod export;
+pub mod mathml;
+mod reader;
+mod writer;
+
+pub use export::ToSbml;
+pub use mathml::{Expr, Op, Var};
+pub use reader::{read, read_file};
+pub use writer::{write, write_file};
+
+use crate::model::{Model, Parameter, State, Time};
+use mathml::Context;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Compartment {
+    pub id: String,
+    pub size: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    pub id: String,
+    pub name: String,
+    pub compartment: usize,
+    /// Initial amount or concentration, in the units the species symbol stands for.
+    pub initial: f64,
+    pub only_substance_units: bool,
+    pub boundary_condition: bool,
+    pub constant: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SbmlReaction {
+    pub id: String,
+    pub reactants: Vec<(usize, f64)>,
+    pub products: Vec<(usize, f64)>,
+    pub rate: Expr,
+}
+
+/// A model read from SBML, with all symbols resolved to indices.
+#[derive(Clone, Debug)]
+pub struct SbmlModel {
+    pub id: String,
+    pub name: String,
+    pub compartments: Vec<Compartment>,
+    pub species: Vec<Species>,
+    /// Global parameters followed by local parameters, named `reaction.parameter`.
+    pub parameters: Vec<Parameter>,
+    pub reactions: Vec<SbmlReaction>,
+    /// Parameters computed from other values, in document order.
+    pub assignment_rules: Vec<(usize, Expr)>,
+    /// Species whose derivative is given explicitly.
+    pub rate_rules: Vec<(usize, Expr)>,
+}
+
+impl SbmlModel {
+    /// Parameter values with the assignment rules applied.
+    fn parameter_values(&self, t: Time, y: &[f64]) -> Vec<f64> {
+        let mut values: Vec<f64> = self.parameters.iter().map(|p| p.value).collect();
+        let compartments: Vec<f64> = self.compartments.iter().map(|c| c.size).collect();
+        for (target, expr) in &self.assignment_rules {
+            let ctx = Context {
+                t,
+                species: y,
+                parameters: &values,
+                compartments: &compartments,
+            };
+            values[*target] = expr.eval(&ctx);
+        }
+        values
+    }
+
+    /// Rates of all reactions at `(t, y)`.
+    pub fn reaction_rates(&self, t: Time, y: &[f64]) -> Vec<f64> {
+        let parameters = self.parameter_values(t, y);
+        let compartments: Vec<f64> = self.compartments.iter().map(|c| c.size).collect();
+        let ctx = Context {
+            t,
+            species: y,
+            parameters: &parameters,
+            compartments: &compartments,
+        };
+        self.reactions.iter().map(|r| r.rate.eval(&ctx)).collect()
+    }
+}
+
+impl ode_solvers::System<f64, State> for SbmlModel {
+    fn system(&self, t: Time, y: &State, dy: &mut State) {
+        dy.fill(0.0);
+        let rates = self.reaction_rates(t, y.as_slice());
+        for (reaction, rate) in self.reactions.iter().zip(rates) {
+            for (s, nu) in &reaction.reactants {
+                dy[*s] -= nu * rate;
+            }
+            for (s, nu) in &reaction.products {
+                dy[*s] += nu * rate;
+            }
+        }
+        for (i, species) in self.species.iter().enumerate() {
+            if species.boundary_condition || species.constant {
+                dy[i] = 0.0;
+            } else if !species.only_substance_units {
+                dy[i] /= self.compartments[species.compartment].size;
+            }
+        }
+        if !self.rate_rules.is_empty() {
+            let parameters = self.parameter_values(t, y.as_slice());
+            let compartments: Vec<f64> = self.compartments.iter().map(|c| c.size).collect();
+            let ctx = Context {
+                t,
+                species: y.as_slice(),
+                parameters: &parameters,
+                compartments: &compartments,
+            };
+            for (s, expr) in &self.rate_rules {
+                dy[*s] = expr.eval(&ctx);
+            }
+        }
+    }
+}
+
+impl Model for SbmlModel {
+    fn name(&self) -> &str {
+        if self.name.is_empty() {
+            &self.id
+        } else {
+            &self.name
+        }
+    }
+
+    fn species(&self) -> Vec<&str> {
+        self.species.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        self.parameters.clone()
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        self.parameters
+            .iter_mut()
+            .find(|p| p.name == name)
+            .map(|p| &mut p.value)
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_iterator(self.species.len(), self.species.iter().map(|s| s.initial))
+    }
+}
@@ -0,0 +1,269 @@
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+
+use super::mathml::{Context, Expr, Var};
+use super::{Compartment, SbmlModel, SbmlReaction, Species};
+use crate::model::{Error, Parameter};
+
+fn child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    node.children()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+/// Elements `item` inside the list element `list` of `node`.
+fn items<'a, 'i>(node: Node<'a, 'i>, list: &str, item: &'a str) -> Vec<Node<'a, 'i>> {
+    child(node, list)
+        .map(|l| {
+            l.children()
+                .filter(|n| n.is_element() && n.tag_name().name() == item)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn id(node: Node) -> Result<String, Error> {
+    node.attribute("id")
+        .map(str::to_string)
+        .ok_or_else(|| Error::Parse(format!("<{}> without id", node.tag_name().name())))
+}
+
+fn number(node: Node, attribute: &str) -> Result<Option<f64>, Error> {
+    node.attribute(attribute)
+        .map(|v| {
+            v.trim()
+                .parse::<f64>()
+                .map_err(|_| Error::Parse(format!("invalid {} `{}`", attribute, v)))
+        })
+        .transpose()
+}
+
+fn flag(node: Node, attribute: &str) -> bool {
+    node.attribute(attribute) == Some("true")
+}
+
+fn math(node: Node) -> Result<Expr, Error> {
+    let math = child(node, "math")
+        .ok_or_else(|| Error::Parse(format!("<{}> without <math>", node.tag_name().name())))?;
+    Expr::from_math(math)
+}
+
+fn parameter(name: &str, value: f64) -> Parameter {
+    let min = if value >= 0.0 { 0.0 } else { f64::NEG_INFINITY };
+    Parameter::new(name, value, value, min, f64::INFINITY)
+}
+
+/// Reads an SBML file.
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<SbmlModel, Error> {
+    let xml = std::fs::read_to_string(path).map_err(|e| Error::Io(e.to_string()))?;
+    read(&xml)
+}
+
+/// Reads an SBML document from a string.
+pub fn read(xml: &str) -> Result<SbmlModel, Error> {
+    let document = Document::parse(xml).map_err(|e| Error::Parse(e.to_string()))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "sbml" {
+        return Err(Error::Parse("root element is not <sbml>".to_string()));
+    }
+    let model = child(root, "model").ok_or_else(|| Error::Parse("missing <model>".to_string()))?;
+    if !items(model, "listOfEvents", "event").is_empty() {
+        return Err(Error::Parse("events are not supported".to_string()));
+    }
+
+    let mut functions = Vec::new();
+    for f in items(model, "listOfFunctionDefinitions", "functionDefinition") {
+        let lambda = child(f, "math")
+            .and_then(|m| child(m, "lambda"))
+            .ok_or_else(|| Error::Parse("function definition without <lambda>".to_string()))?;
+        let params = lambda
+            .children()
+            .filter(|n| n.is_element() && n.tag_name().name() == "bvar")
+            .map(|b| {
+                child(b, "ci")
+                    .and_then(|ci| ci.text())
+                    .map(|s| s.trim().to_string())
+                    .ok_or_else(|| Error::Parse("invalid <bvar>".to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let body = lambda
+            .children()
+            .rfind(|n| n.is_element() && n.tag_name().name() != "bvar")
+            .ok_or_else(|| Error::Parse("empty <lambda>".to_string()))?;
+        functions.push((id(f)?, params, Expr::parse(body)?));
+    }
+
+    let mut compartments = Vec::new();
+    for c in items(model, "listOfCompartments", "compartment") {
+        compartments.push(Compartment {
+            id: id(c)?,
+            size: number(c, "size")?.unwrap_or(1.0),
+        });
+    }
+
+    let mut species = Vec::new();
+    for s in items(model, "listOfSpecies", "species") {
+        let compartment_id = s.attribute("compartment").unwrap_or_default();
+        let compartment = compartments
+            .iter()
+            .position(|c| c.id == compartment_id)
+            .ok_or_else(|| Error::Parse(format!("unknown compartment `{}`", compartment_id)))?;
+        let size = compartments[compartment].size;
+        let only_substance_units = flag(s, "hasOnlySubstanceUnits");
+        let initial = match (
+            number(s, "initialAmount")?,
+            number(s, "initialConcentration")?,
+        ) {
+            (Some(amount), _) if only_substance_units => amount,
+            (Some(amount), _) => amount / size,
+            (None, Some(concentration)) if only_substance_units => concentration * size,
+            (None, Some(concentration)) => concentration,
+            (None, None) => 0.0,
+        };
+        let id = id(s)?;
+        species.push(Species {
+            name: s.attribute("name").unwrap_or(&id).to_string(),
+            id,
+            compartment,
+            initial,
+            only_substance_units,
+            boundary_condition: flag(s, "boundaryCondition"),
+            constant: flag(s, "constant"),
+        });
+    }
+
+    let mut parameters = Vec::new();
+    for p in items(model, "listOfParameters", "parameter") {
+        parameters.push(parameter(&id(p)?, number(p, "value")?.unwrap_or(0.0)));
+    }
+    let n_global = parameters.len();
+
+    let lookup_global = |name: &str| -> Option<Var> {
+        if let Some(i) = species.iter().position(|s| s.id == name) {
+            Some(Var::Species(i))
+        } else if let Some(i) = parameters.iter().position(|p| p.name == name) {
+            Some(Var::Parameter(i))
+        } else {
+            compartments
+                .iter()
+                .position(|c| c.id == name)
+                .map(Var::Compartment)
+        }
+    };
+    let species_id = |name: Option<&str>| -> Result<usize, Error> {
+        let name = name.unwrap_or_default();
+        species
+            .iter()
+            .position(|s| s.id == name)
+            .ok_or_else(|| Error::Parse(format!("unknown species `{}`", name)))
+    };
+
+    let mut assignment_rules = Vec::new();
+    let mut rate_rules = Vec::new();
+    if let Some(rules) = child(model, "listOfRules") {
+        for rule in rules.children().filter(|n| n.is_element()) {
+            let variable = rule.attribute("variable").unwrap_or_default();
+            let expr = math(rule)?.inline(&functions)?.resolve(&lookup_global)?;
+            match (rule.tag_name().name(), lookup_global(variable)) {
+                ("assignmentRule", Some(Var::Parameter(i))) => assignment_rules.push((i, expr)),
+                ("rateRule", Some(Var::Species(i))) => rate_rules.push((i, expr)),
+                (kind, _) => {
+                    return Err(Error::Parse(format!(
+                        "{} for `{}` is not supported",
+                        kind, variable
+                    )))
+                }
+            }
+        }
+    }
+
+    let mut local_parameters = Vec::new();
+    let mut reactions = Vec::new();
+    for r in items(model, "listOfReactions", "reaction") {
+        let reaction_id = id(r)?;
+        let stoichiometry = |list: &str| -> Result<Vec<(usize, f64)>, Error> {
+            items(r, list, "speciesReference")
+                .into_iter()
+                .map(|s| {
+                    Ok((
+                        species_id(s.attribute("species"))?,
+                        number(s, "stoichiometry")?.unwrap_or(1.0),
+                    ))
+                })
+                .collect()
+        };
+        let reactants = stoichiometry("listOfReactants")?;
+        let products = stoichiometry("listOfProducts")?;
+
+        let law = child(r, "kineticLaw")
+            .ok_or_else(|| Error::Parse(format!("reaction `{}` has no kineticLaw", reaction_id)))?;
+        let mut locals = Vec::new();
+        for p in items(law, "listOfLocalParameters", "localParameter")
+            .into_iter()
+            .chain(items(law, "listOfParameters", "parameter"))
+        {
+            let name = id(p)?;
+            locals.push((name.clone(), n_global + local_parameters.len()));
+            local_parameters.push(parameter(
+                &format!("{}.{}", reaction_id, name),
+                number(p, "value")?.unwrap_or(0.0),
+            ));
+        }
+        let lookup = |name: &str| {
+            locals
+                .iter()
+                .find(|(local, _)| local == name)
+                .map(|(_, i)| Var::Parameter(*i))
+                .or_else(|| lookup_global(name))
+        };
+        let rate = math(law)?.inline(&functions)?.resolve(&lookup)?;
+        reactions.push(SbmlReaction {
+            id: reaction_id,
+            reactants,
+            products,
+            rate,
+        });
+    }
+
+    let initial_assignments = items(model, "listOfInitialAssignments", "initialAssignment")
+        .into_iter()
+        .map(|a| {
+            let symbol = a.attribute("symbol").unwrap_or_default();
+            let target = lookup_global(symbol)
+                .ok_or_else(|| Error::Parse(format!("unknown symbol `{}`", symbol)))?;
+            Ok((
+                target,
+                math(a)?.inline(&functions)?.resolve(&lookup_global)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    parameters.extend(local_parameters);
+    for (target, expr) in initial_assignments {
+        let y: Vec<f64> = species.iter().map(|s| s.initial).collect();
+        let p: Vec<f64> = parameters.iter().map(|p| p.value).collect();
+        let c: Vec<f64> = compartments.iter().map(|c| c.size).collect();
+        let value = expr.eval(&Context {
+            t: 0.0,
+            species: &y,
+            parameters: &p,
+            compartments: &c,
+        });
+        match target {
+            Var::Species(i) => species[i].initial = value,
+            Var::Parameter(i) => parameters[i] = parameter(&parameters[i].name, value),
+            Var::Compartment(i) => compartments[i].size = value,
+        }
+    }
+
+    Ok(SbmlModel {
+        id: model.attribute("id").unwrap_or_default().to_string(),
+        name: model.attribute("name").unwrap_or_default().to_string(),
+        compartments,
+        species,
+        parameters,
+        reactions,
+        assignment_rules,
+        rate_rules,
+    })
+}
@@ -0,0 +1,246 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use super::mathml::{Expr, Var};
+use super::SbmlModel;
+use crate::model::Error;
+
+const MATHML: &str = "http://www.w3.org/1998/Math/MathML";
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Name under which a parameter appears in the math of `reaction`.
+///
+/// Local parameters are stored as `reaction.parameter` and written back as local parameters.
+fn parameter_name<'a>(model: &'a SbmlModel, i: usize, reaction: Option<&str>) -> &'a str {
+    let name = model.parameters[i].name.as_str();
+    match (reaction, name.split_once('.')) {
+        (Some(r), Some((owner, local))) if owner == r => local,
+        _ => name,
+    }
+}
+
+fn write_expr(out: &mut String, model: &SbmlModel, expr: &Expr, reaction: Option<&str>) {
+    match expr {
+        Expr::Number(x) if x.is_nan() => out.push_str("<notanumber/>"),
+        Expr::Number(x) if x.is_infinite() && *x > 0.0 => out.push_str("<infinity/>"),
+        Expr::Number(x) if x.is_infinite() => {
+            out.push_str("<apply><minus/><infinity/></apply>")
+        }
+        Expr::Number(x) => {
+            let _ = write!(out, "<cn>{}</cn>", x);
+        }
+        Expr::Symbol(s) | Expr::Call(s, _) => {
+            let _ = write!(out, "<ci>{}</ci>", escape(s));
+        }
+        Expr::Var(var) => {
+            let name = match var {
+                Var::Species(i) => model.species[*i].id.as_str(),
+                Var::Parameter(i) => parameter_name(model, *i, reaction),
+                Var::Compartment(i) => model.compartments[*i].id.as_str(),
+            };
+            let _ = write!(out, "<ci>{}</ci>", escape(name));
+        }
+        Expr::Time => out.push_str(
+            "<csymbol encoding=\"text\" definitionURL=\"http://www.sbml.org/sbml/symbols/time\">t</csymbol>",
+        ),
+        Expr::Apply(op, args) => {
+            let _ = write!(out, "<apply><{}/>", op.tag());
+            let (qualifier, args) = match (op.qualifier(), args.as_slice()) {
+                (Some(tag), [q, x]) => (Some((tag, q)), std::slice::from_ref(x)),
+                _ => (None, args.as_slice()),
+            };
+            if let Some((tag, q)) = qualifier {
+                let _ = write!(out, "<{}>", tag);
+                write_expr(out, model, q, reaction);
+                let _ = write!(out, "</{}>", tag);
+            }
+            for arg in args {
+                write_expr(out, model, arg, reaction);
+            }
+            out.push_str("</apply>");
+        }
+        Expr::Piecewise(pieces, otherwise) => {
+            out.push_str("<piecewise>");
+            for (value, condition) in pieces {
+                out.push_str("<piece>");
+                write_expr(out, model, value, reaction);
+                write_expr(out, model, condition, reaction);
+                out.push_str("</piece>");
+            }
+            if let Some(o) = otherwise {
+                out.push_str("<otherwise>");
+                write_expr(out, model, o, reaction);
+                out.push_str("</otherwise>");
+            }
+            out.push_str("</piecewise>");
+        }
+    }
+}
+
+fn write_math(
+    out: &mut String,
+    model: &SbmlModel,
+    expr: &Expr,
+    reaction: Option<&str>,
+    indent: &str,
+) {
+    let _ = write!(out, "{}<math xmlns=\"{}\">", indent, MATHML);
+    write_expr(out, model, expr, reaction);
+    out.push_str("</math>\n");
+}
+
+fn write_references(out: &mut String, model: &SbmlModel, list: &str, references: &[(usize, f64)]) {
+    if references.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "        <{}>", list);
+    for (s, nu) in references {
+        let _ = writeln!(
+            out,
+            "          <speciesReference species=\"{}\" stoichiometry=\"{}\" constant=\"true\"/>",
+            escape(&model.species[*s].id),
+            nu
+        );
+    }
+    let _ = writeln!(out, "        </{}>", list);
+}
+
+/// Serializes a model as an SBML Level 3 Version 2 document.
+pub fn write(model: &SbmlModel) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<sbml xmlns=\"http://www.sbml.org/sbml/level3/version2/core\" level=\"3\" version=\"2\">\n",
+    );
+    let _ = writeln!(
+        out,
+        "  <model id=\"{}\" name=\"{}\">",
+        escape(&model.id),
+        escape(&model.name)
+    );
+
+    out.push_str("    <listOfCompartments>\n");
+    for c in &model.compartments {
+        let _ = writeln!(
+            out,
+            "      <compartment id=\"{}\" spatialDimensions=\"3\" size=\"{}\" constant=\"true\"/>",
+            escape(&c.id),
+            c.size
+        );
+    }
+    out.push_str("    </listOfCompartments>\n");
+
+    out.push_str("    <listOfSpecies>\n");
+    for s in &model.species {
+        let initial = if s.only_substance_units {
+            "initialAmount"
+        } else {
+            "initialConcentration"
+        };
+        let _ = writeln!(
+            out,
+            "      <species id=\"{}\" name=\"{}\" compartment=\"{}\" {}=\"{}\" hasOnlySubstanceUnits=\"{}\" boundaryCondition=\"{}\" constant=\"{}\"/>",
+            escape(&s.id),
+            escape(&s.name),
+            escape(&model.compartments[s.compartment].id),
+            initial,
+            s.initial,
+            s.only_substance_units,
+            s.boundary_condition,
+            s.constant
+        );
+    }
+    out.push_str("    </listOfSpecies>\n");
+
+    let globals: Vec<usize> = (0..model.parameters.len())
+        .filter(|i| !model.parameters[*i].name.contains('.'))
+        .collect();
+    if !globals.is_empty() {
+        out.push_str("    <listOfParameters>\n");
+        for i in globals {
+            let constant = !model.assignment_rules.iter().any(|(p, _)| *p == i);
+            let _ = writeln!(
+                out,
+                "      <parameter id=\"{}\" value=\"{}\" constant=\"{}\"/>",
+                escape(&model.parameters[i].name),
+                model.parameters[i].value,
+                constant
+            );
+        }
+        out.push_str("    </listOfParameters>\n");
+    }
+
+    if !model.assignment_rules.is_empty() || !model.rate_rules.is_empty() {
+        out.push_str("    <listOfRules>\n");
+        for (p, expr) in &model.assignment_rules {
+            let _ = writeln!(
+                out,
+                "      <assignmentRule variable=\"{}\">",
+                escape(&model.parameters[*p].name)
+            );
+            write_math(&mut out, model, expr, None, "        ");
+            out.push_str("      </assignmentRule>\n");
+        }
+        for (s, expr) in &model.rate_rules {
+            let _ = writeln!(
+                out,
+                "      <rateRule variable=\"{}\">",
+                escape(&model.species[*s].id)
+            );
+            write_math(&mut out, model, expr, None, "        ");
+            out.push_str("      </rateRule>\n");
+        }
+        out.push_str("    </listOfRules>\n");
+    }
+
+    if !model.reactions.is_empty() {
+        out.push_str("    <listOfReactions>\n");
+        for r in &model.reactions {
+            let _ = writeln!(
+                out,
+                "      <reaction id=\"{}\" reversible=\"false\">",
+                escape(&r.id)
+            );
+            write_references(&mut out, model, "listOfReactants", &r.reactants);
+            write_references(&mut out, model, "listOfProducts", &r.products);
+            out.push_str("        <kineticLaw>\n");
+            write_math(&mut out, model, &r.rate, Some(&r.id), "          ");
+            let prefix = format!("{}.", r.id);
+            let locals: Vec<_> = model
+                .parameters
+                .iter()
+                .filter_map(|p| p.name.strip_prefix(&prefix).map(|local| (local, p.value)))
+                .collect();
+            if !locals.is_empty() {
+                out.push_str("          <listOfLocalParameters>\n");
+                for (local, value) in locals {
+                    let _ = writeln!(
+                        out,
+                        "            <localParameter id=\"{}\" value=\"{}\"/>",
+                        escape(local),
+                        value
+                    );
+                }
+                out.push_str("          </listOfLocalParameters>\n");
+            }
+            out.push_str("        </kineticLaw>\n");
+            out.push_str("      </reaction>\n");
+        }
+        out.push_str("    </listOfReactions>\n");
+    }
+
+    out.push_str("  </model>\n");
+    out.push_str("</sbml>\n");
+    out
+}
+
+/// Writes a model to an SBML file.
+pub fn write_file<P: AsRef<Path>>(path: P, model: &SbmlModel) -> Result<(), Error> {
+    std::fs::write(path, write(model)).map_err(|e| Error::Io(e.to_string()))
+}
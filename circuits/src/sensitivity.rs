@@ -0,0 +1,174 @@
+//! Local sensitivities `dy/dp` of trajectories with respect to parameters.
+//!
+//! [`Method::Forward`] integrates the forward sensitivity equations `S' = J S + df/dp` alongside
+//! the model, with `J` and `df/dp` by central differences. [`Method::FiniteDifference`]
+//! integrates the model again with every parameter nudged up and down, which is slower but
+//! works for models whose right hand side is not smooth.
+//!
+//! [`Sensitivities::normalized`] gives the logarithmic sensitivities `dlog y / dlog p` and
+//! [`Sensitivities::columns`] names every column for a table such as a polars `DataFrame`.
+
+use nalgebra::DMatrix;
+use ode_solvers::{Dopri5, System};
+
+use crate::model::{Error, Model, State, Time};
+use crate::stability::{jacobian_at, parameter_derivative};
+
+const RTOL: f64 = 1e-8;
+const ATOL: f64 = 1e-8;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Method {
+    Forward,
+    FiniteDifference,
+}
+
+/// Sensitivity coefficients of every species to every parameter over time.
+#[derive(Clone, Debug)]
+pub struct Sensitivities {
+    pub species: Vec<String>,
+    pub parameters: Vec<String>,
+    /// Parameter values the sensitivities were computed at.
+    pub values: Vec<f64>,
+    pub t: Vec<Time>,
+    pub y: Vec<State>,
+    /// `dy_i / dp_j` at each time, as a species by parameter matrix.
+    pub coefficients: Vec<DMatrix<f64>>,
+}
+
+impl Sensitivities {
+    /// Logarithmic sensitivities `(p_j / y_i) dy_i / dp_j`, zero where `y_i` is below
+    /// the absolute integration tolerance.
+    pub fn normalized(&self) -> Vec<DMatrix<f64>> {
+        self.coefficients
+            .iter()
+            .zip(&self.y)
+            .map(|(s, y)| {
+                DMatrix::from_fn(s.nrows(), s.ncols(), |i, j| {
+                    if y[i].abs() > ATOL {
+                        s[(i, j)] * self.values[j] / y[i]
+                    } else {
+                        0.0
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Named columns `t`, then one column per species and parameter, ready for a DataFrame.
+    ///
+    /// Columns are named `d<species>/d<parameter>`, or `dlog<species>/dlog<parameter>` for
+    /// normalized sensitivities.
+    pub fn columns(&self, normalized: bool) -> Vec<(String, Vec<f64>)> {
+        let (matrices, prefix) = if normalized {
+            (self.normalized(), "dlog")
+        } else {
+            (self.coefficients.clone(), "d")
+        };
+        let mut columns = vec![("t".to_string(), self.t.clone())];
+        for (i, species) in self.species.iter().enumerate() {
+            for (j, parameter) in self.parameters.iter().enumerate() {
+                columns.push((
+                    format!("{}{}/{}{}", prefix, species, prefix, parameter),
+                    matrices.iter().map(|s| s[(i, j)]).collect(),
+                ));
+            }
+        }
+        columns
+    }
+}
+
+/// The model with its state extended by the columns of the sensitivity matrix.
+struct Forward<'a, M> {
+    model: &'a M,
+    parameters: &'a [&'a str],
+}
+
+impl<M: Model + Clone> System<f64, State> for Forward<'_, M> {
+    fn system(&self, t: Time, u: &State, du: &mut State) {
+        let n = self.model.dim();
+        let y = u.rows(0, n).into_owned();
+        du.rows_mut(0, n).copy_from(&self.model.rhs(t, &y));
+        let j = jacobian_at(self.model, t, &y);
+        for (k, parameter) in self.parameters.iter().enumerate() {
+            let s = u.rows(n * (k + 1), n);
+            let dfdp = parameter_derivative(self.model, parameter, t, &y)
+                .expect("parameters are checked before integration");
+            du.rows_mut(n * (k + 1), n).copy_from(&(&j * s + dfdp));
+        }
+    }
+}
+
+fn trajectory<S: System<f64, State>>(
+    system: S,
+    y0: State,
+    t_end: f64,
+    dx: f64,
+) -> Result<(Vec<f64>, Vec<State>), Error> {
+    let mut stepper = Dopri5::new(system, 0.0, t_end, dx, y0, RTOL, ATOL);
+    stepper
+        .integrate()
+        .map_err(|e| Error::NoConvergence(e.to_string()))?;
+    Ok((stepper.x_out().clone(), stepper.y_out().clone()))
+}
+
+/// Sensitivities of `model` from its initial state to `parameters`, sampled every `dx` until
+/// `t_end`.
+pub fn sensitivities<M: Model + Clone>(
+    model: &M,
+    parameters: &[&str],
+    t_end: f64,
+    dx: f64,
+    method: Method,
+) -> Result<Sensitivities, Error> {
+    let values = parameters
+        .iter()
+        .map(|p| {
+            model
+                .get(p)
+                .ok_or_else(|| Error::UnknownParameter(p.to_string()))
+        })
+        .collect::<Result<Vec<f64>, Error>>()?;
+    let n = model.dim();
+    let m = parameters.len();
+    let y0 = model.initial_state();
+
+    let (t, y, coefficients) = match method {
+        Method::Forward => {
+            let mut u0 = State::zeros(n * (m + 1));
+            u0.rows_mut(0, n).copy_from(&y0);
+            let (t, u) = trajectory(Forward { model, parameters }, u0, t_end, dx)?;
+            let y = u.iter().map(|u| u.rows(0, n).into_owned()).collect();
+            let s = u
+                .iter()
+                .map(|u| DMatrix::from_column_slice(n, m, &u.as_slice()[n..]))
+                .collect();
+            (t, y, s)
+        }
+        Method::FiniteDifference => {
+            let (t, y) = trajectory(model.clone(), y0.clone(), t_end, dx)?;
+            let mut s = vec![DMatrix::zeros(n, m); t.len()];
+            for (j, (parameter, p)) in parameters.iter().zip(&values).enumerate() {
+                let h = f64::EPSILON.powf(0.25) * p.abs().max(f64::EPSILON.sqrt());
+                let mut shifted = model.clone();
+                *shifted.parameter_mut(parameter).unwrap() = p + h;
+                let (_, up) = trajectory(shifted.clone(), y0.clone(), t_end, dx)?;
+                *shifted.parameter_mut(parameter).unwrap() = p - h;
+                let (_, down) = trajectory(shifted, y0.clone(), t_end, dx)?;
+                for (k, (up, down)) in up.iter().zip(&down).enumerate().take(t.len()) {
+                    s[k].set_column(j, &((up - down) / (2.0 * h)));
+                }
+            }
+            (t, y, s)
+        }
+    };
+
+    Ok(Sensitivities {
+        species: model.species().iter().map(|s| s.to_string()).collect(),
+        parameters: parameters.iter().map(|p| p.to_string()).collect(),
+        values,
+        t,
+        y,
+        coefficients,
+    })
+}
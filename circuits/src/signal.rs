@@ -0,0 +1,186 @@
+//! Input signals as functions of time, such as the inducer `s` of the autorepressor.
+//!
+//! A [`Signal`] is a constant, a step, a pulse or pulse train, a ramp, a sinusoid or a piecewise
+//! linear [`Signal::Table`], read from a CSV file of `t,value` lines with [`Signal::from_csv`].
+
+use std::path::Path;
+
+use crate::model::Error;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Signal {
+    Constant(f64),
+    /// Jumps from `before` to `after` at time `t`.
+    Step {
+        t: f64,
+        before: f64,
+        after: f64,
+    },
+    /// `high` between `t_on` and `t_off`, `low` otherwise.
+    Pulse {
+        t_on: f64,
+        t_off: f64,
+        low: f64,
+        high: f64,
+    },
+    /// Pulses of length `width` repeating every `period` from `t_on` on.
+    PulseTrain {
+        t_on: f64,
+        width: f64,
+        period: f64,
+        low: f64,
+        high: f64,
+    },
+    /// Linear change from `from` to `to` between `t_start` and `t_end`.
+    Ramp {
+        t_start: f64,
+        t_end: f64,
+        from: f64,
+        to: f64,
+    },
+    /// `mean + amplitude sin(2 pi t / period + phase)`.
+    Sine {
+        mean: f64,
+        amplitude: f64,
+        period: f64,
+        phase: f64,
+    },
+    /// Piecewise linear interpolation of `(t, value)` points sorted by time.
+    Table(Vec<(f64, f64)>),
+}
+
+impl Default for Signal {
+    fn default() -> Self {
+        Signal::Constant(0.0)
+    }
+}
+
+impl Signal {
+    /// Switches from 0 to `high` at time `t`.
+    pub fn step_on(t: f64, high: f64) -> Self {
+        Signal::Step {
+            t,
+            before: 0.0,
+            after: high,
+        }
+    }
+
+    /// Switches from `high` to 0 at time `t`.
+    pub fn step_off(t: f64, high: f64) -> Self {
+        Signal::Step {
+            t,
+            before: high,
+            after: 0.0,
+        }
+    }
+
+    /// Value of the signal at time `t`.
+    pub fn value(&self, t: f64) -> f64 {
+        match self {
+            Signal::Constant(v) => *v,
+            Signal::Step {
+                t: t0,
+                before,
+                after,
+            } => {
+                if t < *t0 {
+                    *before
+                } else {
+                    *after
+                }
+            }
+            Signal::Pulse {
+                t_on,
+                t_off,
+                low,
+                high,
+            } => {
+                if *t_on <= t && t < *t_off {
+                    *high
+                } else {
+                    *low
+                }
+            }
+            Signal::PulseTrain {
+                t_on,
+                width,
+                period,
+                low,
+                high,
+            } => {
+                if t >= *t_on && (t - t_on).rem_euclid(*period) < *width {
+                    *high
+                } else {
+                    *low
+                }
+            }
+            Signal::Ramp {
+                t_start,
+                t_end,
+                from,
+                to,
+            } => {
+                if t <= *t_start {
+                    *from
+                } else if t >= *t_end {
+                    *to
+                } else {
+                    from + (to - from) * (t - t_start) / (t_end - t_start)
+                }
+            }
+            Signal::Sine {
+                mean,
+                amplitude,
+                period,
+                phase,
+            } => mean + amplitude * (2.0 * std::f64::consts::PI * t / period + phase).sin(),
+            Signal::Table(points) => interpolate(points, t),
+        }
+    }
+
+    /// Reads a piecewise linear signal from CSV lines of `t,value`.
+    ///
+    /// A header line and blank lines are skipped.
+    pub fn from_csv_str(csv: &str) -> Result<Self, Error> {
+        let mut points = Vec::new();
+        for (i, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut fields = line.split(',').map(str::trim);
+            let t = fields.next().unwrap_or_default();
+            let value = fields.next().unwrap_or_default();
+            match (t.parse::<f64>(), value.parse::<f64>()) {
+                (Ok(t), Ok(value)) => points.push((t, value)),
+                _ if i == 0 => continue,
+                _ => return Err(Error::Parse(format!("invalid signal line `{}`", line))),
+            }
+        }
+        if points.is_empty() {
+            return Err(Error::Parse("signal table is empty".to_string()));
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Signal::Table(points))
+    }
+
+    /// Reads a piecewise linear signal from a CSV file of `t,value` lines.
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let csv = std::fs::read_to_string(path).map_err(|e| Error::Io(e.to_string()))?;
+        Signal::from_csv_str(&csv)
+    }
+}
+
+fn interpolate(points: &[(f64, f64)], t: f64) -> f64 {
+    let i = points.partition_point(|(ti, _)| *ti <= t);
+    match (i, points.len()) {
+        (_, 0) => 0.0,
+        (0, _) => points[0].1,
+        (i, n) if i == n => points[n - 1].1,
+        (i, _) => {
+            let (t0, v0) = points[i - 1];
+            let (t1, v1) = points[i];
+            v0 + (v1 - v0) * (t - t0) / (t1 - t0)
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Steady states and their linear stability for any [`System`].
+//!
+//! Steady states are found with a damped Newton iteration from the guess, which converges to
+//! nearby fixed points of any stability. When Newton fails the guess is continued by
+//! pseudo-transient continuation: each iteration solves `(I / dt - J) dy = f(y)`, an implicit
+//! Euler step of the dynamics for small `dt` and a Newton step for large `dt`, so the iteration
+//! follows the flow towards an attractor and converges quadratically near it.
+//!
+//! Systems are treated as autonomous and evaluated at `t = 0`, so time-dependent inputs are
+//! frozen at their initial value.
+
+use std::fmt;
+
+use nalgebra::{Complex, DMatrix};
+use ode_solvers::System;
+
+use crate::model::{Error, Model, State, Time};
+
+const MAX_ITERATIONS: usize = 500;
+/// Newton steps smaller than this, relative to the state, count as converged.
+const TOLERANCE: f64 = 1e-10;
+/// Real parts smaller than this, relative to the largest eigenvalue, count as zero.
+const ZERO: f64 = 1e-9;
+
+fn rhs<S: System<f64, State>>(system: &S, y: &State) -> State {
+    rhs_at(system, 0.0, y)
+}
+
+fn rhs_at<S: System<f64, State>>(system: &S, t: Time, y: &State) -> State {
+    let mut dy = State::zeros(y.len());
+    system.system(t, y, &mut dy);
+    dy
+}
+
+/// Jacobian of the right hand side at `y` by central differences.
+pub fn jacobian<S: System<f64, State>>(system: &S, y: &State) -> DMatrix<f64> {
+    jacobian_at(system, 0.0, y)
+}
+
+/// Jacobian of the right hand side at `(t, y)` by central differences.
+pub(crate) fn jacobian_at<S: System<f64, State>>(system: &S, t: Time, y: &State) -> DMatrix<f64> {
+    let n = y.len();
+    let mut jacobian = DMatrix::zeros(n, n);
+    let mut shifted = y.clone();
+    for j in 0..n {
+        let h = f64::EPSILON.cbrt() * y[j].abs().max(1.0);
+        shifted[j] = y[j] + h;
+        let forward = rhs_at(system, t, &shifted);
+        shifted[j] = y[j] - h;
+        let backward = rhs_at(system, t, &shifted);
+        shifted[j] = y[j];
+        jacobian.set_column(j, &((forward - backward) / (2.0 * h)));
+    }
+    jacobian
+}
+
+/// Derivative of the right hand side at `(t, y)` with respect to `parameter` by central
+/// differences.
+pub(crate) fn parameter_derivative<M: Model + Clone>(
+    model: &M,
+    parameter: &str,
+    t: Time,
+    y: &State,
+) -> Result<State, Error> {
+    let mut shifted = model.clone();
+    let value = shifted
+        .parameter_mut(parameter)
+        .ok_or_else(|| Error::UnknownParameter(parameter.to_string()))?;
+    let p = *value;
+    let h = f64::EPSILON.cbrt() * p.abs().max(f64::EPSILON.sqrt());
+    *value = p + h;
+    let forward = rhs_at(&shifted, t, y);
+    *shifted.parameter_mut(parameter).unwrap() = p - h;
+    let backward = rhs_at(&shifted, t, y);
+    Ok((forward - backward) / (2.0 * h))
+}
+
+fn converged(newton: &State, y: &State) -> bool {
+    newton.norm() <= TOLERANCE * (1.0 + y.norm())
+}
+
+/// Newton iteration with a backtracking line search on the residual.
+fn newton<S: System<f64, State>>(system: &S, guess: &State) -> Option<State> {
+    let mut y = guess.clone();
+    let mut f = rhs(system, &y);
+    for _ in 0..MAX_ITERATIONS / 10 {
+        let step = jacobian(system, &y).lu().solve(&f)?;
+        if converged(&step, &y) {
+            return Some(y - step);
+        }
+        let mut lambda = 1.0;
+        loop {
+            let next = &y - &step * lambda;
+            let f_next = rhs(system, &next);
+            if f_next.norm() < f.norm() {
+                y = next;
+                f = f_next;
+                break;
+            }
+            lambda /= 2.0;
+            if lambda < 1e-4 {
+                return None;
+            }
+        }
+    }
+    None
+}
+
+/// Steady state reached from `guess`.
+pub fn steady_state<S: System<f64, State>>(system: &S, guess: &State) -> Result<State, Error> {
+    if let Some(y) = newton(system, guess) {
+        return Ok(y);
+    }
+    let n = guess.len();
+    let mut y = guess.clone();
+    let mut f = rhs(system, &y);
+    if !f.iter().all(|v| v.is_finite()) {
+        return Err(Error::NoConvergence(
+            "right hand side is not finite at the initial guess".to_string(),
+        ));
+    }
+    let mut dt = 0.1 / (1.0 + jacobian(system, &y).amax());
+    for _ in 0..MAX_ITERATIONS {
+        let j = jacobian(system, &y);
+        if let Some(newton) = j.clone().lu().solve(&f) {
+            if converged(&newton, &y) {
+                return Ok(y - newton);
+            }
+        }
+        let a = DMatrix::identity(n, n) / dt - j;
+        let dy = a
+            .lu()
+            .solve(&f)
+            .ok_or_else(|| Error::NoConvergence("singular iteration matrix".to_string()))?;
+        let next = &y + dy;
+        let f_next = rhs(system, &next);
+        let (norm, norm_next) = (f.norm(), f_next.norm());
+        if !norm_next.is_finite() || norm_next > 10.0 * norm {
+            dt /= 10.0;
+            continue;
+        }
+        dt = (dt * (norm / norm_next).clamp(2.0, 10.0)).min(1e15);
+        y = next;
+        f = f_next;
+    }
+    Err(Error::NoConvergence(format!(
+        "no steady state after {} iterations",
+        MAX_ITERATIONS
+    )))
+}
+
+/// Type of a fixed point according to the eigenvalues of its Jacobian.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stability {
+    StableNode,
+    StableFocus,
+    UnstableNode,
+    UnstableFocus,
+    /// Eigenvalues with positive and negative real parts.
+    Saddle,
+    /// A complex pair on the imaginary axis, where a limit cycle can appear.
+    HopfCandidate,
+    /// A real eigenvalue at zero, as at a fold.
+    NonHyperbolic,
+}
+
+impl Stability {
+    /// Classifies a fixed point from the eigenvalues of its Jacobian.
+    pub fn classify(eigenvalues: &[Complex<f64>]) -> Stability {
+        let scale = eigenvalues.iter().map(|l| l.norm()).fold(0.0, f64::max);
+        let zero = |x: f64| x.abs() <= ZERO * scale || scale == 0.0;
+        let oscillating = eigenvalues.iter().any(|l| !zero(l.im));
+        if eigenvalues.iter().any(|l| zero(l.re) && !zero(l.im)) {
+            Stability::HopfCandidate
+        } else if eigenvalues.iter().any(|l| zero(l.re)) {
+            Stability::NonHyperbolic
+        } else if eigenvalues.iter().all(|l| l.re < 0.0) {
+            if oscillating {
+                Stability::StableFocus
+            } else {
+                Stability::StableNode
+            }
+        } else if eigenvalues.iter().all(|l| l.re > 0.0) {
+            if oscillating {
+                Stability::UnstableFocus
+            } else {
+                Stability::UnstableNode
+            }
+        } else {
+            Stability::Saddle
+        }
+    }
+
+    pub fn is_stable(&self) -> bool {
+        matches!(self, Stability::StableNode | Stability::StableFocus)
+    }
+}
+
+impl fmt::Display for Stability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Stability::StableNode => "stable node",
+            Stability::StableFocus => "stable focus",
+            Stability::UnstableNode => "unstable node",
+            Stability::UnstableFocus => "unstable focus",
+            Stability::Saddle => "saddle",
+            Stability::HopfCandidate => "Hopf candidate",
+            Stability::NonHyperbolic => "non-hyperbolic",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A steady state with its linearization.
+#[derive(Clone, Debug)]
+pub struct FixedPoint {
+    pub state: State,
+    pub jacobian: DMatrix<f64>,
+    /// Eigenvalues of the Jacobian, sorted by decreasing real part.
+    pub eigenvalues: Vec<Complex<f64>>,
+    pub stability: Stability,
+}
+
+impl FixedPoint {
+    /// Linearizes `system` around the steady state `state`.
+    pub fn new<S: System<f64, State>>(system: &S, state: State) -> FixedPoint {
+        let jacobian = jacobian(system, &state);
+        let mut eigenvalues: Vec<Complex<f64>> =
+            jacobian.complex_eigenvalues().iter().copied().collect();
+        eigenvalues.sort_by(|a, b| b.re.total_cmp(&a.re).then(b.im.total_cmp(&a.im)));
+        let stability = Stability::classify(&eigenvalues);
+        FixedPoint {
+            state,
+            jacobian,
+            eigenvalues,
+            stability,
+        }
+    }
+}
+
+/// Steady state reached from `guess`, with its stability.
+pub fn fixed_point<S: System<f64, State>>(system: &S, guess: &State) -> Result<FixedPoint, Error> {
+    Ok(FixedPoint::new(system, steady_state(system, guess)?))
+}
+
+/// Distinct fixed points reached from each of `guesses`. Guesses that do not converge are
+/// skipped.
+pub fn fixed_points<S: System<f64, State>>(system: &S, guesses: &[State]) -> Vec<FixedPoint> {
+    let mut found: Vec<FixedPoint> = Vec::new();
+    for guess in guesses {
+        if let Ok(point) = fixed_point(system, guess) {
+            let scale = 1.0 + point.state.norm();
+            if found
+                .iter()
+                .all(|p| (&p.state - &point.state).norm() > 1e-6 * scale)
+            {
+                found.push(point);
+            }
+        }
+    }
+    found
+}
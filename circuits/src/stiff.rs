@@ -0,0 +1,749 @@
+//! Integration of stiff systems, where explicit steppers are held to tiny steps by stability
+//! rather than accuracy.
+//!
+//! [`Stiff`] is built and read like [`ode_solvers::Dopri5`] (`new`, `integrate`, `x_out`,
+//! `y_out`) and offers three methods: the linearly implicit Rosenbrock pair of Shampine and
+//! Reichelt (MATLAB's `ode23s`), variable-order BDF with quasi-constant steps (orders 1 to 5, as
+//! in `ode15s` and SciPy's `BDF`) and, by default, automatic switching. The automatic method
+//! starts with explicit Dormand–Prince steps, moves to Rosenbrock when Hairer's test finds the
+//! step limited by stability, and back when the spectral radius of the Jacobian no longer
+//! constrains an explicit step. Jacobians come from central differences unless an analytic one
+//! is supplied with [`Stiff::jacobian`].
+//!
+//! [`Stiff::segments`] lists where the automatic method switched, [`Stiff::trajectory`] gives the
+//! dense solution and [`Stiff::occurrences`] the [`Event`]s found.
+
+use nalgebra::DMatrix;
+use ode_solvers::dop_shared::Stats;
+use ode_solvers::System;
+
+use crate::events::{locate, Event, Occurrence};
+use crate::model::{Error, State, Time};
+use crate::stability::jacobian_at;
+use crate::trajectory::Trajectory;
+
+/// Integration method of a [`Stiff`] solver.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Method {
+    /// Explicit Dormand–Prince 5(4), as `Dopri5`.
+    DormandPrince,
+    /// Linearly implicit Rosenbrock 2(3) pair, L-stable.
+    Rosenbrock,
+    /// Variable-order backward differentiation formulas.
+    Bdf,
+    /// Dormand–Prince while the problem is not stiff, Rosenbrock while it is.
+    #[default]
+    Auto,
+}
+
+/// Largest `h |lambda|` for which a Dormand–Prince step stays stable.
+const STABILITY_BOUNDARY: f64 = 3.25;
+/// Consecutive steps that must agree before the automatic method switches.
+const SWITCH_STEPS: u32 = 15;
+
+const MAX_ORDER: usize = 5;
+const NEWTON_MAXITER: usize = 4;
+/// Coefficients of the numerical differentiation formulas of Shampine and Reichelt.
+const KAPPA: [f64; MAX_ORDER + 1] = [0.0, -0.1850, -1.0 / 9.0, -0.0823, -0.0415, 0.0];
+
+/// Cubic Hermite interpolation at `t` between `(t_a, y_a)` and `(t_b, y_b)` with derivatives
+/// `f_a` and `f_b`.
+pub(crate) fn hermite(
+    (t_a, y_a, f_a): (f64, &State, &State),
+    (t_b, y_b, f_b): (f64, &State, &State),
+    t: f64,
+) -> State {
+    let h = t_b - t_a;
+    let s = ((t - t_a) / h).clamp(0.0, 1.0);
+    let (s2, s3) = (s * s, s * s * s);
+    y_a * (2.0 * s3 - 3.0 * s2 + 1.0)
+        + f_a * (h * (s3 - 2.0 * s2 + s))
+        + y_b * (3.0 * s2 - 2.0 * s3)
+        + f_b * (h * (s3 - s2))
+}
+
+/// Root mean square of `v` relative to `scale`.
+fn norm(v: &State, scale: &State) -> f64 {
+    (v.component_div(scale).norm_squared() / v.len().max(1) as f64).sqrt()
+}
+
+/// Accepted steps, or a grid of spacing `dx` filled in by Hermite interpolation.
+struct Output {
+    x0: f64,
+    dx: f64,
+    /// Index of the next grid point.
+    next: usize,
+    x_out: Vec<f64>,
+    y_out: Vec<State>,
+}
+
+impl Output {
+    fn new(x0: f64, dx: f64, y0: &State) -> Self {
+        Output {
+            x0,
+            dx,
+            next: 1,
+            x_out: vec![x0],
+            y_out: vec![y0.clone()],
+        }
+    }
+
+    /// Output of the step from `a` to `b`, cut short at `until` by a terminal event.
+    fn push(&mut self, a: (f64, &State, &State), b: (f64, &State, &State), until: f64) {
+        if self.dx <= 0.0 {
+            self.x_out.push(until);
+            self.y_out.push(if until < b.0 {
+                hermite(a, b, until)
+            } else {
+                b.1.clone()
+            });
+            return;
+        }
+        loop {
+            let t = self.x0 + self.next as f64 * self.dx;
+            if t > until * (1.0 + 1e-12) {
+                break;
+            }
+            self.x_out.push(t);
+            self.y_out.push(hermite(a, b, t));
+            self.next += 1;
+        }
+    }
+
+    /// Ends the grid at `t_end` when it does not fall on a grid point.
+    fn finish(&mut self, t_end: f64, y_end: &State) {
+        if self
+            .x_out
+            .last()
+            .is_some_and(|t| *t < t_end * (1.0 - 1e-12))
+        {
+            self.x_out.push(t_end);
+            self.y_out.push(y_end.clone());
+        }
+    }
+}
+
+/// Analytic Jacobian `df/dy` at `(t, y)`.
+type Jacobian = dyn Fn(Time, &State) -> DMatrix<f64>;
+
+/// Outcome of one attempted step of a one-step method.
+struct Attempt {
+    y: State,
+    f: State,
+    error: f64,
+    /// Estimate of `h |lambda|` for the stiffness tests, when it is wanted.
+    stiffness: Option<f64>,
+}
+
+/// Solver for stiff and non-stiff systems with the interface of the `ode_solvers` steppers.
+pub struct Stiff<S> {
+    system: S,
+    method: Method,
+    x: f64,
+    x_end: f64,
+    dx: f64,
+    y: State,
+    rtol: f64,
+    atol: f64,
+    jacobian: Option<Box<Jacobian>>,
+    stats: Stats,
+    output: Output,
+    trajectory: Trajectory,
+    segments: Vec<(f64, Method)>,
+    events: Vec<Event>,
+    occurrences: Vec<Occurrence>,
+}
+
+impl<S: System<f64, State>> Stiff<S> {
+    /// Solver from `x` to `x_end` with output every `dx`, or at every step when `dx` is zero.
+    /// Integration runs forward only; [`Stiff::integrate`] rejects an `x_end` before `x`.
+    pub fn new(system: S, x: f64, x_end: f64, dx: f64, y: State, rtol: f64, atol: f64) -> Self {
+        let output = Output::new(x, dx, &y);
+        Stiff {
+            system,
+            method: Method::Auto,
+            x,
+            x_end,
+            dx,
+            y,
+            rtol,
+            atol,
+            jacobian: None,
+            stats: Stats {
+                num_eval: 0,
+                accepted_steps: 0,
+                rejected_steps: 0,
+            },
+            output,
+            trajectory: Trajectory::new(),
+            segments: Vec::new(),
+            events: Vec::new(),
+            occurrences: Vec::new(),
+        }
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Analytic Jacobian `df/dy` in place of central differences.
+    pub fn jacobian(mut self, jacobian: impl Fn(Time, &State) -> DMatrix<f64> + 'static) -> Self {
+        self.jacobian = Some(Box::new(jacobian));
+        self
+    }
+
+    pub fn x_out(&self) -> &Vec<f64> {
+        &self.output.x_out
+    }
+
+    pub fn y_out(&self) -> &Vec<State> {
+        &self.output.y_out
+    }
+
+    /// Accepted steps of the last integration, to be resampled at any times.
+    pub fn trajectory(&self) -> &Trajectory {
+        &self.trajectory
+    }
+
+    /// Adds an event to locate during the integration.
+    pub fn event(mut self, event: Event) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Events found by the last integration, in order of time.
+    pub fn occurrences(&self) -> &Vec<Occurrence> {
+        &self.occurrences
+    }
+
+    /// Method used from each time on; with [`Method::Auto`] one entry per switch.
+    pub fn segments(&self) -> &Vec<(f64, Method)> {
+        &self.segments
+    }
+
+    fn rhs(&mut self, t: f64, y: &State) -> State {
+        self.stats.num_eval += 1;
+        let mut dy = State::zeros(y.len());
+        self.system.system(t, y, &mut dy);
+        dy
+    }
+
+    fn jacobian_at(&mut self, t: f64, y: &State) -> DMatrix<f64> {
+        match &self.jacobian {
+            Some(jacobian) => jacobian(t, y),
+            None => {
+                self.stats.num_eval += 2 * y.len() as u32;
+                jacobian_at(&self.system, t, y)
+            }
+        }
+    }
+
+    fn scale(&self, a: &State, b: &State) -> State {
+        a.zip_map(b, |a, b| self.atol + self.rtol * a.abs().max(b.abs()))
+    }
+
+    /// Starting step from the size of the state relative to its derivative.
+    fn initial_step(&self, y: &State, f: &State) -> f64 {
+        let scale = self.scale(y, y);
+        let (d0, d1) = (norm(y, &scale), norm(f, &scale));
+        let h = if d0 < 1e-5 || d1 < 1e-5 {
+            1e-6
+        } else {
+            0.01 * d0 / d1
+        };
+        h.min(self.x_end - self.x)
+    }
+
+    fn event_values(&self, t: f64, y: &State) -> Vec<f64> {
+        self.events.iter().map(|e| e.value(t, y)).collect()
+    }
+
+    /// Records the output and the events of an accepted step from `a` to `b`. Returns the time
+    /// and state of a terminal event, where the integration stops.
+    fn accept(
+        &mut self,
+        a: (f64, &State, &State),
+        b: (f64, &State, &State),
+        values: &mut Vec<f64>,
+    ) -> Option<(f64, State)> {
+        let after = self.event_values(b.0, b.1);
+        let mut found: Vec<(f64, usize)> = self
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(i, event)| event.triggers(values[*i], after[*i]))
+            .map(|(i, event)| {
+                let g = |t: f64| event.value(t, &hermite(a, b, t));
+                (locate(g, (a.0, values[i]), (b.0, after[i])), i)
+            })
+            .collect();
+        found.sort_by(|x, y| x.0.total_cmp(&y.0));
+        let stop = found
+            .iter()
+            .find(|(_, i)| self.events[*i].is_terminal())
+            .map(|(t, _)| *t);
+        for (t, i) in found {
+            if stop.is_some_and(|stop| t > stop) {
+                break;
+            }
+            self.occurrences.push(Occurrence {
+                event: self.events[i].name.clone(),
+                t,
+                y: hermite(a, b, t),
+                terminal: self.events[i].is_terminal(),
+            });
+        }
+        *values = after;
+        self.output.push(a, b, stop.unwrap_or(b.0));
+        if self.trajectory.is_empty() {
+            self.trajectory.push(a.0, a.1.clone(), a.2.clone());
+        }
+        match stop {
+            Some(t) => {
+                let y = hermite(a, b, t);
+                let f = self.rhs(t, &y);
+                self.trajectory.push(t, y.clone(), f);
+                Some((t, y))
+            }
+            None => {
+                self.trajectory.push(b.0, b.1.clone(), b.2.clone());
+                None
+            }
+        }
+    }
+
+    /// Integrates from the initial to the final time, replacing any earlier results.
+    pub fn integrate(&mut self) -> Result<Stats, Error> {
+        if self.x_end < self.x {
+            return Err(Error::InvalidRange {
+                name: "t".to_string(),
+                min: self.x,
+                max: self.x_end,
+            });
+        }
+        self.stats = Stats {
+            num_eval: 0,
+            accepted_steps: 0,
+            rejected_steps: 0,
+        };
+        self.output = Output::new(self.x, self.dx, &self.y);
+        self.trajectory = Trajectory::new();
+        self.segments.clear();
+        self.occurrences.clear();
+        match self.method {
+            Method::Bdf => self.integrate_bdf()?,
+            method => self.integrate_one_step(method)?,
+        }
+        if self.trajectory.is_empty() {
+            let (x, y) = (self.x, self.y.clone());
+            let f = self.rhs(x, &y);
+            self.trajectory.push(x, y, f);
+        }
+        Ok(self.stats)
+    }
+
+    fn integrate_one_step(&mut self, method: Method) -> Result<(), Error> {
+        let auto = method == Method::Auto;
+        let mut current = if auto { Method::DormandPrince } else { method };
+        let (mut t, mut y) = (self.x, self.y.clone());
+        let mut f = self.rhs(t, &y);
+        let mut h = self.initial_step(&y, &f);
+        let mut events = self.event_values(t, &y);
+        let (mut stiff, mut calm) = (0, 0);
+        self.segments.push((t, current));
+        while t < self.x_end {
+            if h < 1e-14 * t.abs().max(1.0) {
+                return Err(Error::NoConvergence(format!(
+                    "step size underflow at t = {}",
+                    t
+                )));
+            }
+            let last = t + h >= self.x_end;
+            let step = if last { self.x_end - t } else { h };
+            let (attempt, order) = match current {
+                Method::Rosenbrock => (self.rosenbrock(t, &y, &f, step, auto), 2.0),
+                _ => (self.dormand_prince(t, &y, &f, step), 4.0),
+            };
+            let factor = if attempt.error > 0.0 {
+                0.9 * attempt.error.powf(-1.0 / (order + 1.0))
+            } else {
+                10.0
+            };
+            if attempt.error.is_nan() || attempt.error > 1.0 {
+                self.stats.rejected_steps += 1;
+                h = step * factor.clamp(0.2, 1.0);
+                continue;
+            }
+            self.stats.accepted_steps += 1;
+            let t_new = if last { self.x_end } else { t + step };
+            if let Some((t_stop, y_stop)) =
+                self.accept((t, &y, &f), (t_new, &attempt.y, &attempt.f), &mut events)
+            {
+                (t, y) = (t_stop, y_stop);
+                break;
+            }
+            t = t_new;
+            y = attempt.y;
+            f = attempt.f;
+            h = step * factor.clamp(0.2, 10.0);
+
+            if let (true, Some(stiffness)) = (auto, attempt.stiffness) {
+                match current {
+                    Method::Rosenbrock => {
+                        calm = if stiffness * h / step < STABILITY_BOUNDARY {
+                            calm + 1
+                        } else {
+                            0
+                        };
+                        if calm == SWITCH_STEPS {
+                            current = Method::DormandPrince;
+                            (stiff, calm) = (0, 0);
+                            self.segments.push((t, current));
+                        }
+                    }
+                    _ => {
+                        if stiffness > STABILITY_BOUNDARY {
+                            stiff += 1;
+                            calm = 0;
+                        } else {
+                            calm += 1;
+                            if calm == 6 {
+                                stiff = 0;
+                            }
+                        }
+                        if stiff == SWITCH_STEPS {
+                            current = Method::Rosenbrock;
+                            (stiff, calm) = (0, 0);
+                            self.segments.push((t, current));
+                        }
+                    }
+                }
+            }
+        }
+        self.output.finish(t, &y);
+        Ok(())
+    }
+
+    /// Dormand–Prince 5(4) step with Hairer's estimate of `h |lambda|` from the last two
+    /// stages, which are both evaluated at the end of the step.
+    fn dormand_prince(&mut self, t: f64, y: &State, k1: &State, h: f64) -> Attempt {
+        let k2 = self.rhs(t + h / 5.0, &(y + k1 * (h / 5.0)));
+        let k3 = self.rhs(
+            t + 0.3 * h,
+            &(y + (k1 * (3.0 / 40.0) + &k2 * (9.0 / 40.0)) * h),
+        );
+        let k4 = self.rhs(
+            t + 0.8 * h,
+            &(y + (k1 * (44.0 / 45.0) - &k2 * (56.0 / 15.0) + &k3 * (32.0 / 9.0)) * h),
+        );
+        let k5 = self.rhs(
+            t + 8.0 / 9.0 * h,
+            &(y + (k1 * (19372.0 / 6561.0) - &k2 * (25360.0 / 2187.0) + &k3 * (64448.0 / 6561.0)
+                - &k4 * (212.0 / 729.0))
+                * h),
+        );
+        let y6 = y
+            + (k1 * (9017.0 / 3168.0) - &k2 * (355.0 / 33.0)
+                + &k3 * (46732.0 / 5247.0)
+                + &k4 * (49.0 / 176.0)
+                - &k5 * (5103.0 / 18656.0))
+                * h;
+        let k6 = self.rhs(t + h, &y6);
+        let y_new = y
+            + (k1 * (35.0 / 384.0) + &k3 * (500.0 / 1113.0) + &k4 * (125.0 / 192.0)
+                - &k5 * (2187.0 / 6784.0)
+                + &k6 * (11.0 / 84.0))
+                * h;
+        let k7 = self.rhs(t + h, &y_new);
+        let error = (k1 * (71.0 / 57600.0) - &k3 * (71.0 / 16695.0) + &k4 * (71.0 / 1920.0)
+            - &k5 * (17253.0 / 339200.0)
+            + &k6 * (22.0 / 525.0)
+            - &k7 * (1.0 / 40.0))
+            * h;
+        let spread = (&y_new - &y6).norm();
+        let stiffness = if spread > 0.0 {
+            h * (&k7 - &k6).norm() / spread
+        } else {
+            0.0
+        };
+        Attempt {
+            error: norm(&error, &self.scale(y, &y_new)),
+            y: y_new,
+            f: k7,
+            stiffness: Some(stiffness),
+        }
+    }
+
+    /// Rosenbrock step of `ode23s`; with `spectral` also `h` times the spectral radius of the
+    /// Jacobian.
+    fn rosenbrock(&mut self, t: f64, y: &State, f: &State, h: f64, spectral: bool) -> Attempt {
+        let d = 1.0 / (2.0 + std::f64::consts::SQRT_2);
+        let e32 = 6.0 + std::f64::consts::SQRT_2;
+        let rejected = Attempt {
+            y: y.clone(),
+            f: f.clone(),
+            error: f64::INFINITY,
+            stiffness: None,
+        };
+        let jacobian = self.jacobian_at(t, y);
+        let dt = f64::EPSILON.sqrt() * t.abs().max(1.0);
+        let df_dt = (self.rhs(t + dt, y) - f) / dt;
+        let n = y.len();
+        let lu = (DMatrix::identity(n, n) - &jacobian * (h * d)).lu();
+        let Some(k1) = lu.solve(&(f + &df_dt * (h * d))) else {
+            return rejected;
+        };
+        let f1 = self.rhs(t + h / 2.0, &(y + &k1 * (h / 2.0)));
+        let Some(k2) = lu.solve(&(&f1 - &k1)).map(|k| k + &k1) else {
+            return rejected;
+        };
+        let y_new = y + &k2 * h;
+        let f2 = self.rhs(t + h, &y_new);
+        let Some(k3) = lu.solve(&(&f2 - (&k2 - &f1) * e32 - (&k1 - f) * 2.0 + &df_dt * (h * d)))
+        else {
+            return rejected;
+        };
+        let error = (&k1 - &k2 * 2.0 + k3) * (h / 6.0);
+        let stiffness = spectral.then(|| {
+            let radius = match jacobian.clone().try_schur(f64::EPSILON, 1000) {
+                Some(schur) => schur
+                    .complex_eigenvalues()
+                    .iter()
+                    .map(|l| l.norm())
+                    .fold(0.0, f64::max),
+                None => jacobian.norm(),
+            };
+            h * radius
+        });
+        Attempt {
+            error: norm(&error, &self.scale(y, &y_new)),
+            y: y_new,
+            f: f2,
+            stiffness,
+        }
+    }
+
+    fn integrate_bdf(&mut self) -> Result<(), Error> {
+        self.segments.push((self.x, Method::Bdf));
+        let n = self.y.len();
+        let mut gamma = [0.0; MAX_ORDER + 1];
+        for k in 1..=MAX_ORDER {
+            gamma[k] = gamma[k - 1] + 1.0 / k as f64;
+        }
+        let alpha: Vec<f64> = (0..=MAX_ORDER)
+            .map(|k| (1.0 - KAPPA[k]) * gamma[k])
+            .collect();
+        let error_const: Vec<f64> = (0..=MAX_ORDER)
+            .map(|k| KAPPA[k] * gamma[k] + 1.0 / (k + 1) as f64)
+            .collect();
+        let newton_tol = (10.0 * f64::EPSILON / self.rtol).max(0.03f64.min(self.rtol.sqrt()));
+
+        let (mut t, y0) = (self.x, self.y.clone());
+        let f0 = self.rhs(t, &y0);
+        let mut h = self.initial_step(&y0, &f0);
+        let mut events = self.event_values(t, &y0);
+        // Scaled backward differences of the solution, two more than the order for the error
+        // estimates of the neighbouring orders.
+        let mut differences = vec![State::zeros(n); MAX_ORDER + 3];
+        differences[0] = y0.clone();
+        differences[1] = &f0 * h;
+        let mut order = 1;
+        let mut equal_steps = 0;
+        let mut jacobian = self.jacobian_at(t, &y0);
+        let mut lu = None;
+        let (mut y, mut f) = (y0, f0);
+
+        while t < self.x_end {
+            let mut current_jacobian = false;
+            let (y_new, d, error_norm, safety, scale) = loop {
+                if h < 1e-14 * t.abs().max(1.0) {
+                    return Err(Error::NoConvergence(format!(
+                        "step size underflow at t = {}",
+                        t
+                    )));
+                }
+                if t + h >= self.x_end {
+                    change_differences(&mut differences, order, (self.x_end - t) / h);
+                    h = self.x_end - t;
+                    equal_steps = 0;
+                    lu = None;
+                }
+                let t_new = t + h;
+                let y_predict: State = differences[..=order].iter().sum();
+                let scale = y_predict.map(|y| self.atol + self.rtol * y.abs());
+                let psi = (1..=order)
+                    .fold(State::zeros(n), |psi, i| psi + &differences[i] * gamma[i])
+                    / alpha[order];
+                let c = h / alpha[order];
+
+                let solution = loop {
+                    let factorization =
+                        lu.get_or_insert_with(|| (DMatrix::identity(n, n) - &jacobian * c).lu());
+                    let solution = self.newton(
+                        t_new,
+                        &y_predict,
+                        &psi,
+                        c,
+                        &scale,
+                        newton_tol,
+                        factorization,
+                    );
+                    if solution.is_some() || current_jacobian {
+                        break solution;
+                    }
+                    jacobian = self.jacobian_at(t_new, &y_predict);
+                    lu = None;
+                    current_jacobian = true;
+                };
+                let Some((y_new, d, iterations)) = solution else {
+                    self.stats.rejected_steps += 1;
+                    h *= 0.5;
+                    change_differences(&mut differences, order, 0.5);
+                    equal_steps = 0;
+                    lu = None;
+                    continue;
+                };
+                let safety = 0.9 * (2 * NEWTON_MAXITER + 1) as f64
+                    / (2 * NEWTON_MAXITER + iterations) as f64;
+                let scale = y_new.map(|y| self.atol + self.rtol * y.abs());
+                let error_norm = norm(&(&d * error_const[order]), &scale);
+                if error_norm > 1.0 {
+                    self.stats.rejected_steps += 1;
+                    let factor = (safety * error_norm.powf(-1.0 / (order + 1) as f64)).max(0.2);
+                    h *= factor;
+                    change_differences(&mut differences, order, factor);
+                    equal_steps = 0;
+                    lu = None;
+                    continue;
+                }
+                break (y_new, d, error_norm, safety, scale);
+            };
+
+            self.stats.accepted_steps += 1;
+            equal_steps += 1;
+            let t_new = t + h;
+            differences[order + 2] = &d - &differences[order + 1];
+            differences[order + 1] = d;
+            for i in (0..=order).rev() {
+                differences[i] = &differences[i] + &differences[i + 1];
+            }
+            let f_new = self.rhs(t_new, &y_new);
+            if let Some((t_stop, y_stop)) =
+                self.accept((t, &y, &f), (t_new, &y_new, &f_new), &mut events)
+            {
+                (t, y) = (t_stop, y_stop);
+                break;
+            }
+            (t, y, f) = (t_new, y_new, f_new);
+
+            if equal_steps < order + 1 {
+                continue;
+            }
+            let lower = if order > 1 {
+                norm(&(&differences[order] * error_const[order - 1]), &scale)
+            } else {
+                f64::INFINITY
+            };
+            let higher = if order < MAX_ORDER {
+                norm(&(&differences[order + 2] * error_const[order + 1]), &scale)
+            } else {
+                f64::INFINITY
+            };
+            let factors = [lower, error_norm, higher]
+                .iter()
+                .enumerate()
+                .map(|(i, e)| e.powf(-1.0 / (order + i) as f64))
+                .collect::<Vec<_>>();
+            let (best, factor) =
+                factors
+                    .iter()
+                    .enumerate()
+                    .fold(
+                        (1, factors[1]),
+                        |best, (i, f)| {
+                            if *f > best.1 {
+                                (i, *f)
+                            } else {
+                                best
+                            }
+                        },
+                    );
+            order = order + best - 1;
+            let factor = (safety * factor).min(10.0);
+            h *= factor;
+            change_differences(&mut differences, order, factor);
+            equal_steps = 0;
+            lu = None;
+        }
+        self.output.finish(t, &y);
+        Ok(())
+    }
+
+    /// Simplified Newton iteration for the BDF corrector. Returns the solution, its distance
+    /// from the prediction and the number of iterations, or `None` when it diverges or is too
+    /// slow to converge.
+    #[allow(clippy::too_many_arguments)]
+    fn newton(
+        &mut self,
+        t: f64,
+        y_predict: &State,
+        psi: &State,
+        c: f64,
+        scale: &State,
+        tol: f64,
+        lu: &nalgebra::LU<f64, nalgebra::Dyn, nalgebra::Dyn>,
+    ) -> Option<(State, State, usize)> {
+        let mut y = y_predict.clone();
+        let mut d = State::zeros(y.len());
+        let mut previous: Option<f64> = None;
+        for k in 0..NEWTON_MAXITER {
+            let f = self.rhs(t, &y);
+            let dy = lu.solve(&(&f * c - psi - &d))?;
+            let dy_norm = norm(&dy, scale);
+            let rate = previous.map(|p| dy_norm / p);
+            if let Some(rate) = rate {
+                if rate >= 1.0
+                    || rate.powi((NEWTON_MAXITER - k) as i32) / (1.0 - rate) * dy_norm > tol
+                {
+                    return None;
+                }
+            }
+            y += &dy;
+            d += &dy;
+            if dy_norm == 0.0 || rate.is_some_and(|rate| rate / (1.0 - rate) * dy_norm < tol) {
+                return Some((y, d, k + 1));
+            }
+            previous = Some(dy_norm);
+        }
+        None
+    }
+}
+
+/// Rows `R` of the change of the backward differences of an order `order` method when the
+/// step is multiplied by `factor`.
+fn step_change(order: usize, factor: f64) -> DMatrix<f64> {
+    let mut r = DMatrix::from_element(order + 1, order + 1, 1.0);
+    for i in 1..=order {
+        for j in 1..=order {
+            r[(i, j)] = r[(i - 1, j)] * (i as f64 - 1.0 - factor * j as f64) / i as f64;
+        }
+        r[(i, 0)] = 0.0;
+    }
+    r
+}
+
+/// Rescales the backward differences to a step `factor` times the current one.
+fn change_differences(differences: &mut [State], order: usize, factor: f64) {
+    let ru = step_change(order, factor) * step_change(order, 1.0);
+    let changed: Vec<State> = (0..=order)
+        .map(|i| {
+            (0..=order).fold(State::zeros(differences[0].len()), |sum, k| {
+                sum + &differences[k] * ru[(k, i)]
+            })
+        })
+        .collect();
+    for (difference, new) in differences.iter_mut().zip(changed) {
+        *difference = new;
+    }
+}
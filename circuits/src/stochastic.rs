@@ -0,0 +1,305 @@
+//! Exact stochastic simulation of reaction networks.
+//!
+//! [`Gillespie`] runs one trajectory with the direct method or the next-reaction method of
+//! Gibson and Bruck, which only recomputes the propensities a firing changes. [`Replicates`]
+//! runs many trajectories, each on its own stream of the seeded generator, and summarizes them
+//! by mean, variance, quantiles and histograms at the sampling times.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::model::{Model, State};
+use crate::random::{exponential, quantile};
+use crate::reactions::ReactionNetwork;
+
+/// Algorithm used to pick the next reaction.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Method {
+    /// Draws the waiting time and the reaction from the total propensity.
+    #[default]
+    Direct,
+    /// Keeps a putative firing time per reaction and fires the earliest.
+    NextReaction,
+}
+
+/// Exact stochastic simulation on the copy numbers of a [`ReactionNetwork`].
+pub struct Gillespie<'a> {
+    network: &'a ReactionNetwork,
+    method: Method,
+    stoichiometry: Vec<Vec<i64>>,
+    dependents: Vec<Vec<usize>>,
+    propensities: Vec<f64>,
+    /// Absolute putative firing times of the next-reaction method, empty until initialized.
+    next: Vec<f64>,
+    t: f64,
+    x: Vec<f64>,
+    rng: ChaCha8Rng,
+}
+
+impl<'a> Gillespie<'a> {
+    /// Starts from the rounded initial amounts of the network with a seeded generator.
+    pub fn new(network: &'a ReactionNetwork, seed: u64) -> Self {
+        Gillespie {
+            network,
+            method: Method::Direct,
+            stoichiometry: network.stoichiometry(),
+            dependents: network.dependency_graph(),
+            propensities: vec![0.0; network.reactions.len()],
+            next: Vec::new(),
+            t: 0.0,
+            x: network.initial_state().iter().map(|x| x.round()).collect(),
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self.next.clear();
+        self
+    }
+
+    /// Switches to stream `stream` of the seeded generator, independent of the others.
+    pub fn stream(mut self, stream: u64) -> Self {
+        self.rng.set_stream(stream);
+        self
+    }
+
+    /// Replaces the copy numbers and the current time.
+    pub fn reset(&mut self, t: f64, x: &State) {
+        self.t = t;
+        self.x = x.iter().map(|x| x.round()).collect();
+        self.next.clear();
+    }
+
+    pub fn t(&self) -> f64 {
+        self.t
+    }
+
+    pub fn state(&self) -> State {
+        State::from_vec(self.x.clone())
+    }
+
+    /// Fires a single reaction and returns its index, or `None` when all propensities vanish.
+    pub fn step(&mut self) -> Option<usize> {
+        let (t, j) = self.next_reaction()?;
+        self.fire(t, j);
+        Some(j)
+    }
+
+    /// Simulates until time `t_end`.
+    pub fn advance_until(&mut self, t_end: f64) {
+        self.advance_steps(t_end, usize::MAX);
+    }
+
+    /// Fires at most `steps` reactions before time `t_end`, and moves on to `t_end` when the
+    /// next reaction would come after it.
+    pub fn advance_steps(&mut self, t_end: f64, steps: usize) {
+        for _ in 0..steps {
+            match self.next_reaction() {
+                Some((t, j)) if t <= t_end => self.fire(t, j),
+                _ => {
+                    self.t = self.t.max(t_end);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Records the state at each of the increasing `times`.
+    pub fn sample(&mut self, times: &[f64]) -> (Vec<f64>, Vec<State>) {
+        let mut y_out = Vec::with_capacity(times.len());
+        for t in times {
+            self.advance_until(*t);
+            y_out.push(self.state());
+        }
+        (times.to_vec(), y_out)
+    }
+
+    /// Time and index of the next reaction, without firing it.
+    ///
+    /// Discarding a direct-method draw past the end of an interval is exact because waiting
+    /// times are memoryless; the putative times of the next-reaction method simply stay put.
+    fn next_reaction(&mut self) -> Option<(f64, usize)> {
+        match self.method {
+            Method::Direct => {
+                let a0 = self.update_propensities();
+                if a0 <= 0.0 {
+                    return None;
+                }
+                let tau = self.exponential() / a0;
+                Some((self.t + tau, self.select(a0)))
+            }
+            Method::NextReaction => {
+                if self.next.is_empty() {
+                    self.update_propensities();
+                    self.next = (0..self.propensities.len())
+                        .map(|j| self.t + self.exponential() / self.propensities[j])
+                        .collect();
+                }
+                let (j, t) = self
+                    .next
+                    .iter()
+                    .enumerate()
+                    .min_by(|a, b| a.1.total_cmp(b.1))?;
+                t.is_finite().then_some((*t, j))
+            }
+        }
+    }
+
+    fn update_propensities(&mut self) -> f64 {
+        for (j, a) in self.propensities.iter_mut().enumerate() {
+            *a = self.network.propensity(j, &self.x);
+        }
+        self.propensities.iter().sum()
+    }
+
+    fn exponential(&mut self) -> f64 {
+        exponential(&mut self.rng)
+    }
+
+    /// Generator of the simulation, for approximations that take exact steps in between.
+    pub(crate) fn rng(&mut self) -> &mut ChaCha8Rng {
+        &mut self.rng
+    }
+
+    fn select(&mut self, a0: f64) -> usize {
+        let target = self.rng.random::<f64>() * a0;
+        let mut cumulative = 0.0;
+        for (j, a) in self.propensities.iter().enumerate() {
+            cumulative += a;
+            if target < cumulative {
+                return j;
+            }
+        }
+        self.propensities.len() - 1
+    }
+
+    fn fire(&mut self, t: f64, j: usize) {
+        self.t = t;
+        for (x, change) in self.x.iter_mut().zip(self.stoichiometry[j].iter()) {
+            *x += *change as f64;
+        }
+        if self.method == Method::NextReaction {
+            for i in 0..self.dependents[j].len() {
+                let k = self.dependents[j][i];
+                let old = self.propensities[k];
+                let new = self.network.propensity(k, &self.x);
+                self.propensities[k] = new;
+                // Rescale the remaining waiting time, drawing afresh only when it is spent or
+                // the reaction was switched off.
+                self.next[k] = if k != j && old > 0.0 && new > 0.0 {
+                    t + old / new * (self.next[k] - t)
+                } else {
+                    t + self.exponential() / new
+                };
+            }
+        }
+    }
+}
+
+/// Counts of the values of one species in bins of equal width.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    /// Bin edges, one more than the counts.
+    pub edges: Vec<f64>,
+    pub counts: Vec<usize>,
+}
+
+/// Independent trajectories of a reaction network sampled at common times.
+#[derive(Clone, Debug)]
+pub struct Replicates {
+    pub species: Vec<String>,
+    pub t: Vec<f64>,
+    /// States of every replicate at every sampling time.
+    pub trajectories: Vec<Vec<State>>,
+}
+
+impl Replicates {
+    /// Runs `n` replicates from the initial amounts of `network`. Replicate `r` uses stream
+    /// `r` of the generator seeded with `seed`, so every replicate is reproducible on its own.
+    pub fn run(
+        network: &ReactionNetwork,
+        method: Method,
+        times: &[f64],
+        n: usize,
+        seed: u64,
+    ) -> Self {
+        Replicates::from_fn(network, times, n, |r| {
+            Gillespie::new(network, seed)
+                .method(method)
+                .stream(r as u64)
+                .sample(times)
+                .1
+        })
+    }
+
+    /// Collects `n` trajectories sampled at `times`, the `r`th given by `trajectory(r)`.
+    pub fn from_fn(
+        network: &ReactionNetwork,
+        times: &[f64],
+        n: usize,
+        trajectory: impl Fn(usize) -> Vec<State>,
+    ) -> Self {
+        Replicates {
+            species: network.species.clone(),
+            t: times.to_vec(),
+            trajectories: (0..n).map(trajectory).collect(),
+        }
+    }
+
+    /// Values of `species` over the replicates at sampling time `k`.
+    pub fn values(&self, species: usize, k: usize) -> Vec<f64> {
+        self.trajectories.iter().map(|y| y[k][species]).collect()
+    }
+
+    fn summarize(&self, statistic: impl Fn(&mut [f64]) -> f64) -> Vec<State> {
+        (0..self.t.len())
+            .map(|k| {
+                State::from_iterator(
+                    self.species.len(),
+                    (0..self.species.len()).map(|s| statistic(&mut self.values(s, k))),
+                )
+            })
+            .collect()
+    }
+
+    pub fn mean(&self) -> Vec<State> {
+        self.summarize(|v| v.iter().sum::<f64>() / v.len() as f64)
+    }
+
+    /// Sample variance over the replicates.
+    pub fn variance(&self) -> Vec<State> {
+        self.summarize(|v| {
+            let mean = v.iter().sum::<f64>() / v.len() as f64;
+            v.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (v.len() as f64 - 1.0).max(1.0)
+        })
+    }
+
+    pub fn quantile(&self, q: f64) -> Vec<State> {
+        self.summarize(|v| quantile(v, q))
+    }
+
+    /// Histogram of `species` at sampling time `k` with bins of integer width covering the
+    /// observed copy numbers, at most `bins` of them.
+    pub fn histogram(&self, species: usize, k: usize, bins: usize) -> Histogram {
+        let values = self.values(species, k);
+        if values.is_empty() {
+            return Histogram {
+                edges: vec![0.0],
+                counts: Vec::new(),
+            };
+        }
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let width = ((max - min + 1.0) / bins.max(1) as f64).ceil().max(1.0);
+        let n = ((max - min + 1.0) / width).ceil() as usize;
+        let mut counts = vec![0; n];
+        for v in &values {
+            counts[(((v - min) / width) as usize).min(n - 1)] += 1;
+        }
+        Histogram {
+            edges: (0..=n).map(|i| min + i as f64 * width).collect(),
+            counts,
+        }
+    }
+}
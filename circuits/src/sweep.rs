@@ -0,0 +1,241 @@
+//! Runs of one model over many parameter sets, spread across the cores of the machine.
+//!
+//! A [`Sweep`] is a list of parameter sets, given one by one or as the Cartesian product of
+//! values per parameter. [`Sweep::run`] integrates every set with the automatic method of
+//! [`Stiff`] on its own thread and resamples the solutions at common times. The [`Runs`] can be
+//! flattened to the long format of one row per run, time and species with [`Runs::long`], or
+//! with `Runs::data_frame` to a polars `DataFrame` when the `polars` feature is enabled.
+
+use std::thread;
+
+use crate::model::{Error, Model, State, Time};
+use crate::stiff::Stiff;
+
+const RTOL: f64 = 1e-6;
+const ATOL: f64 = 1e-6;
+
+/// Parameter sets to run a model with.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sweep {
+    names: Vec<String>,
+    sets: Vec<Vec<f64>>,
+    threads: Option<usize>,
+}
+
+impl Sweep {
+    /// Empty sweep over the parameters `names`.
+    pub fn new(names: &[&str]) -> Self {
+        Sweep {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            sets: Vec::new(),
+            threads: None,
+        }
+    }
+
+    /// Every combination of the values of each parameter, the last parameter varying fastest.
+    pub fn grid(axes: &[(&str, Vec<f64>)]) -> Self {
+        let names: Vec<&str> = axes.iter().map(|(name, _)| *name).collect();
+        let sets = axes.iter().fold(vec![Vec::new()], |sets, (_, values)| {
+            sets.iter()
+                .flat_map(|set| {
+                    values.iter().map(move |value| {
+                        let mut set = set.clone();
+                        set.push(*value);
+                        set
+                    })
+                })
+                .collect()
+        });
+        Sweep {
+            sets,
+            ..Sweep::new(&names)
+        }
+    }
+
+    /// Adds one parameter set, with a value for every parameter of the sweep.
+    pub fn set(mut self, values: &[f64]) -> Result<Self, Error> {
+        if values.len() != self.names.len() {
+            return Err(Error::Dimension {
+                expected: self.names.len(),
+                found: values.len(),
+            });
+        }
+        self.sets.push(values.to_vec());
+        Ok(self)
+    }
+
+    /// Number of worker threads, by default the available parallelism of the machine.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads.max(1));
+        self
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn sets(&self) -> &[Vec<f64>] {
+        &self.sets
+    }
+
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    /// Integrates `model` from its initial state with every parameter set and samples the
+    /// solutions at `times`, which start at the initial time. Fails on the first parameter set
+    /// that is rejected by the model or cannot be integrated.
+    pub fn run<M: Model + Clone + Send + Sync>(
+        &self,
+        model: &M,
+        times: &[Time],
+    ) -> Result<Runs, Error> {
+        let threads = self
+            .threads
+            .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1);
+        let chunk = self.sets.len().div_ceil(threads).max(1);
+        let states = thread::scope(|scope| {
+            let workers: Vec<_> = self
+                .sets
+                .chunks(chunk)
+                .map(|sets| {
+                    scope.spawn(move || {
+                        sets.iter()
+                            .map(|values| self.simulate(model, values, times))
+                            .collect::<Result<Vec<_>, Error>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("sweep worker panicked"))
+                .collect::<Result<Vec<_>, Error>>()
+        })?;
+        Ok(Runs {
+            parameters: self.names.clone(),
+            species: model.species().iter().map(|s| s.to_string()).collect(),
+            t: times.to_vec(),
+            runs: self
+                .sets
+                .iter()
+                .zip(states.into_iter().flatten())
+                .enumerate()
+                .map(|(id, (values, states))| Run {
+                    id,
+                    values: values.clone(),
+                    states,
+                })
+                .collect(),
+        })
+    }
+
+    fn simulate<M: Model + Clone>(
+        &self,
+        model: &M,
+        values: &[f64],
+        times: &[Time],
+    ) -> Result<Vec<State>, Error> {
+        let mut model = model.clone();
+        for (name, value) in self.names.iter().zip(values) {
+            model.set(name, *value)?;
+        }
+        let (Some(t0), Some(t_end)) = (times.first(), times.last()) else {
+            return Ok(Vec::new());
+        };
+        let y0 = model.initial_state();
+        let mut stepper = Stiff::new(model, *t0, *t_end, 0.0, y0, RTOL, ATOL);
+        stepper.integrate()?;
+        Ok(stepper.trajectory().sample(times))
+    }
+}
+
+/// Solution of one parameter set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    /// Position of the parameter set in the sweep.
+    pub id: usize,
+    /// Values of the swept parameters.
+    pub values: Vec<f64>,
+    /// States at the sampled times.
+    pub states: Vec<State>,
+}
+
+/// Solutions of all parameter sets of a sweep at common times.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Runs {
+    /// Names of the swept parameters.
+    pub parameters: Vec<String>,
+    pub species: Vec<String>,
+    pub t: Vec<Time>,
+    pub runs: Vec<Run>,
+}
+
+impl Runs {
+    /// One row per run, time and species.
+    pub fn long(&self) -> Long {
+        let rows = self.runs.len() * self.t.len() * self.species.len();
+        let mut long = Long {
+            sweep: Vec::with_capacity(rows),
+            parameters: self
+                .parameters
+                .iter()
+                .map(|name| (name.clone(), Vec::with_capacity(rows)))
+                .collect(),
+            t: Vec::with_capacity(rows),
+            species: Vec::with_capacity(rows),
+            value: Vec::with_capacity(rows),
+        };
+        for run in &self.runs {
+            for (t, y) in self.t.iter().zip(&run.states) {
+                for (i, species) in self.species.iter().enumerate() {
+                    long.sweep.push(run.id as u32);
+                    for ((_, column), value) in long.parameters.iter_mut().zip(&run.values) {
+                        column.push(*value);
+                    }
+                    long.t.push(*t);
+                    long.species.push(species.clone());
+                    long.value.push(y[i]);
+                }
+            }
+        }
+        long
+    }
+}
+
+#[cfg(feature = "polars")]
+impl Runs {
+    /// The long format as a `DataFrame` with the columns `sweep`, one per swept parameter, `t`,
+    /// `species` and `value`.
+    pub fn data_frame(&self) -> polars::prelude::PolarsResult<polars::prelude::DataFrame> {
+        use polars::prelude::{Column, DataFrame};
+
+        let long = self.long();
+        let mut columns = vec![Column::new("sweep".into(), long.sweep)];
+        for (name, values) in long.parameters {
+            columns.push(Column::new(name.into(), values));
+        }
+        columns.extend([
+            Column::new("t".into(), long.t),
+            Column::new("species".into(), long.species),
+            Column::new("value".into(), long.value),
+        ]);
+        DataFrame::new(columns)
+    }
+}
+
+/// Columns of a sweep in long format.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Long {
+    /// Id of the run of each row.
+    pub sweep: Vec<u32>,
+    /// One named column per swept parameter.
+    pub parameters: Vec<(String, Vec<f64>)>,
+    pub t: Vec<Time>,
+    pub species: Vec<String>,
+    pub value: Vec<f64>,
+}
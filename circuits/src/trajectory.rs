@@ -0,0 +1,135 @@
+//! Solutions that can be read at any time, not only where the stepper happened to stop.
+//!
+//! A [`Trajectory`] keeps the accepted steps of an integration together with the derivative at
+//! each of them and interpolates in between with cubic Hermite polynomials. Resampling it on a
+//! [`uniform`] or [`logarithmic`] grid, or at the time points of observations, puts
+//! simulations, analytic curves and data on the same time axis without tuning the output step
+//! of the solver.
+//!
+//! [`Trajectory::from_steps`] builds one from the output of the `ode_solvers` steppers, and
+//! [`Trajectory::series`] gives the `[t, y]` pairs that charts take.
+
+use ode_solvers::System;
+
+use crate::model::{Error, State, Time};
+use crate::stiff::hermite;
+
+/// Accepted steps of an integration with their derivatives.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trajectory {
+    times: Vec<Time>,
+    states: Vec<State>,
+    derivatives: Vec<State>,
+}
+
+impl Trajectory {
+    pub(crate) fn new() -> Self {
+        Trajectory::default()
+    }
+
+    pub(crate) fn push(&mut self, t: Time, y: State, f: State) {
+        self.times.push(t);
+        self.states.push(y);
+        self.derivatives.push(f);
+    }
+
+    /// Trajectory through the output of any stepper, for example `Dopri5` with sparse output,
+    /// with the derivatives evaluated by `system`.
+    pub fn from_steps<S: System<f64, State>>(system: &S, times: &[Time], states: &[State]) -> Self {
+        let mut trajectory = Trajectory::new();
+        for (t, y) in times.iter().zip(states) {
+            let mut f = State::zeros(y.len());
+            system.system(*t, y, &mut f);
+            trajectory.push(*t, y.clone(), f);
+        }
+        trajectory
+    }
+
+    /// Times of the steps.
+    pub fn times(&self) -> &[Time] {
+        &self.times
+    }
+
+    /// States at the steps.
+    pub fn states(&self) -> &[State] {
+        &self.states
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// First and last time covered.
+    pub fn span(&self) -> Option<(Time, Time)> {
+        Some((*self.times.first()?, *self.times.last()?))
+    }
+
+    /// State at `t`, held at the first or last state outside the span.
+    pub fn at(&self, t: Time) -> State {
+        assert!(!self.is_empty(), "empty trajectory");
+        if self.times.len() == 1 {
+            return self.states[0].clone();
+        }
+        let k = self
+            .times
+            .partition_point(|x| *x < t)
+            .clamp(1, self.times.len() - 1);
+        hermite(
+            (
+                self.times[k - 1],
+                &self.states[k - 1],
+                &self.derivatives[k - 1],
+            ),
+            (self.times[k], &self.states[k], &self.derivatives[k]),
+            t,
+        )
+    }
+
+    /// States at `times`.
+    pub fn sample(&self, times: &[Time]) -> Vec<State> {
+        times.iter().map(|t| self.at(*t)).collect()
+    }
+
+    /// `[t, y]` pairs of one species at `times`, the layout of chart series.
+    pub fn series(&self, species: usize, times: &[Time]) -> Vec<Vec<f64>> {
+        times
+            .iter()
+            .map(|t| vec![*t, self.at(*t)[species]])
+            .collect()
+    }
+}
+
+/// `n` equally spaced times from `start` to `end`, both included.
+pub fn uniform(start: Time, end: Time, n: usize) -> Vec<Time> {
+    match n {
+        0 => Vec::new(),
+        1 => vec![start],
+        _ => (0..n)
+            .map(|k| start + (end - start) * k as f64 / (n - 1) as f64)
+            .collect(),
+    }
+}
+
+/// `n` logarithmically spaced times from `start` to `end`, both included, for dynamics spread
+/// over several time scales.
+pub fn logarithmic(start: Time, end: Time, n: usize) -> Result<Vec<Time>, Error> {
+    if start.is_nan() || end.is_nan() || start <= 0.0 || end <= 0.0 {
+        return Err(Error::InvalidRange {
+            name: "t".to_string(),
+            min: start,
+            max: end,
+        });
+    }
+    let mut times: Vec<Time> = uniform(start.ln(), end.ln(), n)
+        .into_iter()
+        .map(f64::exp)
+        .collect();
+    // Exact end points, which exp(ln(t)) need not return.
+    if let Some(first) = times.first_mut() {
+        *first = start;
+    }
+    if n > 1 {
+        times[n - 1] = end;
+    }
+    Ok(times)
+}
@@ -0,0 +1,88 @@
+use std::f64::consts::{FRAC_1_SQRT_2, LN_2, PI};
+
+use circuits::analysis::{crossing, Oscillation, Response};
+use circuits::trajectory::uniform;
+
+/// Unregulated expression `x = beta / alpha (1 - exp(-alpha t))` reaches half of its steady
+/// state at `ln 2 / alpha` and stays within 2% of it after `ln 50 / alpha`, without overshoot.
+#[test]
+fn unregulated_response() {
+    let (beta, alpha) = (2.0, 0.5);
+    let t = uniform(0.0, 40.0 / alpha, 80001);
+    let x: Vec<f64> = t
+        .iter()
+        .map(|t| beta / alpha * (1.0 - (-alpha * t).exp()))
+        .collect();
+    let response = Response::new(&t, &x).expect("a step");
+    assert_eq!(response.initial, 0.0);
+    assert!((response.steady_state - beta / alpha).abs() < 1e-12);
+    assert!(
+        (response.rise_time - LN_2 / alpha).abs() < 1e-6,
+        "{:?}",
+        response
+    );
+    assert_eq!(response.overshoot, 0.0);
+    let step = t[1] - t[0];
+    assert!(
+        (response.settling_time - 50f64.ln() / alpha).abs() <= step,
+        "{:?}",
+        response
+    );
+
+    // Times are measured from the first sample, and a falling step works the same way.
+    let shifted: Vec<f64> = t.iter().map(|t| t + 10.0).collect();
+    let falling: Vec<f64> = x.iter().map(|x| beta / alpha - x).collect();
+    let response = Response::new(&shifted, &falling).expect("a step");
+    assert!(
+        (response.rise_time - LN_2 / alpha).abs() < 1e-6,
+        "{:?}",
+        response
+    );
+
+    assert!(Response::new(&t, &vec![1.0; t.len()]).is_none());
+    assert!(Response::new(&t[..1], &x[..1]).is_none());
+}
+
+/// `1 - exp(-t) cos t` overshoots most at `t = 3 pi / 4`, by `exp(-3 pi / 4) / sqrt 2`.
+#[test]
+fn damped_response_overshoots() {
+    let t = uniform(0.0, 40.0, 40001);
+    let x: Vec<f64> = t.iter().map(|t| 1.0 - (-t).exp() * t.cos()).collect();
+    let response = Response::new(&t, &x).expect("a step");
+    let expected = (-3.0 * PI / 4.0).exp() * FRAC_1_SQRT_2;
+    assert!(
+        (response.overshoot - expected).abs() < 1e-6,
+        "{:?}",
+        response
+    );
+    assert!(response.rise_time < 3.0 * PI / 4.0);
+}
+
+#[test]
+fn oscillation_of_a_sine() {
+    let t = uniform(0.0, 50.0, 5001);
+    let sustained: Vec<f64> = t
+        .iter()
+        .map(|t| 3.0 + 2.0 * (2.0 * PI * t / 5.0).sin())
+        .collect();
+    let oscillation = Oscillation::new(&t, &sustained).expect("peaks");
+    assert!((oscillation.period - 5.0).abs() < 1e-3, "{:?}", oscillation);
+    assert!(
+        (oscillation.amplitude - 4.0).abs() < 1e-3,
+        "{:?}",
+        oscillation
+    );
+    assert!(oscillation.is_sustained(0.99));
+
+    let damped: Vec<f64> = t
+        .iter()
+        .zip(&sustained)
+        .map(|(t, x)| 3.0 + (x - 3.0) * (-0.05 * t).exp())
+        .collect();
+    let oscillation = Oscillation::new(&t, &damped).expect("peaks");
+    assert!(!oscillation.is_sustained(0.9), "{:?}", oscillation);
+    assert!(Oscillation::new(&t, &t).is_none());
+
+    assert_eq!(crossing(&[0.0, 1.0, 2.0], &[4.0, 2.0, 0.0], 1.0), Some(1.5));
+    assert_eq!(crossing(&[0.0, 1.0], &[0.0, 1.0], 2.0), None);
+}
@@ -0,0 +1,56 @@
+use circuits::approximation::{compare, Approximation, Scheme};
+use circuits::reactions::ReactionNetwork;
+use circuits::stochastic::{Method, Replicates};
+
+const BIRTH: f64 = 1000.0;
+const DEATH: f64 = 1.0;
+
+/// `X` made at a constant rate and removed at a rate proportional to its amount, whose mean
+/// is `BIRTH / DEATH (1 - exp(-DEATH t))` from zero.
+fn birth_death() -> ReactionNetwork {
+    ReactionNetwork::new("birth-death")
+        .species("X", 0.0)
+        .parameter("birth", BIRTH)
+        .parameter("death", DEATH)
+        .reaction("birth", "0 => X @ birth")
+        .and_then(|n| n.reaction("death", "X => 0 @ death"))
+        .expect("valid reactions")
+}
+
+#[test]
+fn approximations_follow_the_mean() {
+    let network = birth_death();
+    let times: Vec<f64> = (0..=10).map(|k| 0.5 * k as f64).collect();
+    let n = 400;
+    let exact = Replicates::run(&network, Method::Direct, &times, n, 1);
+    for approximation in [
+        // Leaps that long against the relaxation time bias the transient, as explicit Euler
+        // steps would; 0.03 is off by about 1% near t = 3.
+        Approximation::TauLeaping { epsilon: 0.01 },
+        Approximation::Langevin {
+            dt: 0.01,
+            scheme: Scheme::EulerMaruyama,
+        },
+    ] {
+        let replicates = approximation.replicates(&network, &times, n, 2);
+        for (t, mean) in times.iter().zip(replicates.mean()) {
+            let expected = BIRTH / DEATH * (1.0 - (-DEATH * t).exp());
+            // Four standard errors of the mean of a Poisson distribution.
+            let tolerance = 4.0 * (expected / n as f64).sqrt() + 1e-9;
+            assert!(
+                (mean[0] - expected).abs() < tolerance,
+                "{:?} at t = {}: {} against {}",
+                approximation,
+                t,
+                mean[0],
+                expected
+            );
+        }
+        let deviation = &compare(&exact, &replicates)[0];
+        assert!(deviation.mean_error < 0.01, "{:?}", deviation);
+        assert!((deviation.std_ratio - 1.0).abs() < 0.2, "{:?}", deviation);
+        if let Approximation::TauLeaping { .. } = approximation {
+            assert!(deviation.ks_distance < 0.15, "{:?}", deviation);
+        }
+    }
+}
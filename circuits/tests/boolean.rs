@@ -0,0 +1,313 @@
+use std::time::{Duration, Instant};
+
+use circuits::boolean::{discretize, BooleanNetwork, Expr, Update};
+use circuits::gene_network::{Gene, GeneNetwork};
+use circuits::models::ToggleSwitch;
+use circuits::regulation::{Hill, Logic};
+use circuits::{Error, Model, State};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn toggle() -> BooleanNetwork {
+    BooleanNetwork::new()
+        .rule("u", "!v")
+        .and_then(|n| n.rule("v", "!u"))
+        .expect("valid rules")
+}
+
+fn ring(genes: usize) -> BooleanNetwork {
+    (0..genes).fold(BooleanNetwork::new(), |network, i| {
+        network
+            .rule(
+                &format!("g{}", i),
+                &format!("!g{}", (i + genes - 1) % genes),
+            )
+            .expect("valid rule")
+    })
+}
+
+/// Attractors as strings of 0 and 1 with their basins, sorted.
+fn summary(network: &BooleanNetwork, update: Update) -> Vec<(Vec<String>, f64)> {
+    let mut attractors: Vec<(Vec<String>, f64)> = network
+        .attractors(update)
+        .expect("a small network")
+        .into_iter()
+        .map(|a| {
+            let states = a
+                .states
+                .iter()
+                .map(|s| s.iter().map(|on| if *on { '1' } else { '0' }).collect())
+                .collect();
+            (states, a.basin)
+        })
+        .collect();
+    attractors.sort_by(|a, b| a.0.cmp(&b.0));
+    attractors
+}
+
+fn rules(network: &BooleanNetwork) -> Vec<String> {
+    (0..network.len())
+        .map(|i| {
+            let rule = network
+                .rule_of(i)
+                .map_or("input".to_string(), |r| r.to_string());
+            format!("{} = {}", network.names()[i], rule)
+        })
+        .collect()
+}
+
+#[test]
+fn toggle_switch_attractors() {
+    let network = toggle();
+    let states = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        summary(&network, Update::Asynchronous),
+        vec![(states(&["01"]), 0.75), (states(&["10"]), 0.75)]
+    );
+    // Switching both genes at once adds an oscillation between all on and all off.
+    assert_eq!(
+        summary(&network, Update::Synchronous),
+        vec![
+            (states(&["00", "11"]), 0.5),
+            (states(&["01"]), 0.25),
+            (states(&["10"]), 0.25)
+        ]
+    );
+    assert_eq!(
+        summary(&network, Update::Probabilistic(0.5)),
+        summary(&network, Update::Asynchronous)
+    );
+    // Nothing moves without updates.
+    assert_eq!(summary(&network, Update::Probabilistic(0.0)).len(), 4);
+}
+
+#[test]
+fn repressilator_cycles() {
+    let network = ring(3);
+    let synchronous = summary(&network, Update::Synchronous);
+    assert_eq!(synchronous.len(), 2);
+    assert_eq!(
+        synchronous[0],
+        (vec!["000".to_string(), "111".to_string()], 0.25)
+    );
+    assert_eq!(synchronous[1].0.len(), 6);
+    assert_eq!(synchronous[1].1, 0.75);
+    let asynchronous = summary(&network, Update::Asynchronous);
+    assert_eq!(asynchronous.len(), 1);
+    assert_eq!(asynchronous[0].0.len(), 6);
+    assert_eq!(asynchronous[0].1, 1.0);
+}
+
+#[test]
+fn sampled_attractors_match_enumeration() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let network = ring(3);
+    for update in [Update::Synchronous, Update::Asynchronous] {
+        let sampled = network
+            .sampled_attractors(update, 200, 20, 100, &mut rng)
+            .expect("few reachable states");
+        let mut sizes: Vec<usize> = sampled.iter().map(|a| a.states.len()).collect();
+        sizes.sort();
+        let mut expected: Vec<usize> = network
+            .attractors(update)
+            .expect("a small network")
+            .iter()
+            .map(|a| a.states.len())
+            .collect();
+        expected.sort();
+        assert_eq!(sizes, expected, "{:?}", update);
+    }
+}
+
+#[test]
+fn probabilistic_sampling_stops_at_the_limit() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    for genes in [22, 64] {
+        let start = Instant::now();
+        let result =
+            ring(genes).sampled_attractors(Update::Probabilistic(0.5), 1, 0, 1000, &mut rng);
+        assert!(
+            matches!(result, Err(Error::NoConvergence(_))),
+            "{:?}",
+            result
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    assert!(matches!(
+        ring(12).attractors(Update::Probabilistic(0.5)),
+        Err(Error::OutOfBounds { .. })
+    ));
+}
+
+#[test]
+fn empty_network() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let network = BooleanNetwork::new();
+    for update in [
+        Update::Synchronous,
+        Update::Asynchronous,
+        Update::Probabilistic(0.5),
+    ] {
+        let states = network
+            .simulate(&[], update, 3, &mut rng)
+            .expect("an empty network");
+        assert_eq!(states, vec![Vec::<bool>::new(); 4]);
+    }
+}
+
+#[test]
+fn states_of_the_wrong_length() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let network = toggle();
+    for state in [vec![], vec![true], vec![true, false, true]] {
+        let expected = Error::Dimension {
+            expected: 2,
+            found: state.len(),
+        };
+        assert_eq!(
+            network.simulate(&state, Update::Synchronous, 3, &mut rng),
+            Err(expected.clone())
+        );
+        assert_eq!(
+            network.step(&state, Update::Asynchronous, &mut rng),
+            Err(expected)
+        );
+    }
+    let hill = toggle_network().expect("valid gene names");
+    assert_eq!(
+        discretize(&hill, &State::from_vec(vec![1.0])),
+        Err(Error::Dimension {
+            expected: 2,
+            found: 1
+        })
+    );
+}
+
+#[test]
+fn truth_tables() {
+    let mut network = BooleanNetwork::new().node("x").node("y").node("z");
+    let tables = [
+        ([false, false, false, true], "x & y"),
+        ([false, true, true, true], "x | y"),
+        ([false, true, true, false], "(x & !y) | (!x & y)"),
+        ([true, false, true, false], "!x"),
+        ([true, true, true, true], "true"),
+    ];
+    for (table, expected) in tables {
+        network.set_rule(2, Expr::from_truth_table(&[0, 1], &table));
+        assert_eq!(network.rule_of(2).expect("a rule").to_string(), expected);
+        // The rule reproduces the table.
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for (row, on) in table.iter().enumerate() {
+            let state = vec![row & 1 == 1, row & 2 == 2, false];
+            let next = network
+                .step(&state, Update::Synchronous, &mut rng)
+                .expect("one value per gene");
+            assert_eq!(next[2], *on, "{} in row {}", expected, row);
+        }
+    }
+}
+
+#[test]
+fn rules_parse_and_reject() {
+    let network = BooleanNetwork::new()
+        .rule("z", "(x and y) or not w")
+        .expect("valid rule");
+    assert_eq!(rules(&network)[0], "z = (x & y) | !w");
+    assert!(BooleanNetwork::new().rule("a", "b &").is_err());
+    assert!(BooleanNetwork::new().rule("a", "(b").is_err());
+}
+
+fn toggle_network() -> Result<GeneNetwork, Error> {
+    let toggle = ToggleSwitch::default();
+    GeneNetwork::new("Toggle switch")
+        .gene(Gene::new("u").beta(toggle.alpha1))?
+        .gene(Gene::new("v").beta(toggle.alpha2))?
+        .regulation("v", "u", Hill::repression(1.0, toggle.beta))?
+        .regulation("u", "v", Hill::repression(1.0, toggle.gamma))
+}
+
+#[test]
+fn hill_abstraction() {
+    let network = toggle_network().expect("valid gene names");
+    let boolean = BooleanNetwork::from_gene_network(&network);
+    assert_eq!(rules(&boolean), vec!["u = !v", "v = !u"]);
+
+    // The stable states of the Hill model are the fixed points of the abstraction.
+    let mut stable: Vec<Vec<bool>> = ToggleSwitch::default()
+        .steady_states()
+        .iter()
+        .filter(|p| p.stability.is_stable())
+        .map(|p| discretize(&network, &p.state))
+        .collect::<Result<_, _>>()
+        .expect("one level per gene");
+    stable.sort();
+    let mut fixed: Vec<Vec<bool>> = boolean
+        .attractors(Update::Asynchronous)
+        .expect("a small network")
+        .into_iter()
+        .filter(|a| a.is_fixed_point())
+        .map(|a| a.states[0].clone())
+        .collect();
+    fixed.sort();
+    assert_eq!(stable, fixed);
+
+    let ffl = |logic| -> Result<GeneNetwork, Error> {
+        GeneNetwork::new("FFL")
+            .gene(Gene::new("X").beta(0.0).alpha(0.0))?
+            .gene(Gene::new("Z").logic(logic))?
+            .gene(Gene::new("W"))?
+            .regulation("X", "Y", Hill::activation(0.1, 2.0))?
+            .regulation("X", "Z", Hill::activation(0.1, 2.0))?
+            .regulation("Y", "Z", Hill::activation(0.5, 2.0))
+    };
+    let and = ffl(Logic::And).expect("valid gene names");
+    assert_eq!(
+        rules(&BooleanNetwork::from_gene_network(&and)),
+        vec!["X = input", "Z = X & Y", "W = true", "Y = X"]
+    );
+    let or = ffl(Logic::Or).expect("valid gene names");
+    assert_eq!(
+        rules(&BooleanNetwork::from_gene_network(&or))[1],
+        "Z = X | Y"
+    );
+}
+
+/// `_` separates the gene names in the parameter names, as in `k_R_T`.
+#[test]
+fn gene_names_without_underscore() {
+    assert!(GeneNetwork::new("").gene(Gene::new("lac_I")).is_err());
+    assert!(GeneNetwork::new("")
+        .regulation("a", "b_c", Hill::activation(1.0, 2.0))
+        .is_err());
+    let network = toggle_network().expect("valid gene names");
+    assert!(GeneNetwork::from_graph("", network.graph().clone()).is_ok());
+    let mut graph = network.graph().clone();
+    graph.add_node(Gene::new("u_v"));
+    assert!(GeneNetwork::from_graph("", graph).is_err());
+    let mut graph = network.graph().clone();
+    graph.add_node(Gene::new("u"));
+    assert!(matches!(
+        GeneNetwork::from_graph("", graph),
+        Err(Error::Parse(_))
+    ));
+}
+
+/// Without inducer the hand-written toggle switch is the same ODE as its gene network.
+#[test]
+fn gene_network_matches_the_toggle_switch() {
+    let network = toggle_network().expect("valid gene names");
+    let toggle = ToggleSwitch::default();
+    assert_eq!(network.species(), toggle.species());
+    for (u, v) in [(0.0, 0.0), (0.5, 3.0), (10.0, 0.1), (150.0, 15.0)] {
+        let x = State::from_vec(vec![u, v]);
+        let (expected, actual) = (toggle.rhs(0.0, &x), network.rhs(0.0, &x));
+        assert!(
+            (&expected - &actual).amax() < 1e-12 * (1.0 + expected.amax()),
+            "{} vs {}",
+            expected,
+            actual
+        );
+    }
+    assert_eq!(network.get("n_v_u"), Some(toggle.beta));
+}
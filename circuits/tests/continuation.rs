@@ -0,0 +1,53 @@
+use circuits::continuation::{BifurcationKind, Continuation};
+use circuits::models::Goodwin;
+use circuits::{Error, Model};
+
+/// The Goodwin loop with equal decay rates has a supercritical Hopf point at the secant
+/// condition `n = 8`; its limit cycle can be followed up to `n = 14` in coarse steps.
+#[test]
+fn goodwin_cycles_from_hopf() {
+    let model = Goodwin {
+        n: 4.0,
+        ..Goodwin::default()
+    };
+    let branch = Continuation::new("n", 2.0, 14.0)
+        .run(&model, &model.initial_state())
+        .expect("failed continuation");
+    let hopf = branch
+        .bifurcations
+        .iter()
+        .position(|b| matches!(b.kind, BifurcationKind::Hopf { .. }))
+        .expect("a Hopf point");
+    assert!(
+        (branch.bifurcations[hopf].parameter - 8.0).abs() < 0.05,
+        "{:?}",
+        branch.bifurcations[hopf]
+    );
+
+    for points in [10, 40] {
+        let mut branch = branch.clone();
+        let found = branch
+            .follow_cycles(&model, hopf, 14.0, points)
+            .expect("failed to follow the cycles");
+        assert_eq!(found, points);
+        assert_eq!(branch.cycles.len(), points);
+        let last = branch.cycles.last().expect("a cycle");
+        assert!((last.parameter - 14.0).abs() < 1e-9);
+        assert!(branch.cycles.iter().all(|c| c.stable && c.period > 0.0));
+        // The amplitude grows away from the Hopf point.
+        let amplitude = |i: usize| branch.cycles[i].max[0] - branch.cycles[i].min[0];
+        assert!(amplitude(points - 1) > amplitude(0));
+    }
+
+    let mut branch = branch.clone();
+    let past_the_end = branch.bifurcations.len();
+    assert!(matches!(
+        branch.follow_cycles(&model, past_the_end, 14.0, 10),
+        Err(Error::InvalidArgument(_))
+    ));
+    branch.bifurcations[hopf].kind = BifurcationKind::Fold;
+    assert!(matches!(
+        branch.follow_cycles(&model, hopf, 14.0, 10),
+        Err(Error::InvalidArgument(_))
+    ));
+}
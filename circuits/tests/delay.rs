@@ -0,0 +1,65 @@
+use circuits::delay::{Dde, DelaySystem};
+use circuits::{Error, State, Time};
+
+/// `y'(t) = -y(t - tau)`.
+struct Lagged {
+    tau: f64,
+}
+
+impl DelaySystem for Lagged {
+    fn delays(&self) -> Vec<f64> {
+        vec![self.tau]
+    }
+
+    fn delayed_system(&self, _t: Time, _y: &State, lagged: &[State], dy: &mut State) {
+        dy[0] = -lagged[0][0];
+    }
+}
+
+/// With a constant history of one the method of steps gives `1 - t` on `[0, 1]` and
+/// `1 - t + (t - 1)^2 / 2` on `[1, 2]`.
+fn exact(t: f64) -> f64 {
+    if t <= 1.0 {
+        1.0 - t
+    } else {
+        1.0 - t + (t - 1.0).powi(2) / 2.0
+    }
+}
+
+#[test]
+fn method_of_steps_matches_the_piecewise_solution() {
+    let mut dde = Dde::new(
+        Lagged { tau: 1.0 },
+        |_| State::from_vec(vec![1.0]),
+        0.0,
+        2.0,
+    )
+    .tolerances(1e-9, 1e-9);
+    dde.integrate().expect("failed integration");
+    // The derivative jumps at 0 and its derivative at 1, so a step lands on each.
+    assert!(dde.x_out().iter().any(|t| (t - 1.0).abs() < 1e-12));
+    assert_eq!(dde.x_out().last(), Some(&2.0));
+    for (t, y) in dde.x_out().iter().zip(dde.y_out()) {
+        assert!((y[0] - exact(*t)).abs() < 1e-8, "{} at {}", y[0], t);
+    }
+    for k in 0..=40 {
+        let t = -0.5 + 2.5 * k as f64 / 40.0;
+        let y = dde.at(t)[0];
+        let expected = if t < 0.0 { 1.0 } else { exact(t) };
+        assert!(
+            (y - expected).abs() < 1e-7,
+            "{} against {} at {}",
+            y,
+            expected,
+            t
+        );
+    }
+}
+
+#[test]
+fn delays_must_be_positive() {
+    for tau in [0.0, -1.0, f64::NAN] {
+        let mut dde = Dde::new(Lagged { tau }, |_| State::from_vec(vec![1.0]), 0.0, 2.0);
+        assert!(matches!(dde.integrate(), Err(Error::OutOfBounds { .. })));
+    }
+}
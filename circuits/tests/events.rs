@@ -0,0 +1,81 @@
+use std::f64::consts::LN_2;
+
+use circuits::events::{Direction, Event};
+use circuits::stiff::{Method, Stiff};
+use circuits::{State, Time};
+use ode_solvers::System;
+
+/// `y' = -y`, so `y = exp(-t)` from one crosses `2^-k` at `k ln 2`.
+struct Decay;
+
+impl System<f64, State> for Decay {
+    fn system(&self, _t: Time, y: &State, dy: &mut State) {
+        dy[0] = -y[0];
+    }
+}
+
+fn decay(method: Method) -> Stiff<Decay> {
+    Stiff::new(
+        Decay,
+        0.0,
+        5.0,
+        0.0,
+        State::from_vec(vec![1.0]),
+        1e-8,
+        1e-10,
+    )
+    .method(method)
+}
+
+#[test]
+fn locates_a_threshold_crossing() {
+    for method in [Method::Rosenbrock, Method::Bdf] {
+        let mut stepper = decay(method)
+            .event(Event::threshold("half", 0, 0.5).direction(Direction::Falling))
+            .event(Event::threshold("rising", 0, 0.5).direction(Direction::Rising))
+            .event(Event::new("time", |t, _| t - 1.0));
+        stepper.integrate().expect("failed integration");
+        let occurrences = stepper.occurrences();
+        let names: Vec<&str> = occurrences.iter().map(|o| o.event.as_str()).collect();
+        assert_eq!(names, ["half", "time"], "{:?}", method);
+        // Roots are located on the cubic dense output of a step, not on the exact solution.
+        assert!(
+            (occurrences[0].t - LN_2).abs() < 1e-5,
+            "{:?}",
+            occurrences[0]
+        );
+        assert!(
+            (occurrences[0].y[0] - 0.5).abs() < 1e-6,
+            "{:?}",
+            occurrences[0]
+        );
+        assert!(
+            (occurrences[1].t - 1.0).abs() < 1e-9,
+            "{:?}",
+            occurrences[1]
+        );
+        assert!(!occurrences[0].terminal);
+        // A non-terminal event leaves the integration running to the end.
+        assert_eq!(stepper.x_out().last(), Some(&5.0));
+    }
+}
+
+#[test]
+fn terminal_event_stops_the_integration() {
+    for method in [Method::Rosenbrock, Method::Bdf] {
+        let mut stepper = decay(method)
+            .event(Event::threshold("half", 0, 0.5))
+            .event(Event::threshold("quarter", 0, 0.25).terminal())
+            .event(Event::threshold("eighth", 0, 0.125));
+        stepper.integrate().expect("failed integration");
+        let occurrences = stepper.occurrences();
+        assert_eq!(occurrences.len(), 2, "{:?}: {:?}", method, occurrences);
+        let stop = &occurrences[1];
+        assert!(stop.terminal && stop.event == "quarter");
+        assert!((stop.t - 2.0 * LN_2).abs() < 1e-5, "{:?}", stop);
+        let t_end = *stepper.x_out().last().expect("output");
+        assert!((t_end - stop.t).abs() < 1e-12, "{} vs {}", t_end, stop.t);
+        let y_end = stepper.y_out().last().expect("output");
+        assert!((y_end[0] - 0.25).abs() < 1e-6, "{}", y_end);
+    }
+}
@@ -0,0 +1,94 @@
+use circuits::models::{FeedForwardLoop, FflType};
+use circuits::regulation::Logic;
+
+/// Coherent loops delay `Z` in one direction only: turning on with AND logic, turning off
+/// with OR logic.
+#[test]
+fn coherent_loops_delay_one_direction() {
+    for logic in [Logic::And, Logic::Or] {
+        for kind in FflType::ALL.into_iter().filter(FflType::is_coherent) {
+            let ffl = FeedForwardLoop::new(kind, logic);
+            let delays = ffl.delays().expect("failed integration");
+            let rises_on = ffl.steady_state(1.0, 1.0)[1] > ffl.steady_state(0.0, 1.0)[1];
+            // The step of Sx after which Z turns on.
+            let (turning_on, turning_off) = if rises_on {
+                (delays.on, delays.off)
+            } else {
+                (delays.off, delays.on)
+            };
+            let (delayed, prompt) = match logic {
+                Logic::Or => (turning_off, turning_on),
+                _ => (turning_on, turning_off),
+            };
+            assert!(
+                delayed > 1.5 * delays.reference,
+                "{:?}-{:?}: {:?}",
+                kind,
+                logic,
+                delays
+            );
+            assert!(
+                (prompt - delays.reference).abs() < 0.05 * delays.reference,
+                "{:?}-{:?}: {:?}",
+                kind,
+                logic,
+                delays
+            );
+            assert_eq!(ffl.pulse(0.05).expect("failed integration"), None);
+        }
+    }
+}
+
+#[test]
+fn incoherent_loops_pulse() {
+    let pulsing = [
+        (FflType::I1, Logic::And),
+        (FflType::I4, Logic::And),
+        (FflType::I2, Logic::Or),
+        (FflType::I3, Logic::Or),
+    ];
+    for logic in [Logic::And, Logic::Or] {
+        for kind in FflType::ALL.into_iter().filter(|k| !k.is_coherent()) {
+            let pulse = FeedForwardLoop::new(kind, logic)
+                .pulse(0.05)
+                .expect("failed integration");
+            assert_eq!(
+                pulse.is_some(),
+                pulsing.contains(&(kind, logic)),
+                "{:?}-{:?}: {:?}",
+                kind,
+                logic,
+                pulse
+            );
+            if let Some(pulse) = pulse {
+                assert!(pulse.adaptation() > 0.4 && pulse.adaptation() <= 1.0);
+            }
+        }
+    }
+}
+
+/// I1-AND speeds up the rise of `Z`; its pulse is measured from the initial level.
+#[test]
+fn i1_and_accelerates_and_pulses() {
+    let ffl = FeedForwardLoop::new(FflType::I1, Logic::And);
+    let delays = ffl.delays().expect("failed integration");
+    assert!(delays.on < 0.5 * delays.reference, "{:?}", delays);
+    let pulse = ffl
+        .pulse(0.05)
+        .expect("failed integration")
+        .expect("a pulse");
+    assert_eq!(pulse.initial, 0.0);
+    assert!(pulse.peak > 1.5 * pulse.steady_state, "{:?}", pulse);
+}
+
+/// A pulse that returns to the initial level has no half time.
+#[test]
+fn no_half_time_without_net_change() {
+    let ffl = FeedForwardLoop::new(FflType::I4, Logic::And);
+    assert!(ffl.delays().expect("failed integration").on.is_nan());
+    let pulse = ffl
+        .pulse(0.05)
+        .expect("failed integration")
+        .expect("a pulse");
+    assert!(pulse.adaptation() > 0.99, "{:?}", pulse);
+}
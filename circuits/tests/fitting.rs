@@ -0,0 +1,112 @@
+use circuits::fitting::{Algorithm, Estimation, Observations};
+use circuits::models::Sir;
+use circuits::{Error, Model};
+use ode_solvers::Dopri5;
+
+/// Infected and recovered counts of `model` every 10 days up to day 300, as a CSV table with
+/// the recovered count of day 50 missing. Each count is off by a fixed fraction of at most
+/// `noise`.
+fn epidemic_csv(model: &Sir, noise: f64) -> String {
+    let errors = [0.3, -0.2, 0.1, 0.4, -0.5, 0.0, 0.2, -0.1, -0.3, 0.25, -0.4];
+    let mut errors = errors.iter().cycle().map(|e| 1.0 + 2.0 * noise * e);
+    let y0 = model.initial_state();
+    let mut stepper = Dopri5::new(*model, 0.0, 300.0, 10.0, y0, 1e-12, 1e-12);
+    stepper.integrate().expect("failed integration");
+    let mut csv = "t,I,R\n".to_string();
+    for (t, y) in stepper.x_out().iter().zip(stepper.y_out()) {
+        let infected = y[1] * errors.next().unwrap_or(1.0);
+        let recovered = y[2] * errors.next().unwrap_or(1.0);
+        if *t == 50.0 {
+            csv.push_str(&format!("{},{},\n", t, infected));
+        } else {
+            csv.push_str(&format!("{},{},{}\n", t, infected, recovered));
+        }
+    }
+    csv
+}
+
+#[test]
+fn parses_missing_values() {
+    let observations =
+        Observations::from_csv_str("t,I,R\n2,5,\n0,1,0\n1,3,0.5\n").expect("valid table");
+    assert_eq!(observations.t, vec![0.0, 1.0, 2.0]);
+    assert_eq!(
+        observations.columns[0],
+        ("I".to_string(), vec![1.0, 3.0, 5.0])
+    );
+    let (name, recovered) = &observations.columns[1];
+    assert_eq!(name, "R");
+    assert_eq!(recovered[..2], [0.0, 0.5]);
+    assert!(recovered[2].is_nan());
+    assert_eq!(
+        observations
+            .indices(&Sir::default())
+            .expect("known species"),
+        vec![1, 2]
+    );
+
+    for malformed in ["", "t\n0", "t,I\n0,1,2", "t,I\n-1,1", "t,I\n0,x"] {
+        assert!(
+            matches!(Observations::from_csv_str(malformed), Err(Error::Parse(_))),
+            "{:?}",
+            malformed
+        );
+    }
+}
+
+#[test]
+fn recovers_the_rates_of_an_epidemic() {
+    let truth = Sir::default();
+    let guess = Sir {
+        beta: 2.0 * truth.beta,
+        gamma: 0.5 * truth.gamma,
+    };
+    // Exact data pin the rates down; with 1% errors the intervals have to cover them.
+    for (noise, accuracy) in [(0.0, 1e-6), (0.01, 0.02)] {
+        let csv = epidemic_csv(&truth, noise);
+        let observations = Observations::from_csv_str(&csv).expect("valid table");
+        assert!(observations.columns[1].1[5].is_nan());
+        for algorithm in [Algorithm::LevenbergMarquardt, Algorithm::NelderMead] {
+            let fit = Estimation::new(&["beta", "gamma"])
+                .algorithm(algorithm)
+                .run(&guess, &observations)
+                .expect("failed fit");
+            assert!(fit.residuals[1].1[5].is_nan());
+
+            for (estimate, expected) in fit.estimates.iter().zip([truth.beta, truth.gamma]) {
+                assert!(
+                    (estimate.value / expected - 1.0).abs() < accuracy,
+                    "{:?}: {} vs {}",
+                    algorithm,
+                    estimate.value,
+                    expected
+                );
+                if noise > 0.0 {
+                    let (low, high) = estimate.interval;
+                    assert!(
+                        low <= expected && expected <= high,
+                        "{:?}: {} outside {:?}",
+                        algorithm,
+                        expected,
+                        estimate.interval
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn weights_must_be_positive() {
+    for weight in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(matches!(
+            Estimation::new(&["beta"]).weight("I", weight),
+            Err(Error::OutOfBounds { .. })
+        ));
+    }
+    let estimation = Estimation::new(&["beta"])
+        .weight("I", 2.0)
+        .and_then(|e| e.weight("I", 0.5))
+        .expect("positive weights");
+    assert_eq!(estimation.weights, vec![("I".to_string(), 0.5)]);
+}
@@ -0,0 +1,103 @@
+use circuits::global_sensitivity::{Morris, Range, Sobol};
+use circuits::{Model, Parameter, State, Time};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// A model without dynamics whose parameters feed the test functions directly.
+#[derive(Copy, Clone, Debug, Default)]
+struct Inputs {
+    a: f64,
+    b: f64,
+    c: f64,
+}
+
+impl ode_solvers::System<f64, State> for Inputs {
+    fn system(&self, _t: Time, _x: &State, dx: &mut State) {
+        dx[0] = 0.0;
+    }
+}
+
+impl Model for Inputs {
+    fn name(&self) -> &str {
+        "inputs"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["x"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![
+            Parameter::new("a", self.a, 0.0, -10.0, 10.0),
+            Parameter::new("b", self.b, 0.0, -10.0, 10.0),
+            Parameter::new("c", self.c, 0.0, -10.0, 10.0),
+        ]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "a" => Some(&mut self.a),
+            "b" => Some(&mut self.b),
+            "c" => Some(&mut self.c),
+            _ => None,
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![0.0])
+    }
+}
+
+fn unit_ranges() -> Vec<Range> {
+    ["a", "b", "c"]
+        .iter()
+        .map(|name| Range::new(name, 0.0, 1.0))
+        .collect()
+}
+
+/// `a + 2 b` on the unit cube: the variances `1/12` and `4/12` split as 0.2 and 0.8, with no
+/// interactions and nothing from `c`.
+#[test]
+fn sobol_indices_of_an_additive_function() {
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let indices = Sobol::new(unit_ranges())
+        .samples(4000)
+        .run(&Inputs::default(), |m| m.a + 2.0 * m.b, &mut rng)
+        .expect("known parameters");
+    for (index, expected) in indices.iter().zip([0.2, 0.8, 0.0]) {
+        assert!((index.first - expected).abs() < 0.05, "{:?}", index);
+        assert!((index.total - expected).abs() < 0.05, "{:?}", index);
+    }
+    assert_eq!(indices[2].total, 0.0);
+
+    let constant = Sobol::new(unit_ranges())
+        .samples(10)
+        .run(&Inputs::default(), |_| 1.0, &mut rng)
+        .expect("known parameters");
+    assert!(constant.iter().all(|i| i.first == 0.0 && i.total == 0.0));
+}
+
+/// Elementary effects of a linear function are its slopes times the widths of the ranges.
+#[test]
+fn morris_screens_out_an_inert_parameter() {
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+    let mut ranges = unit_ranges();
+    ranges[1] = Range::new("b", 1.0, 3.0);
+    let effects = Morris::new(ranges)
+        .trajectories(10)
+        .run(&Inputs::default(), |m| m.a - 3.0 * m.b, &mut rng)
+        .expect("known parameters");
+    for (effect, slope) in effects.iter().zip([1.0, -6.0, 0.0]) {
+        assert!((effect.mu - slope).abs() < 1e-9, "{:?}", effect);
+        assert!(
+            (effect.mu_star - f64::abs(slope)).abs() < 1e-9,
+            "{:?}",
+            effect
+        );
+        assert!(effect.sigma < 1e-9, "{:?}", effect);
+    }
+    assert_eq!(effects[2].mu_star, 0.0);
+
+    let unknown = Morris::new(vec![Range::new("d", 0.0, 1.0)]);
+    assert!(unknown.run(&Inputs::default(), |m| m.a, &mut rng).is_err());
+}
@@ -0,0 +1,118 @@
+use circuits::fitting::Observations;
+use circuits::mcmc::{Ensemble, Prior};
+use circuits::{Model, Parameter, State, Time};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// `x` growing at the constant rate `c` from zero, so that `x(t) = c t`.
+#[derive(Copy, Clone, Debug)]
+struct Growth {
+    c: f64,
+}
+
+impl ode_solvers::System<f64, State> for Growth {
+    fn system(&self, _t: Time, _x: &State, dx: &mut State) {
+        dx[0] = self.c;
+    }
+}
+
+impl Model for Growth {
+    fn name(&self) -> &str {
+        "growth"
+    }
+
+    fn species(&self) -> Vec<&str> {
+        vec!["x"]
+    }
+
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![Parameter::new("c", self.c, 1.0, 0.0, 10.0)]
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "c" => Some(&mut self.c),
+            _ => None,
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State::from_vec(vec![0.0])
+    }
+}
+
+/// With a flat prior and Gaussian noise the posterior of `c` is Gaussian with mean
+/// `sum(t y) / sum(t^2)` and variance `sigma^2 / sum(t^2)`.
+#[test]
+fn recovers_a_gaussian_posterior() {
+    let sigma = 0.5;
+    let t: Vec<f64> = (1..=10).map(|k| k as f64).collect();
+    let noise = [0.3, -0.2, 0.1, 0.4, -0.5, 0.0, 0.2, -0.1, -0.3, 0.25];
+    let y: Vec<f64> = t.iter().zip(noise).map(|(t, e)| 2.0 * t + e).collect();
+    let tt = t.iter().map(|t| t * t).sum::<f64>();
+    let mean = t.iter().zip(&y).map(|(t, y)| t * y).sum::<f64>() / tt;
+    let variance = sigma * sigma / tt;
+    let observations = Observations {
+        t,
+        columns: vec![("x".to_string(), y)],
+    };
+
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    let posterior = Ensemble::new(vec![(
+        "c",
+        Prior::Uniform {
+            min: 0.0,
+            max: 10.0,
+        },
+    )])
+    .sigma("x", sigma)
+    .steps(1500)
+    .burn_in(200)
+    .run(&Growth { c: 1.5 }, &observations, &mut rng)
+    .expect("failed sampling");
+
+    let samples = posterior.samples(0);
+    let sampled_mean = posterior.mean()[0];
+    let sampled_variance = samples
+        .iter()
+        .map(|x| (x - sampled_mean).powi(2))
+        .sum::<f64>()
+        / (samples.len() - 1) as f64;
+    assert!(
+        (sampled_mean - mean).abs() < 0.1 * variance.sqrt(),
+        "{} vs {}",
+        sampled_mean,
+        mean
+    );
+    assert!(
+        (sampled_variance / variance - 1.0).abs() < 0.1,
+        "{} vs {}",
+        sampled_variance,
+        variance
+    );
+    let r_hat = posterior.r_hat().expect("enough steps for R-hat");
+    assert!((r_hat[0] - 1.0).abs() < 0.05, "{:?}", r_hat);
+}
+
+#[test]
+fn r_hat_needs_enough_steps() {
+    let observations = Observations {
+        t: vec![1.0, 2.0],
+        columns: vec![("x".to_string(), vec![2.0, 4.0])],
+    };
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    for (steps, defined) in [(0, false), (3, false), (4, true)] {
+        let posterior = Ensemble::new(vec![(
+            "c",
+            Prior::Uniform {
+                min: 0.0,
+                max: 10.0,
+            },
+        )])
+        .steps(steps)
+        .burn_in(10)
+        .run(&Growth { c: 2.0 }, &observations, &mut rng)
+        .expect("failed sampling");
+        assert_eq!(posterior.r_hat().is_some(), defined, "{} steps", steps);
+    }
+}
@@ -0,0 +1,45 @@
+use circuits::analysis::Oscillation;
+use circuits::models::{Goodwin, NegativeAutoregulation, Repressilator, ToggleSwitch};
+use circuits::stiff::Stiff;
+use circuits::trajectory::uniform;
+use circuits::Model;
+
+/// Oscillation of species `i` of `model` over the second half of a run to `t_end`.
+fn late_oscillation<M: Model + Clone>(model: M, i: usize, t_end: f64) -> Option<Oscillation> {
+    let y0 = model.initial_state();
+    let mut stepper = Stiff::new(model, 0.0, t_end, 0.0, y0, 1e-8, 1e-8);
+    stepper.integrate().expect("failed integration");
+    let late = uniform(t_end / 2.0, t_end, 4001);
+    let values: Vec<f64> = stepper
+        .trajectory()
+        .sample(&late)
+        .iter()
+        .map(|y| y[i])
+        .collect();
+    Oscillation::new(&late, &values)
+}
+
+#[test]
+fn toggle_switch_is_bistable_until_induced() {
+    assert!(ToggleSwitch::default().is_bistable());
+    let induced = ToggleSwitch {
+        iptg: 1e-2,
+        ..ToggleSwitch::default()
+    };
+    assert!(!induced.is_bistable());
+}
+
+#[test]
+fn repressilator_and_goodwin_oscillate() {
+    let repressilator =
+        late_oscillation(Repressilator::default(), 3, 300.0).expect("peaks of the repressilator");
+    let goodwin =
+        late_oscillation(Goodwin::default(), 0, 600.0).expect("peaks of the Goodwin loop");
+    for oscillation in [repressilator, goodwin] {
+        assert!(oscillation.is_sustained(0.95), "{:?}", oscillation);
+        assert!(oscillation.period > 0.0 && oscillation.amplitude > 0.0);
+    }
+    // Negative autoregulation settles; any peaks left are at the level of the tolerances.
+    let settled = late_oscillation(NegativeAutoregulation::default(), 1, 100.0);
+    assert!(settled.is_none_or(|o| o.amplitude < 1e-6), "{:?}", settled);
+}
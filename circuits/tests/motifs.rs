@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use circuits::models::FflType;
+use circuits::motifs::{Subgraph, TranscriptionNetwork};
+use circuits::regulation::Sign;
+use circuits::Error;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// A C1 loop with a self-repressed input and a target that feeds a fourth gene, an I1 loop,
+/// a three-gene cycle and a loop with a dual edge, none connected to another.
+fn network() -> TranscriptionNetwork {
+    TranscriptionNetwork::from_edge_list_str(
+        "X,X,-\n\
+         X,Y,+\n\
+         X,Z,+\n\
+         Y,Z,+\n\
+         Z,W,+\n\
+         A,B,+\n\
+         A,C,+\n\
+         B,C,-\n\
+         P,Q,+\n\
+         Q,R,-\n\
+         R,P,-\n\
+         D,E,+-\n\
+         D,F,+\n\
+         E,F,+",
+    )
+    .expect("valid edge list")
+}
+
+#[test]
+fn counts_loops_and_cycles() {
+    let network = network();
+    let ffl = Subgraph::from_edges(3, &[(0, 1), (0, 2), (1, 2)]);
+    let cycle = Subgraph::from_edges(3, &[(0, 1), (1, 2), (2, 0)]);
+    let cascade = Subgraph::from_edges(3, &[(0, 1), (1, 2)]);
+    assert_eq!(ffl.name(), Some("feed-forward loop"));
+    assert_eq!(cycle.name(), Some("feedback loop"));
+    assert_eq!(
+        network.census(3).expect("valid size"),
+        BTreeMap::from([(ffl, 3), (cycle, 1), (cascade, 2)])
+    );
+    let census = network.census(4).expect("valid size");
+    let tailed = Subgraph::from_edges(4, &[(0, 1), (0, 2), (1, 2), (2, 3)]);
+    assert_eq!(census, BTreeMap::from([(tailed, 1)]));
+    assert!(matches!(network.census(5), Err(Error::InvalidArgument(_))));
+
+    // The loop with the dual edge has no sign type.
+    for (kind, count) in network.ffl_types() {
+        let expected = usize::from(matches!(kind, FflType::C1 | FflType::I1));
+        assert_eq!(count, expected, "{:?}", kind);
+    }
+}
+
+#[test]
+fn skips_a_header_line() {
+    for (text, edges) in [
+        ("regulator,target\nX,Y\nY,Z", 2),
+        ("# comment\nTF\tgene\tmode\nX\tY\t+", 1),
+        ("from,to,effect\nX,Y,+", 1),
+        ("X,Y\nY,Z", 2),
+        ("X Y -\nY Z", 2),
+    ] {
+        let network = TranscriptionNetwork::from_edge_list_str(text).expect("valid edge list");
+        assert_eq!(network.edge_count(), edges, "{:?}", text);
+        assert_eq!(network.node_count(), edges + 1, "{:?}", text);
+    }
+    assert!(matches!(
+        TranscriptionNetwork::from_edge_list_str("X,Y\nY,Z,sometimes"),
+        Err(Error::Parse(_))
+    ));
+}
+
+/// In- and out-degree of each gene, by name.
+fn degrees(network: &TranscriptionNetwork) -> BTreeMap<String, (usize, usize)> {
+    let graph = network.graph();
+    let mut degrees: BTreeMap<String, (usize, usize)> = graph
+        .node_weights()
+        .map(|name| (name.clone(), (0, 0)))
+        .collect();
+    for edge in graph.raw_edges() {
+        degrees.get_mut(&graph[edge.source()]).expect("gene").1 += 1;
+        degrees.get_mut(&graph[edge.target()]).expect("gene").0 += 1;
+    }
+    degrees
+}
+
+/// Sorted edges as `(regulator, target, sign)`.
+fn edges(network: &TranscriptionNetwork) -> Vec<(String, String, Option<Sign>)> {
+    let graph = network.graph();
+    let mut edges: Vec<_> = graph
+        .raw_edges()
+        .iter()
+        .map(|e| {
+            (
+                graph[e.source()].clone(),
+                graph[e.target()].clone(),
+                e.weight,
+            )
+        })
+        .collect();
+    edges.sort_by_key(|(a, b, _)| (a.clone(), b.clone()));
+    edges
+}
+
+#[test]
+fn randomization_keeps_degrees() {
+    let network = network();
+    let original = edges(&network);
+    let repressions = |edges: &[(String, String, Option<Sign>)]| {
+        edges
+            .iter()
+            .filter(|e| e.2 == Some(Sign::Repression))
+            .count()
+    };
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    for _ in 0..10 {
+        let random = network.randomized(10, &mut rng);
+        assert_eq!(random.edge_count(), network.edge_count());
+        assert_eq!(degrees(&random), degrees(&network));
+        let switched = edges(&random);
+        // No repeated edges, and only the self-loop of X stays a self-loop.
+        assert!(switched
+            .windows(2)
+            .all(|w| (&w[0].0, &w[0].1) != (&w[1].0, &w[1].1)));
+        assert!(switched
+            .iter()
+            .all(|(a, b, sign)| a != b || (a == "X" && *sign == Some(Sign::Repression))));
+        assert_eq!(repressions(&switched), repressions(&original));
+    }
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    assert_ne!(
+        edges(&network.randomized(10, &mut rng)),
+        original,
+        "no edge was switched"
+    );
+}
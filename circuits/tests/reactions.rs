@@ -0,0 +1,113 @@
+use circuits::models::Sir;
+use circuits::reactions::{RateLaw, ReactionNetwork};
+use circuits::{Error, Model, State};
+
+fn network() -> ReactionNetwork {
+    ReactionNetwork::new("test")
+        .species("A", 5.0)
+        .species("B", 2.0)
+        .species("C", 0.0)
+        .parameter("k", 0.5)
+        .parameter("beta", 3.0)
+        .parameter("K", 2.0)
+        .parameter("n", 2.0)
+}
+
+#[test]
+fn sir_network_matches_the_model() {
+    let mut model = Sir::default();
+    let mut network = model.network();
+    assert_eq!(network.species, model.species());
+    assert_eq!(network.initial_state(), model.initial_state());
+    for (beta, gamma) in [(1e-4, 0.01), (3e-3, 0.2)] {
+        for m in [&mut model as &mut dyn Model, &mut network] {
+            m.set("beta", beta).expect("beta in bounds");
+            m.set("gamma", gamma).expect("gamma in bounds");
+        }
+        for y in [[999.0, 1.0, 0.0], [500.0, 300.0, 200.0], [0.0, 10.0, 990.0]] {
+            let y = State::from_vec(y.to_vec());
+            let (expected, actual) = (model.rhs(0.0, &y), network.rhs(0.0, &y));
+            assert!(
+                (&expected - &actual).amax() < 1e-12 * (1.0 + expected.amax()),
+                "{} vs {}",
+                expected,
+                actual
+            );
+        }
+    }
+}
+
+#[test]
+fn parses_stoichiometry_and_rates() {
+    let network = network()
+        .reaction("dimerization", "2 A + B => 3 C @ k")
+        .and_then(|n| n.reaction("birth", "0 => A @ k"))
+        .and_then(|n| n.reaction("activation", "∅ => C @ hill_act(beta, A, K, n)"))
+        .and_then(|n| n.reaction("repression", "B => @ hill_rep(beta, A, K, n)"))
+        .expect("valid reactions");
+    let dimerization = &network.reactions[0];
+    assert_eq!(dimerization.reactants, vec![(0, 2), (1, 1)]);
+    assert_eq!(dimerization.products, vec![(2, 3)]);
+    assert_eq!(dimerization.rate, RateLaw::MassAction { k: 0 });
+    assert!(network.reactions[1].reactants.is_empty());
+    assert_eq!(
+        network.reactions[2].rate,
+        RateLaw::HillActivation {
+            beta: 1,
+            species: 0,
+            k: 2,
+            n: 3
+        }
+    );
+    assert!(network.reactions[3].products.is_empty());
+    assert_eq!(
+        network.stoichiometry(),
+        vec![
+            vec![-2, -1, 3],
+            vec![1, 0, 0],
+            vec![0, 0, 1],
+            vec![0, -1, 0]
+        ]
+    );
+
+    // A = 4 and B = 2, so (A / K)^n = 4.
+    let x = [4.0, 2.0, 0.0];
+    assert_eq!(network.rate(0, &x), 0.5 * 16.0 * 2.0);
+    assert_eq!(network.propensity(0, &x), 0.5 * 4.0 * 3.0 * 2.0);
+    assert_eq!(network.rate(1, &x), 0.5);
+    assert!((network.rate(2, &x) - 3.0 * 4.0 / 5.0).abs() < 1e-12);
+    assert!((network.rate(3, &x) - 3.0 / 5.0).abs() < 1e-12);
+    assert_eq!(
+        network.rhs(0.0, &State::from_vec(x.to_vec()))[1],
+        -16.0 - 0.6
+    );
+}
+
+#[test]
+fn rejects_malformed_reactions() {
+    for equation in [
+        "A => B",
+        "A + B @ k",
+        "A => D @ k",
+        "A B => C @ k",
+        "A => C @ hill_act(beta, A, K)",
+        "A => C @ hill_up(beta, A, K, n)",
+        "A => C @ hill_act(beta, D, K, n)",
+    ] {
+        assert!(
+            matches!(network().reaction("r", equation), Err(Error::Parse(_))),
+            "{}",
+            equation
+        );
+    }
+    for equation in ["A => B @ kk", "A => C @ hill_rep(beta, A, K, m)"] {
+        assert!(
+            matches!(
+                network().reaction("r", equation),
+                Err(Error::UnknownParameter(_))
+            ),
+            "{}",
+            equation
+        );
+    }
+}
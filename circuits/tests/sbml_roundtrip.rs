@@ -0,0 +1,116 @@
+use circuits::models::{Autorepressor, CoffeeRust, NegativeAutoregulation};
+use circuits::sbml::{self, ToSbml};
+use circuits::signal::Signal;
+use circuits::{Error, Model, State};
+use ode_solvers::Dopri5;
+
+fn trajectory<M: Model>(model: M, t_end: f64) -> Vec<State> {
+    let y0 = model.initial_state();
+    let mut stepper = Dopri5::new(model, 0.0, t_end, t_end / 50.0, y0, 1e-10, 1e-10);
+    stepper.integrate().expect("failed integration");
+    stepper.y_out().clone()
+}
+
+fn assert_roundtrip<M: Model + ToSbml + Clone>(model: M, t_end: f64) {
+    let xml = sbml::write(&model.to_sbml());
+    let imported = sbml::read(&xml).expect("exported SBML should be readable");
+    assert_eq!(imported.species(), model.species());
+
+    let expected = trajectory(model, t_end);
+    let actual = trajectory(imported, t_end);
+    assert_eq!(expected.len(), actual.len());
+    for (e, a) in expected.iter().zip(actual.iter()) {
+        for (x, y) in e.iter().zip(a.iter()) {
+            assert!(
+                (x - y).abs() <= 1e-6 * (1.0 + x.abs()),
+                "{} differs from {}",
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn autorepressor_roundtrip() {
+    assert_roundtrip(Autorepressor::default(), 10.0);
+}
+
+#[test]
+fn autorepressor_pulse_train_roundtrip() {
+    let model = Autorepressor {
+        s: Signal::PulseTrain {
+            t_on: 1.0,
+            width: 2.0,
+            period: 5.0,
+            low: 0.0,
+            high: 100.0,
+        },
+        ..Default::default()
+    };
+    assert_roundtrip(model, 20.0);
+}
+
+#[test]
+fn negative_autoregulation_roundtrip() {
+    assert_roundtrip(NegativeAutoregulation::default(), 25.0);
+}
+
+#[test]
+fn coffee_rust_roundtrip() {
+    assert_roundtrip(CoffeeRust::default(), 700.0);
+}
+
+#[test]
+fn operators_with_wrong_arity_are_rejected() {
+    let xml = sbml::write(&NegativeAutoregulation::default().to_sbml());
+    let law = "<apply><times/><ci>gamma_m</ci><ci>m</ci></apply>";
+    assert!(xml.contains(law));
+    for malformed in [
+        "<apply><divide/><ci>m</ci></apply>",
+        "<apply><power/><ci>m</ci></apply>",
+        "<apply><minus/></apply>",
+        "<apply><exp/><ci>m</ci><ci>gamma_m</ci></apply>",
+        "<apply><lt/><ci>m</ci></apply>",
+        "<apply><power/><logbase><cn>2</cn></logbase><ci>m</ci><ci>n</ci></apply>",
+        "<apply><minus/><logbase><cn>2</cn></logbase><ci>m</ci></apply>",
+        "<apply><log/><degree><cn>2</cn></degree><ci>m</ci></apply>",
+        "<apply><root/><degree><cn>3</cn></degree><ci>m</ci><ci>gamma_m</ci></apply>",
+        "<apply><root/><ci>m</ci><ci>gamma_m</ci></apply>",
+        "<apply><log/><cn>2</cn><ci>m</ci></apply>",
+    ] {
+        let result = sbml::read(&xml.replace(law, malformed));
+        assert!(matches!(result, Err(Error::Parse(_))), "{}", malformed);
+    }
+}
+
+#[test]
+fn roots_and_logarithms_take_their_qualifier() {
+    let model = NegativeAutoregulation::default();
+    let xml = sbml::write(&model.to_sbml());
+    let law = "<apply><times/><ci>gamma_m</ci><ci>m</ci></apply>";
+    let y = State::from_vec(vec![2.0, 5.0]);
+    let expected = model.rhs(0.0, &y);
+    // Each factor equals 3, so every law degrades m at rate 3 instead of gamma_m.
+    for three in [
+        "<apply><root/><degree><cn>3</cn></degree><cn>27</cn></apply>",
+        "<apply><root/><cn>9</cn></apply>",
+        "<apply><log/><logbase><cn>2</cn></logbase><cn>8</cn></apply>",
+        "<apply><log/><cn>1000</cn></apply>",
+    ] {
+        let replaced = format!("<apply><times/>{}<ci>m</ci></apply>", three);
+        let imported = sbml::read(&xml.replace(law, &replaced)).expect("valid qualifier");
+        let actual = imported.rhs(0.0, &y);
+        assert!(
+            (actual[0] - (expected[0] + (model.gamma_m - 3.0) * y[0])).abs() < 1e-9,
+            "{}",
+            three
+        );
+    }
+
+    let call = "<apply><ci>f</ci><degree><cn>2</cn></degree><ci>m</ci></apply>";
+    assert!(matches!(
+        sbml::read(&xml.replace(law, call)),
+        Err(Error::Parse(message)) if message.contains("function call")
+    ));
+}
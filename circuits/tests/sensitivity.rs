@@ -0,0 +1,72 @@
+use circuits::models::Unregulated;
+use circuits::sensitivity::{sensitivities, Method};
+
+const PARAMETERS: [&str; 3] = ["beta_m", "gamma_m", "beta_p"];
+
+/// With `m(t) = beta_m / gamma_m (1 - exp(-gamma_m t))` the sensitivities of the mRNA are
+/// known in closed form, and both methods have to agree with them and with each other.
+#[test]
+fn forward_and_finite_differences_match_the_unregulated_solution() {
+    let model = Unregulated::default();
+    let (beta, gamma) = (model.beta_m, model.gamma_m);
+    let forward = sensitivities(&model, &PARAMETERS, 20.0, 0.5, Method::Forward)
+        .expect("failed forward sensitivities");
+    let differences = sensitivities(&model, &PARAMETERS, 20.0, 0.5, Method::FiniteDifference)
+        .expect("failed finite differences");
+    assert_eq!(forward.t, differences.t);
+
+    for ((t, s), d) in forward
+        .t
+        .iter()
+        .zip(&forward.coefficients)
+        .zip(&differences.coefficients)
+    {
+        let decay = (-gamma * t).exp();
+        let dm_dbeta = (1.0 - decay) / gamma;
+        let dm_dgamma = -beta / (gamma * gamma) * (1.0 - decay) + beta / gamma * t * decay;
+        for (sensitivity, method) in [(s, "forward"), (d, "finite differences")] {
+            assert!(
+                (sensitivity[(0, 0)] - dm_dbeta).abs() < 1e-5,
+                "{} at {}",
+                method,
+                t
+            );
+            assert!(
+                (sensitivity[(0, 1)] - dm_dgamma).abs() < 1e-5,
+                "{} at {}",
+                method,
+                t
+            );
+            // The mRNA does not depend on the translation rate.
+            assert!(sensitivity[(0, 2)].abs() < 1e-5, "{} at {}", method, t);
+        }
+        assert!(
+            (s - d).amax() < 1e-5 * (1.0 + s.amax()),
+            "{} vs {} at {}",
+            s,
+            d,
+            t
+        );
+    }
+}
+
+/// Both species are proportional to `beta_m` and the protein to `beta_p`, so their
+/// logarithmic sensitivities are one once they are present.
+#[test]
+fn normalized_sensitivities_of_proportional_rates() {
+    let model = Unregulated::default();
+    let result = sensitivities(&model, &PARAMETERS, 20.0, 0.5, Method::Forward)
+        .expect("failed forward sensitivities");
+    let normalized = result.normalized();
+    // Nothing is present at the start.
+    assert_eq!(normalized[0].amax(), 0.0);
+    for (t, s) in result.t.iter().zip(&normalized).skip(1) {
+        for (i, j) in [(0, 0), (1, 0), (1, 2)] {
+            assert!((s[(i, j)] - 1.0).abs() < 1e-4, "{} at {}", s, t);
+        }
+    }
+    let columns = result.columns(true);
+    assert_eq!(columns[0].0, "t");
+    assert_eq!(columns[1].0, "dlogm/dlogbeta_m");
+    assert_eq!(columns.len(), 1 + 2 * PARAMETERS.len());
+}
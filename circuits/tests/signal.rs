@@ -0,0 +1,77 @@
+use circuits::signal::Signal;
+use circuits::Error;
+
+#[test]
+fn table_interpolates_between_breakpoints() {
+    let signal = Signal::from_csv_str("t,s\n\n4,0\n0,1\n2,3\n").expect("valid table");
+    assert_eq!(
+        signal,
+        Signal::Table(vec![(0.0, 1.0), (2.0, 3.0), (4.0, 0.0)])
+    );
+    for (t, expected) in [
+        // Held at the ends outside the table.
+        (-1.0, 1.0),
+        (0.0, 1.0),
+        (1.0, 2.0),
+        (2.0, 3.0),
+        (3.0, 1.5),
+        (4.0, 0.0),
+        (10.0, 0.0),
+    ] {
+        assert_eq!(signal.value(t), expected, "at {}", t);
+    }
+
+    for malformed in ["", "t,s\n", "0,1\n1,x", "t,s\n0,1\n1"] {
+        assert!(
+            matches!(Signal::from_csv_str(malformed), Err(Error::Parse(_))),
+            "{:?}",
+            malformed
+        );
+    }
+}
+
+#[test]
+fn shapes_switch_at_their_breakpoints() {
+    let step = Signal::step_on(2.0, 5.0);
+    assert_eq!([step.value(1.9), step.value(2.0)], [0.0, 5.0]);
+    let off = Signal::step_off(2.0, 5.0);
+    assert_eq!([off.value(1.9), off.value(2.0)], [5.0, 0.0]);
+
+    let pulse = Signal::Pulse {
+        t_on: 1.0,
+        t_off: 3.0,
+        low: 0.5,
+        high: 2.0,
+    };
+    let values = [0.0, 1.0, 2.0, 3.0, 4.0].map(|t| pulse.value(t));
+    assert_eq!(values, [0.5, 2.0, 2.0, 0.5, 0.5]);
+
+    let train = Signal::PulseTrain {
+        t_on: 1.0,
+        width: 1.0,
+        period: 3.0,
+        low: 0.0,
+        high: 1.0,
+    };
+    let values = [0.5, 1.0, 1.5, 2.0, 4.0, 4.5, 5.0, 7.0].map(|t| train.value(t));
+    assert_eq!(values, [0.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0]);
+
+    let ramp = Signal::Ramp {
+        t_start: 1.0,
+        t_end: 3.0,
+        from: 2.0,
+        to: 6.0,
+    };
+    let values = [0.0, 1.0, 1.5, 2.0, 3.0, 5.0].map(|t| ramp.value(t));
+    assert_eq!(values, [2.0, 2.0, 3.0, 4.0, 6.0, 6.0]);
+
+    let sine = Signal::Sine {
+        mean: 1.0,
+        amplitude: 2.0,
+        period: 4.0,
+        phase: 0.0,
+    };
+    assert!((sine.value(1.0) - 3.0).abs() < 1e-12);
+    assert!((sine.value(3.0) + 1.0).abs() < 1e-12);
+    assert_eq!(Signal::default().value(7.0), 0.0);
+}
@@ -0,0 +1,84 @@
+use circuits::models::{NegativeAutoregulation, ToggleSwitch};
+use circuits::stability::{fixed_points, Stability};
+use circuits::{Model, State};
+use nalgebra::Complex;
+
+/// Guesses on a grid over `[0, top]` in every coordinate.
+fn grid(top: &[f64]) -> Vec<State> {
+    let levels = [0.0, 0.01, 0.1, 0.5, 1.0];
+    let mut guesses = vec![State::zeros(top.len())];
+    for (i, top) in top.iter().enumerate() {
+        guesses = guesses
+            .iter()
+            .flat_map(|g| {
+                levels.iter().map(move |f| {
+                    let mut g = g.clone();
+                    g[i] = f * top;
+                    g
+                })
+            })
+            .collect();
+    }
+    guesses
+}
+
+#[test]
+fn toggle_switch_has_two_stable_nodes_and_a_saddle() {
+    let model = ToggleSwitch::default();
+    let mut points = fixed_points(&model, &grid(&[model.alpha1, model.alpha2]));
+    points.sort_by(|a, b| a.state[0].total_cmp(&b.state[0]));
+    let kinds: Vec<Stability> = points.iter().map(|p| p.stability).collect();
+    assert_eq!(
+        kinds,
+        [
+            Stability::StableNode,
+            Stability::Saddle,
+            Stability::StableNode
+        ]
+    );
+    for point in &points {
+        assert!(
+            model.rhs(0.0, &point.state).amax() < 1e-8,
+            "{}",
+            point.state
+        );
+    }
+    // The saddle has one unstable direction.
+    assert!(points[1].eigenvalues[0].re > 0.0 && points[1].eigenvalues[1].re < 0.0);
+}
+
+#[test]
+fn negative_autoregulation_has_one_stable_node() {
+    let model = NegativeAutoregulation::default();
+    let points = fixed_points(&model, &grid(&[10.0, 10.0]));
+    assert_eq!(points.len(), 1);
+    assert_eq!(points[0].stability, Stability::StableNode);
+    assert!(model.rhs(0.0, &points[0].state).amax() < 1e-8);
+}
+
+#[test]
+fn classifies_eigenvalues() {
+    let c = Complex::new;
+    for (eigenvalues, expected) in [
+        (vec![c(-1.0, 0.0), c(-2.0, 0.0)], Stability::StableNode),
+        (vec![c(-1.0, 2.0), c(-1.0, -2.0)], Stability::StableFocus),
+        (vec![c(1.0, 0.0), c(3.0, 0.0)], Stability::UnstableNode),
+        (vec![c(0.5, 1.0), c(0.5, -1.0)], Stability::UnstableFocus),
+        (vec![c(1.0, 0.0), c(-1.0, 0.0)], Stability::Saddle),
+        (
+            vec![c(0.0, 1.0), c(0.0, -1.0), c(-1.0, 0.0)],
+            Stability::HopfCandidate,
+        ),
+        (vec![c(0.0, 0.0), c(-1.0, 0.0)], Stability::NonHyperbolic),
+        (vec![c(1e-14, 0.0), c(-1.0, 0.0)], Stability::NonHyperbolic),
+    ] {
+        assert_eq!(
+            Stability::classify(&eigenvalues),
+            expected,
+            "{:?}",
+            eigenvalues
+        );
+    }
+    assert!(Stability::StableFocus.is_stable());
+    assert!(!Stability::Saddle.is_stable());
+}
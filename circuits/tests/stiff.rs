@@ -0,0 +1,122 @@
+use circuits::stiff::{Method, Stiff};
+use circuits::{Error, State, Time};
+use ode_solvers::System;
+
+/// Robertson's chemical kinetics, the classic stiff test problem.
+struct Robertson;
+
+impl System<f64, State> for Robertson {
+    fn system(&self, _t: Time, y: &State, dy: &mut State) {
+        dy[0] = -0.04 * y[0] + 1e4 * y[1] * y[2];
+        dy[1] = 0.04 * y[0] - 1e4 * y[1] * y[2] - 3e7 * y[1] * y[1];
+        dy[2] = 3e7 * y[1] * y[1];
+    }
+}
+
+/// Relaxation towards `cos t`, fast until `t = 5` and slow afterwards.
+struct Relaxation;
+
+impl System<f64, State> for Relaxation {
+    fn system(&self, t: Time, y: &State, dy: &mut State) {
+        let rate = if t < 5.0 { 1e4 } else { 1.0 };
+        dy[0] = -rate * (y[0] - t.cos());
+    }
+}
+
+/// Harmonic oscillator, not stiff.
+struct Oscillator;
+
+impl System<f64, State> for Oscillator {
+    fn system(&self, _t: Time, y: &State, dy: &mut State) {
+        dy[0] = y[1];
+        dy[1] = -y[0];
+    }
+}
+
+fn solve<S: System<f64, State>>(system: S, y0: State, t_end: f64, method: Method) -> Stiff<S> {
+    let mut stepper = Stiff::new(system, 0.0, t_end, 0.0, y0, 1e-6, 1e-10).method(method);
+    stepper.integrate().expect("failed integration");
+    stepper
+}
+
+#[test]
+fn robertson_at_40() {
+    // Reference solution of Hairer and Wanner.
+    let expected = [0.7158271, 9.185535e-6, 0.2841637];
+    for method in [Method::Auto, Method::Rosenbrock, Method::Bdf] {
+        let y0 = State::from_vec(vec![1.0, 0.0, 0.0]);
+        let stepper = solve(Robertson, y0, 40.0, method);
+        let y = stepper.y_out().last().expect("final state");
+        for (actual, expected) in y.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-3 * expected,
+                "{:?}: {} against {}",
+                method,
+                actual,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn auto_switches_with_stiffness() {
+    let stepper = solve(
+        Robertson,
+        State::from_vec(vec![1.0, 0.0, 0.0]),
+        40.0,
+        Method::Auto,
+    );
+    let methods: Vec<Method> = stepper.segments().iter().map(|(_, m)| *m).collect();
+    assert_eq!(methods[0], Method::DormandPrince);
+    assert!(methods.contains(&Method::Rosenbrock), "{:?}", methods);
+
+    let stepper = solve(Relaxation, State::from_vec(vec![0.0]), 30.0, Method::Auto);
+    let segments = stepper.segments();
+    assert!(
+        segments
+            .iter()
+            .any(|(t, m)| *m == Method::Rosenbrock && *t < 5.0),
+        "{:?}",
+        segments
+    );
+    let (t_last, last) = segments[segments.len() - 1];
+    assert!(
+        t_last > 5.0 && last == Method::DormandPrince,
+        "{:?}",
+        segments
+    );
+
+    let stepper = solve(
+        Oscillator,
+        State::from_vec(vec![1.0, 0.0]),
+        30.0,
+        Method::Auto,
+    );
+    assert_eq!(stepper.segments(), &vec![(0.0, Method::DormandPrince)]);
+}
+
+#[test]
+fn backward_integration_is_rejected() {
+    for method in [
+        Method::Auto,
+        Method::DormandPrince,
+        Method::Rosenbrock,
+        Method::Bdf,
+    ] {
+        let mut stepper = Stiff::new(
+            Oscillator,
+            1.0,
+            -2.0,
+            0.0,
+            State::from_vec(vec![1.0, 0.0]),
+            1e-6,
+            1e-6,
+        )
+        .method(method);
+        assert!(matches!(
+            stepper.integrate(),
+            Err(Error::InvalidRange { .. })
+        ));
+    }
+}
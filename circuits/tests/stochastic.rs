@@ -0,0 +1,59 @@
+use circuits::reactions::ReactionNetwork;
+use circuits::stochastic::{Method, Replicates};
+
+/// Births at rate 10 and deaths at rate 0.1 per molecule, Poisson with mean 100 at steady
+/// state.
+fn birth_death() -> ReactionNetwork {
+    ReactionNetwork::new("birth-death")
+        .species("X", 0.0)
+        .parameter("birth", 10.0)
+        .parameter("death", 0.1)
+        .reaction("birth", "0 => X @ birth")
+        .and_then(|n| n.reaction("death", "X => 0 @ death"))
+        .expect("valid reactions")
+}
+
+#[test]
+fn direct_and_next_reaction_agree() {
+    let network = birth_death();
+    let times: Vec<f64> = (0..=8).map(|k| 5.0 * k as f64).collect();
+    let n = 500;
+    let direct = Replicates::run(&network, Method::Direct, &times, n, 1);
+    let next = Replicates::run(&network, Method::NextReaction, &times, n, 2);
+    let (mean_d, mean_n) = (direct.mean(), next.mean());
+    let (var_d, var_n) = (direct.variance(), next.variance());
+    for k in 1..times.len() {
+        let expected = 100.0 * (1.0 - (-0.1 * times[k]).exp());
+        // Poisson at all times: the variance equals the mean.
+        for (mean, variance) in [(mean_d[k][0], var_d[k][0]), (mean_n[k][0], var_n[k][0])] {
+            assert!(
+                (mean - expected).abs() < 4.0 * (expected / n as f64).sqrt(),
+                "t = {}: mean {} against {}",
+                times[k],
+                mean,
+                expected
+            );
+            assert!(
+                (variance / expected - 1.0).abs() < 0.25,
+                "t = {}: variance {} against {}",
+                times[k],
+                variance,
+                expected
+            );
+        }
+        let error = (var_d[k][0] / n as f64 + var_n[k][0] / n as f64).sqrt();
+        assert!(
+            (mean_d[k][0] - mean_n[k][0]).abs() < 4.0 * error,
+            "t = {}: {} against {}",
+            times[k],
+            mean_d[k][0],
+            mean_n[k][0]
+        );
+    }
+    let median = next.quantile(0.5);
+    assert!(
+        (median[8][0] - mean_n[8][0]).abs() < 5.0,
+        "{}",
+        median[8][0]
+    );
+}
@@ -0,0 +1,52 @@
+use circuits::models::NegativeAutoregulation;
+use circuits::sweep::Sweep;
+use circuits::trajectory::uniform;
+use circuits::Error;
+
+#[test]
+fn sets_need_one_value_per_parameter() {
+    let sweep = Sweep::new(&["beta_m", "gamma_p"]);
+    assert!(matches!(
+        sweep.clone().set(&[1.0]),
+        Err(Error::Dimension {
+            expected: 2,
+            found: 1
+        })
+    ));
+    assert_eq!(
+        sweep.set(&[1.0, 0.1, 2.0]),
+        Err(Error::Dimension {
+            expected: 2,
+            found: 3
+        })
+    );
+}
+
+#[test]
+fn runs_every_set() -> Result<(), Error> {
+    let sweep = Sweep::new(&["beta_m", "gamma_p"])
+        .set(&[1.0, 0.1])?
+        .set(&[2.0, 0.2])?;
+    let runs = sweep.run(&NegativeAutoregulation::default(), &uniform(0.0, 10.0, 11))?;
+    assert_eq!(runs.runs.len(), 2);
+    assert_eq!(runs.runs[1].values, vec![2.0, 0.2]);
+    assert!(runs.runs.iter().all(|run| run.states.len() == 11));
+    Ok(())
+}
+
+#[cfg(feature = "polars")]
+#[test]
+fn long_format_data_frame() -> Result<(), Box<dyn std::error::Error>> {
+    let sweep = Sweep::grid(&[("beta_m", vec![1.0, 2.0]), ("gamma_p", vec![0.1])]);
+    let runs = sweep.run(&NegativeAutoregulation::default(), &uniform(0.0, 10.0, 11))?;
+    let df = runs.data_frame()?;
+    assert_eq!(
+        df.get_column_names_str(),
+        ["sweep", "beta_m", "gamma_p", "t", "species", "value"]
+    );
+    assert_eq!(df.height(), 2 * 11 * runs.species.len());
+    let last = runs.runs[1].states[10][runs.species.len() - 1];
+    let value = df.column("value")?.f64()?.get(df.height() - 1);
+    assert_eq!(value, Some(last));
+    Ok(())
+}
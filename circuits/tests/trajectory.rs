@@ -0,0 +1,71 @@
+use circuits::trajectory::{logarithmic, uniform, Trajectory};
+use circuits::{Error, State, Time};
+use ode_solvers::{Dopri5, OutputType, System};
+
+/// `y' = -y`, so `y = exp(-t)` from one.
+struct Decay;
+
+impl System<f64, State> for Decay {
+    fn system(&self, _t: Time, y: &State, dy: &mut State) {
+        dy[0] = -y[0];
+    }
+}
+
+#[test]
+fn resamples_between_the_steps() {
+    let mut stepper = Dopri5::new(
+        Decay,
+        0.0,
+        10.0,
+        0.0,
+        State::from_vec(vec![1.0]),
+        1e-8,
+        1e-8,
+    );
+    stepper.set_output(OutputType::Sparse);
+    stepper.integrate().expect("failed integration");
+    let trajectory = Trajectory::from_steps(&Decay, stepper.x_out(), stepper.y_out());
+    assert_eq!(trajectory.span(), Some((0.0, 10.0)));
+    let steps = trajectory.times().len();
+    assert!(steps > 5 && steps < 100, "{} steps", steps);
+
+    // Midpoints of the steps are as far as possible from the output of the stepper.
+    let midpoints: Vec<f64> = trajectory
+        .times()
+        .windows(2)
+        .map(|w| 0.5 * (w[0] + w[1]))
+        .collect();
+    for (t, y) in midpoints.iter().zip(trajectory.sample(&midpoints)) {
+        assert!((y[0] - (-t).exp()).abs() < 1e-5, "{} at {}", y[0], t);
+    }
+    for (t, y) in trajectory.times().iter().zip(trajectory.states()) {
+        assert!((trajectory.at(*t) - y).amax() < 1e-15);
+    }
+    // Held at the ends outside the span.
+    assert_eq!(trajectory.at(-1.0)[0], 1.0);
+    assert_eq!(trajectory.at(11.0), trajectory.at(10.0));
+    let series = trajectory.series(0, &[0.0, 10.0]);
+    assert_eq!(series[0], vec![0.0, 1.0]);
+    assert_eq!(series[1][0], 10.0);
+}
+
+#[test]
+fn uniform_and_logarithmic_grids() {
+    assert_eq!(uniform(0.0, 1.0, 5), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+    assert_eq!(uniform(2.0, 3.0, 1), vec![2.0]);
+    assert!(uniform(0.0, 1.0, 0).is_empty());
+
+    let grid = logarithmic(0.01, 100.0, 5).expect("positive range");
+    assert_eq!(grid.len(), 5);
+    assert_eq!((grid[0], grid[4]), (0.01, 100.0));
+    for (t, expected) in grid.iter().zip([0.01, 0.1, 1.0, 10.0, 100.0]) {
+        assert!((t / expected - 1.0).abs() < 1e-12, "{} vs {}", t, expected);
+    }
+    assert_eq!(logarithmic(3.0, 30.0, 1), Ok(vec![3.0]));
+    for (start, end) in [(0.0, 1.0), (-1.0, 1.0), (1.0, f64::NAN)] {
+        assert!(matches!(
+            logarithmic(start, end, 3),
+            Err(Error::InvalidRange { .. })
+        ));
+    }
+}